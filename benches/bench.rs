@@ -1,14 +1,82 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::cell::Cell;
+use std::sync::{mpsc, Arc};
 use std::thread;
-use switflake::Switflake;
+use std::time::{Duration, Instant};
+use switflake::{
+    decompose, decompose_slice, encode_base62, Clock, Id, Layout, PoolOrdering, SharedSwitflake,
+    SharedTickClock, Switflake, SystemClock, ThreadIdPool,
+};
 
 fn bench_generate_id_single_thread(c: &mut Criterion) {
     let mut swit = Switflake::new(1).expect("Failed to create Switflake");
     c.bench_function("generate_id_single_thread", |b| {
-        b.iter(|| swit.generate_id().expect("Failed to generate ID"))
+        b.iter(|| black_box(swit.generate_id().expect("Failed to generate ID")))
     });
 }
 
+// A clock moving on to the next millisecond every `per_tick` readings, so a
+// generator reading it once per ID gets at most that many IDs a tick.
+struct TickingClock {
+    reads: Cell<u64>,
+    per_tick: u64,
+}
+
+impl Clock for TickingClock {
+    fn now_millis(&self) -> Result<u64, switflake::SwitflakeError> {
+        let reads = self.reads.get();
+        self.reads.set(reads + 1);
+        Ok(1_700_000_000_000 + reads / self.per_tick)
+    }
+}
+
+// Theoretical ceiling: generation without its checks, on a clock that keeps
+// every tick within the counter so the checks could never have failed.
+fn bench_generate_id_unchecked(c: &mut Criterion) {
+    let clock = TickingClock {
+        reads: Cell::new(0),
+        per_tick: Layout::default().counter_mask(),
+    };
+    let mut swit = Switflake::builder()
+        .node_id(1)
+        .clock(clock)
+        .build()
+        .expect("Failed to create Switflake");
+    c.bench_function("generate_id_unchecked_single_thread", |b| {
+        // SAFETY: only generate_id_unchecked reads the clock once the
+        // generator is built, once per ID, and the clock never goes back
+        // and moves to the next millisecond every counter_mask() readings,
+        // so no tick is asked for more IDs than its counter holds; the
+        // timestamps stay near 2023, well within the 41-bit field.
+        b.iter(|| black_box(unsafe { swit.generate_id_unchecked() }))
+    });
+}
+
+// The two halves of generate_id: is it the clock or the arithmetic?
+#[cfg(feature = "bench-internals")]
+fn bench_generate_id_read_clock(c: &mut Criterion) {
+    let swit = Switflake::new(1).expect("Failed to create Switflake");
+    c.bench_function("generate_id_read_clock", |b| {
+        b.iter(|| black_box(swit.bench_read_clock().expect("Failed to read clock")))
+    });
+}
+
+#[cfg(feature = "bench-internals")]
+fn bench_generate_id_pack(c: &mut Criterion) {
+    let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+    swit.generate_id().expect("Failed to generate ID");
+    c.bench_function("generate_id_pack", |b| {
+        b.iter(|| black_box(black_box(&swit).bench_pack()))
+    });
+}
+
+// Without the feature the split isn't reachable; keep the group list fixed.
+#[cfg(not(feature = "bench-internals"))]
+fn bench_generate_id_read_clock(_: &mut Criterion) {}
+
+#[cfg(not(feature = "bench-internals"))]
+fn bench_generate_id_pack(_: &mut Criterion) {}
+
 fn bench_generate_id_multi_thread(c: &mut Criterion) {
     c.bench_function("generate_id_multi_thread", |b| {
         b.iter(|| {
@@ -28,9 +96,293 @@ fn bench_generate_id_multi_thread(c: &mut Criterion) {
     });
 }
 
+/// Long-lived threads that each run `iters` calls of their job per sample,
+/// so thread creation stays out of the measurement.
+struct Workers {
+    requests: Vec<mpsc::Sender<u64>>,
+    done: mpsc::Receiver<()>,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl Workers {
+    fn spawn<F>(count: usize, mut make_job: impl FnMut() -> F) -> Self
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let (done_tx, done) = mpsc::channel();
+        let mut requests = Vec::new();
+        let mut handles = Vec::new();
+        for _ in 0..count {
+            let (tx, rx) = mpsc::channel::<u64>();
+            let done_tx = done_tx.clone();
+            let mut job = make_job();
+            handles.push(thread::spawn(move || {
+                for iters in rx {
+                    for _ in 0..iters {
+                        job();
+                    }
+                    done_tx.send(()).expect("Bench thread gone");
+                }
+            }));
+            requests.push(tx);
+        }
+        Workers {
+            requests,
+            done,
+            handles,
+        }
+    }
+
+    fn run(&self, iters: u64) -> Duration {
+        let start = Instant::now();
+        for tx in &self.requests {
+            tx.send(iters).expect("Worker gone");
+        }
+        for _ in &self.requests {
+            self.done.recv().expect("Worker gone");
+        }
+        start.elapsed()
+    }
+
+    fn join(self) {
+        drop(self.requests);
+        for handle in self.handles {
+            handle.join().expect("Thread join failed");
+        }
+    }
+}
+
+// Steady-state throughput of 8 threads, each with its own generator.
+fn bench_generate_id_owned_per_thread(c: &mut Criterion) {
+    const THREADS: usize = 8;
+    let workers = Workers::spawn(THREADS, || {
+        let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+        move || {
+            black_box(swit.generate_id_blocking().expect("Failed to generate ID"));
+        }
+    });
+    let mut group = c.benchmark_group("steady_state");
+    group.throughput(Throughput::Elements(THREADS as u64));
+    group.bench_function("owned_per_thread_8", |b| {
+        b.iter_custom(|iters| workers.run(iters))
+    });
+    group.finish();
+    workers.join();
+}
+
+// A clock running a thousand times fast, so the 255 IDs a tick holds don't
+// cap the contended throughput being measured.
+#[derive(Clone, Copy)]
+struct FastClock(Instant);
+
+impl Clock for FastClock {
+    fn now_millis(&self) -> Result<u64, switflake::SwitflakeError> {
+        Ok(1_700_000_000_000 + self.0.elapsed().as_micros() as u64)
+    }
+}
+
+// One SharedSwitflake shared by N threads, all taking counters from the
+// same sequence one at a time through a compare-and-swap, or in reserved
+// blocks of 16 through a fetch_add.
+fn bench_generate_id_shared_contended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shared_contended");
+    for threads in [2, 4, 8] {
+        for (name, block) in [("cas", 0), ("reserved", 16)] {
+            let shared = Arc::new(
+                SharedSwitflake::with_clock(1, FastClock(Instant::now()))
+                    .expect("Failed to create SharedSwitflake")
+                    .with_reservations(block),
+            );
+            let workers = Workers::spawn(threads, || {
+                let shared = shared.clone();
+                move || {
+                    black_box(
+                        shared
+                            .generate_id_blocking()
+                            .expect("Failed to generate ID"),
+                    );
+                }
+            });
+            group.throughput(Throughput::Elements(threads as u64));
+            group.bench_function(format!("{}_{}", name, threads), |b| {
+                b.iter_custom(|iters| workers.run(iters))
+            });
+            workers.join();
+        }
+    }
+    group.finish();
+}
+
+fn bench_new_drop_contended(c: &mut Criterion) {
+    c.bench_function("new_drop_32_threads", |b| {
+        b.iter(|| {
+            let handles: Vec<_> = (0..32)
+                .map(|_| {
+                    thread::spawn(|| {
+                        for _ in 0..64 {
+                            // 풀이 가득 차면 실패하는 것도 측정 대상
+                            let _ = black_box(Switflake::new(1));
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().expect("Thread join failed");
+            }
+        })
+    });
+}
+
+fn bench_pool_ordering(c: &mut Criterion) {
+    static SEQ_CST: ThreadIdPool = ThreadIdPool::new().with_ordering(PoolOrdering::SeqCst);
+    static ACQ_REL: ThreadIdPool = ThreadIdPool::new().with_ordering(PoolOrdering::AcqRel);
+    let mut group = c.benchmark_group("pool_ordering_new_drop_8_threads");
+    for (name, pool) in [("seq_cst", &SEQ_CST), ("acq_rel", &ACQ_REL)] {
+        let workers = Workers::spawn(8, || {
+            move || {
+                // 풀이 가득 차면 실패하는 것도 측정 대상
+                let _ = black_box(Switflake::builder().pool(pool).build());
+            }
+        });
+        group.bench_function(name, |b| b.iter_custom(|iters| workers.run(iters)));
+        workers.join();
+    }
+    group.finish();
+}
+
+fn bench_shared_tick_clock(c: &mut Criterion) {
+    // 생성기 8개가 같은 틱에 시계를 읽는 상황
+    const GENERATORS: usize = 8;
+    let shared = SharedTickClock::new();
+    let clocks: Vec<SharedTickClock> = (0..GENERATORS).map(|_| shared.clone()).collect();
+    let mut group = c.benchmark_group("eight_generator_clock_reads");
+    group.throughput(Throughput::Elements(GENERATORS as u64));
+    group.bench_function("own_clock_reads", |b| {
+        b.iter(|| {
+            for _ in 0..GENERATORS {
+                black_box(SystemClock.now_millis().expect("Clock before Unix epoch"));
+            }
+        })
+    });
+    group.bench_function("shared_tick_clock", |b| {
+        b.iter(|| {
+            for clock in &clocks {
+                black_box(clock.now_millis().expect("Clock before Unix epoch"));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_parse_id(c: &mut Criterion) {
+    let id = 7_159_857_416_126_464_001u64;
+    let decimal = id.to_string();
+    let base62 = encode_base62(id);
+    let mut group = c.benchmark_group("parse_id");
+    group.bench_function("decimal_fast_path", |b| {
+        b.iter(|| Id::try_from(black_box(decimal.as_str())))
+    });
+    group.bench_function("base62_table_path", |b| {
+        b.iter(|| Id::try_from(black_box(base62.as_str())))
+    });
+    group.finish();
+}
+
+// The hot path used to mask every timestamp; it now range-checks instead.
+fn bench_timestamp_field(c: &mut Criterion) {
+    let layout = Layout::default();
+    let timestamps: Vec<u64> = (0..1024).map(|i| 1_700_000_000_000 + i).collect();
+    let mut group = c.benchmark_group("timestamp_field");
+    group.bench_function("masked", |b| {
+        b.iter(|| {
+            timestamps.iter().fold(0u64, |acc, &t| {
+                acc ^ ((black_box(t) & layout.timestamp_mask()) << layout.timestamp_shift)
+            })
+        })
+    });
+    group.bench_function("range_checked", |b| {
+        b.iter(|| {
+            timestamps.iter().fold(0u64, |acc, &t| {
+                let t = black_box(t);
+                if t > layout.timestamp_mask() {
+                    return acc;
+                }
+                acc ^ (t << layout.timestamp_shift)
+            })
+        })
+    });
+    group.finish();
+}
+
+// Same-tick generation used to pack every field; it now ORs the counter
+// into a prefix packed once per tick.
+fn bench_same_tick_pack(c: &mut Criterion) {
+    let layout = Layout::default();
+    let (timestamp, node_id, thread_id) = (1_700_000_000_000u64, 42u64, 5u64);
+    let mut group = c.benchmark_group("same_tick_pack");
+    group.bench_function("all_fields", |b| {
+        b.iter(|| {
+            (0..255u64).fold(0u64, |acc, counter| {
+                let sequence = (thread_id << layout.counter_bits) | black_box(counter);
+                acc ^ (black_box(timestamp) << layout.timestamp_shift)
+                    | (black_box(node_id) << layout.node_shift)
+                    | ((sequence & layout.sequence_mask()) << layout.sequence_shift)
+            })
+        })
+    });
+    group.bench_function("cached_prefix", |b| {
+        let prefix = (timestamp << layout.timestamp_shift)
+            | (node_id << layout.node_shift)
+            | (thread_id << (layout.counter_bits + layout.sequence_shift));
+        b.iter(|| {
+            (0..255u64).fold(0u64, |acc, counter| {
+                acc ^ (black_box(prefix) | black_box(counter) << layout.sequence_shift)
+            })
+        })
+    });
+    group.finish();
+}
+
+fn bench_decompose_slice(c: &mut Criterion) {
+    let layout = Layout::default();
+    let ids: Vec<u64> = (0..4096u64)
+        .map(|i| ((1_700_000_000_000 + i / 64) << 23) | ((i * 0x9E37) & 0x7F_FFFF))
+        .collect();
+    let mut out = vec![decompose(0, &layout); ids.len()];
+    let mut group = c.benchmark_group("decompose_4096");
+    group.throughput(Throughput::Elements(ids.len() as u64));
+    group.bench_function("per_element", |b| {
+        b.iter(|| {
+            for (id, parts) in ids.iter().zip(out.iter_mut()) {
+                *parts = decompose(black_box(*id), &layout);
+            }
+            black_box(&out);
+        })
+    });
+    group.bench_function("slice", |b| {
+        b.iter(|| {
+            decompose_slice(black_box(&ids), &mut out, &layout);
+            black_box(&out);
+        })
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_generate_id_single_thread,
-    bench_generate_id_multi_thread
+    bench_generate_id_unchecked,
+    bench_generate_id_read_clock,
+    bench_generate_id_pack,
+    bench_generate_id_multi_thread,
+    bench_generate_id_owned_per_thread,
+    bench_generate_id_shared_contended,
+    bench_new_drop_contended,
+    bench_pool_ordering,
+    bench_shared_tick_clock,
+    bench_parse_id,
+    bench_timestamp_field,
+    bench_same_tick_pack,
+    bench_decompose_slice
 );
 criterion_main!(benches);
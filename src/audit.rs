@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{decompose, Layout};
+
+/// Findings of [`audit_ids`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Number of IDs inspected.
+    pub total: usize,
+    /// Number of IDs that repeat an earlier one.
+    pub duplicates: usize,
+    /// Smallest and largest timestamp seen for every node id.
+    pub node_timestamps: HashMap<u64, (u64, u64)>,
+    /// `(previous, current)` pairs from the same node where `current` is not
+    /// greater than the ID seen before it.
+    pub out_of_order: Vec<(u64, u64)>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool {
+        self.duplicates == 0 && self.out_of_order.is_empty()
+    }
+}
+
+/// Checks a stream of IDs, e.g. collected from a whole fleet, for duplicates
+/// and for IDs of one node that are not strictly increasing in stream order.
+///
+/// The stream is expected to list each node's IDs in the order they were
+/// generated.
+pub fn audit_ids(ids: impl Iterator<Item = u64>, layout: &Layout) -> AuditReport {
+    let mut report = AuditReport::default();
+    let mut seen = HashSet::new();
+    let mut last_per_node: HashMap<u64, u64> = HashMap::new();
+
+    for id in ids {
+        report.total += 1;
+        if !seen.insert(id) {
+            report.duplicates += 1;
+        }
+
+        let parts = decompose(id, layout);
+        report
+            .node_timestamps
+            .entry(parts.node_id)
+            .and_modify(|(min, max)| {
+                *min = (*min).min(parts.timestamp);
+                *max = (*max).max(parts.timestamp);
+            })
+            .or_insert((parts.timestamp, parts.timestamp));
+
+        if let Some(previous) = last_per_node.insert(parts.node_id, id) {
+            if id < previous {
+                report.out_of_order.push((previous, id));
+            }
+        }
+    }
+    report
+}
+
+/// How many of `ids` carry each node id.
+pub fn node_distribution(ids: &[u64], layout: &Layout) -> HashMap<u64, usize> {
+    let mut counts = HashMap::new();
+    for &id in ids {
+        *counts.entry(decompose(id, layout).node_id).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Fraction of `ids` minted with node id 0, or 0.0 for no IDs.
+///
+/// Node 0 is what a generator gets when nobody set its node id, so a fleet
+/// where it shows up much more often than one node's share usually has
+/// misconfigured hosts, all minting from the same node.
+pub fn suspicious_node_zero_ratio(ids: &[u64], layout: &Layout) -> f64 {
+    if ids.is_empty() {
+        return 0.0;
+    }
+    let zeros = ids
+        .iter()
+        .filter(|&&id| decompose(id, layout).node_id == 0)
+        .count();
+    zeros as f64 / ids.len() as f64
+}
+
+/// The first place where `ids` fails to strictly increase, as the index of
+/// the offending ID and the `(previous, offending)` pair, or `None` if the
+/// whole stream increases. Reads the stream once in constant memory, so it
+/// suits checking a stored, supposedly sorted column end to end.
+pub fn first_monotonicity_violation(ids: impl Iterator<Item = u64>) -> Option<(usize, u64, u64)> {
+    let mut prev = None;
+    for (index, id) in ids.enumerate() {
+        if let Some(prev) = prev.filter(|&prev| id <= prev) {
+            return Some((index, prev, id));
+        }
+        prev = Some(id);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_duplicate_and_out_of_order() {
+        let layout = Layout::default();
+        let id = |ts: u64, node: u64, counter: u16| layout.pack(ts, node, 0, counter);
+
+        let ids = [
+            id(100, 1, 0),
+            id(100, 2, 0),
+            id(101, 1, 0),
+            id(101, 2, 0),
+            id(101, 2, 0), // 중복
+            id(99, 1, 7),  // 노드 1 역순
+            id(105, 2, 1),
+        ];
+        let report = audit_ids(ids.iter().copied(), &layout);
+
+        assert_eq!(report.total, 7);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.out_of_order, vec![(id(101, 1, 0), id(99, 1, 7))]);
+        assert_eq!(report.node_timestamps[&1], (99, 101));
+        assert_eq!(report.node_timestamps[&2], (100, 105));
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_clean_stream() {
+        let layout = Layout::default();
+        let ids = (0..100u64).map(|i| layout.pack(1_000 + i / 10, i % 3, 0, (i % 10) as u16));
+        let report = audit_ids(ids, &layout);
+        assert!(report.is_clean());
+        assert_eq!(report.node_timestamps.len(), 3);
+    }
+
+    #[test]
+    fn test_first_monotonicity_violation() {
+        assert_eq!(first_monotonicity_violation([1, 2, 5, 9].into_iter()), None);
+        assert_eq!(first_monotonicity_violation(std::iter::empty()), None);
+        assert_eq!(
+            first_monotonicity_violation([1, 2, 5, 4, 3].into_iter()),
+            Some((3, 5, 4))
+        );
+        // 같은 값도 위반
+        assert_eq!(
+            first_monotonicity_violation([7, 7].into_iter()),
+            Some((1, 7, 7))
+        );
+    }
+
+    #[test]
+    fn test_node_distribution_and_zero_ratio() {
+        let layout = Layout::default();
+        // 노드 0 여섯 개, 노드 3 세 개, 노드 4095 하나
+        let ids: Vec<u64> = [0, 0, 3, 0, 4095, 3, 0, 0, 3, 0]
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| layout.pack(1_000 + i as u64, node, 5, 7))
+            .collect();
+
+        let distribution = node_distribution(&ids, &layout);
+        assert_eq!(distribution, HashMap::from([(0, 6), (3, 3), (4095, 1)]));
+        assert_eq!(suspicious_node_zero_ratio(&ids, &layout), 0.6);
+        assert_eq!(suspicious_node_zero_ratio(&ids[2..3], &layout), 0.0);
+        assert_eq!(suspicious_node_zero_ratio(&[], &layout), 0.0);
+    }
+}
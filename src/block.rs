@@ -0,0 +1,304 @@
+use std::time::Duration;
+
+use crate::layout::mask;
+use crate::{Clock, Switflake, SwitflakeError};
+
+/// How a blocking generator waits for the next millisecond once the current
+/// one's sequence is exhausted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockStrategy {
+    /// Busy-wait. Lowest latency, but burns a core for the whole wait.
+    #[default]
+    Spin,
+    /// Busy-wait only when the next millisecond is at most this far away,
+    /// and sleep through longer waits. Sleeps can overshoot by the OS timer
+    /// granularity, typically tens of microseconds.
+    SleepAbove(Duration),
+}
+
+/// What [`Switflake::generate_id`] does once the current millisecond's
+/// sequence is exhausted; set with
+/// [`SwitflakeBuilder::overflow_strategy`](crate::SwitflakeBuilder::overflow_strategy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowStrategy {
+    /// Fail with [`SwitflakeError::SequenceExhausted`].
+    #[default]
+    Fail,
+    /// Take another free slot of the generator's pool, if one can be had
+    /// with a single attempt, and carry on under its thread id with a fresh
+    /// counter. Borrowed slots are held, so no generator built meanwhile
+    /// can get them, until the millisecond is over. Fails as with
+    /// [`Fail`](Self::Fail) when the pool has no free slot, or the
+    /// generator took none.
+    BorrowSlot,
+}
+
+/// What the next `generate` call would cost, as reported by
+/// [`Switflake::next_id_cost`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateCost {
+    /// An ID is available right away.
+    Immediate,
+    /// This tick's sequence is used up: `generate_id` would fail with
+    /// [`SwitflakeError::SequenceExhausted`] and `generate_id_blocking`
+    /// would wait about this long.
+    WouldBlock(Duration),
+    /// Generating would fail with this error whatever the wait.
+    WouldError(SwitflakeError),
+}
+
+impl<C> Switflake<C> {
+    /// Takes another slot to continue this tick under, as
+    /// [`OverflowStrategy::BorrowSlot`] allows; returns whether it got one.
+    pub(crate) fn borrow_slot(&mut self, now_micros: u64) -> bool {
+        let pool = match (self.overflow_strategy, self.pool) {
+            (OverflowStrategy::BorrowSlot, Some(pool)) => pool,
+            _ => return false,
+        };
+        let Some(lease) = pool.try_acquire_lease() else {
+            return false;
+        };
+        // 방금 해제된 슬롯은 이전 소유자의 ID와 겹칠 수 있음
+        let until = pool.cooling_until(lease.thread_id);
+        if until > now_micros {
+            pool.release_after(lease, until);
+            return false;
+        }
+        self.borrowed_slots.push(lease);
+        true
+    }
+
+    /// Gives back every borrowed slot, marking it used until `end_micros`,
+    /// the end of the tick it was borrowed for.
+    pub(crate) fn return_borrowed_slots(&mut self, end_micros: u64) {
+        if let Some(pool) = self.pool {
+            for lease in self.borrowed_slots.drain(..) {
+                pool.release_after(lease, end_micros);
+            }
+        }
+    }
+
+    /// The thread id this tick's IDs carry: the last borrowed slot's, if
+    /// any, or the generator's own.
+    #[inline]
+    pub(crate) fn current_thread_id(&self) -> u8 {
+        self.borrowed_slots
+            .last()
+            .map_or(self.thread_id, |lease| lease.thread_id)
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Predicts the cost of the next `generate` call without using up a
+    /// sequence slot, for schedulers deciding whether to admit more work.
+    ///
+    /// Only a snapshot: the clock may move on before the call is made. With
+    /// [`global_order`](crate::SwitflakeBuilder::global_order) other
+    /// generators share the token, so an `Immediate` may still turn out
+    /// exhausted.
+    pub fn next_id_cost(&self) -> GenerateCost {
+        let now = match self.time_unit.now(&self.clock) {
+            Ok(now) => now,
+            Err(e) => return GenerateCost::WouldError(e),
+        };
+        let timestamp = match now.checked_sub(self.epoch_ticks()) {
+            Some(timestamp) => timestamp,
+            None => return GenerateCost::WouldError(SwitflakeError::ClockMovedBackwards),
+        };
+        if timestamp > self.layout.timestamp_mask() {
+            return GenerateCost::WouldError(SwitflakeError::TimestampOverflow);
+        }
+        if timestamp < self.last_timestamp {
+            return GenerateCost::WouldError(SwitflakeError::ClockMovedBackwards);
+        }
+        let exhausted = timestamp == self.last_timestamp
+            && self.local_counter as u64 == self.layout.counter_mask()
+            && self.borrowed == mask(self.adaptive_node_bits)
+            && !(self.overflow_strategy == OverflowStrategy::BorrowSlot
+                && self.pool.is_some_and(|pool| !pool.is_full()));
+        if exhausted {
+            GenerateCost::WouldBlock(self.duration_to_next_tick())
+        } else {
+            GenerateCost::Immediate
+        }
+    }
+
+    /// Time left until the clock enters the next tick of the configured
+    /// [`TimeUnit`](crate::TimeUnit), between zero (exclusive) and one full
+    /// unit. Clocks without sub-millisecond resolution report a whole
+    /// millisecond; if the clock cannot be read the result is zero.
+    pub fn duration_to_next_tick(&self) -> Duration {
+        let unit = self.time_unit.as_micros();
+        match self.clock.now_micros() {
+            Ok(now) => Duration::from_micros(unit - now % unit),
+            Err(_) => Duration::ZERO,
+        }
+    }
+
+    /// Waits, according to the block strategy, for the clock to move past
+    /// the current tick.
+    pub(crate) fn wait_for_next_tick(&self) -> Result<(), SwitflakeError> {
+        match self.block_strategy {
+            BlockStrategy::Spin => std::hint::spin_loop(),
+            BlockStrategy::SleepAbove(threshold) => {
+                let wait = self.duration_to_next_tick();
+                if wait > threshold {
+                    self.clock.sleep(wait);
+                } else {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{decompose, TimeUnit};
+
+    fn exhausted(strategy: BlockStrategy) -> (Switflake<MockClock>, MockClock) {
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .block_strategy(strategy)
+            .build()
+            .expect("Failed to create Switflake");
+        while swit.generate_id().is_ok() {}
+        (swit, clock)
+    }
+
+    #[test]
+    fn test_duration_to_next_tick_within_unit() {
+        let _guard = serial_guard();
+        for unit in [TimeUnit::Milliseconds, TimeUnit::TenMilliseconds] {
+            let swit = Switflake::builder()
+                .time_unit(unit)
+                .build()
+                .expect("Failed to create Switflake");
+            for _ in 0..50 {
+                let wait = swit.duration_to_next_tick();
+                assert!(wait > Duration::ZERO && wait <= unit.as_duration());
+            }
+        }
+
+        // 밀리초 해상도 시계는 항상 한 틱 전체를 기다림
+        let mock = Switflake::builder()
+            .clock(MockClock::at(1_700_000_000_005))
+            .time_unit(TimeUnit::TenMilliseconds)
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(mock.duration_to_next_tick(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_next_id_cost() {
+        use crate::clock::MonotonicSource;
+        use crate::MonotonicSystemClock;
+
+        // 밀리초 안에서 400µs 지난 시점에 멈춘 시계
+        struct Frozen;
+        impl MonotonicSource for Frozen {
+            fn elapsed(&self) -> Duration {
+                Duration::from_micros(400)
+            }
+        }
+
+        let _guard = serial_guard();
+        let mut swit = Switflake::builder()
+            .clock(MonotonicSystemClock::with_source(1_700_000_000_000, Frozen))
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(swit.next_id_cost(), GenerateCost::Immediate);
+        for _ in 0..swit.layout().counter_mask() {
+            assert_eq!(swit.next_id_cost(), GenerateCost::Immediate);
+            swit.generate_id().expect("Failed to generate ID");
+        }
+        assert_eq!(
+            swit.next_id_cost(),
+            GenerateCost::WouldBlock(Duration::from_micros(600))
+        );
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::SequenceExhausted));
+
+        let early = Switflake::builder()
+            .epoch(1_800_000_000_000)
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(
+            early.next_id_cost(),
+            GenerateCost::WouldError(SwitflakeError::ClockMovedBackwards)
+        );
+    }
+
+    #[test]
+    fn test_long_wait_sleeps() {
+        let _guard = serial_guard();
+        let (mut swit, clock) = exhausted(BlockStrategy::SleepAbove(Duration::from_micros(100)));
+        // 목 시계는 밀리초 단위라 다음 틱까지 1ms가 남은 것으로 보임
+        let id = swit.generate_id_blocking().expect("Failed to generate ID");
+        assert_eq!(clock.sleeps(), 1);
+        assert_eq!(swit.layout().timestamp_of(id), 1_700_000_000_001);
+    }
+
+    #[test]
+    fn test_short_wait_spins() {
+        let _guard = serial_guard();
+        let (mut swit, clock) = exhausted(BlockStrategy::SleepAbove(Duration::from_millis(5)));
+        let before = clock.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            before.advance(1);
+        });
+        swit.generate_id_blocking().expect("Failed to generate ID");
+        handle.join().expect("Thread join failed");
+        assert_eq!(clock.sleeps(), 0);
+    }
+
+    #[test]
+    fn test_borrow_slot_on_overflow() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(3)
+            .clock(clock.clone())
+            .overflow_strategy(OverflowStrategy::BorrowSlot)
+            .build()
+            .expect("Failed to create Switflake");
+        let pool = crate::ThreadIdPool::global();
+        let layout = swit.layout();
+
+        let mut ids = std::collections::HashSet::new();
+        for _ in 0..600 {
+            let id = swit.generate_id().expect("Failed to generate ID");
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+        // 255개씩 세 슬롯을 사용
+        assert_eq!(pool.occupied_slots().len(), 3);
+        let mut other = Switflake::builder()
+            .node_id(3)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        while let Ok(id) = other.generate_id() {
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+        while let Ok(id) = swit.generate_id() {
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+        assert_eq!(ids.len(), 8 * 255);
+        assert_eq!(
+            swit.next_id_cost(),
+            GenerateCost::WouldBlock(Duration::from_millis(1))
+        );
+
+        // 다음 밀리초에 빌린 슬롯을 반납
+        clock.advance(1);
+        let id = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(decompose(id, &layout).thread_id, swit.lease().thread_id);
+        assert_eq!(pool.occupied_slots().len(), 2);
+    }
+}
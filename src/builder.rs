@@ -0,0 +1,787 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::layout::{NAMESPACE_BIT, VERSION_BITS};
+use crate::pool::SlotPool;
+use crate::rate::RateBreaker;
+#[cfg(any(debug_assertions, feature = "paranoid"))]
+use crate::recent::RecentIds;
+use crate::{
+    BlockStrategy, Clock, GeneratorRegistry, Layout, LazyGenerator, MultiSlotGenerator,
+    NumaDetector, NumaThreadIdPool, OverflowStrategy, ReservedDropPolicy, SequenceCoordinator,
+    SlotBitmap, SlotLease, Slots, StateLog, Switflake, SwitflakeError, SystemClock, ThreadIdPool,
+    TimeUnit,
+};
+
+/// Configures and builds a [`Switflake`].
+///
+/// ```
+/// use switflake::Switflake;
+///
+/// let mut swit = Switflake::builder().node_id(42).build().unwrap();
+/// let id = swit.generate_id().unwrap();
+/// # let _ = id;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SwitflakeBuilder<C = SystemClock> {
+    settings: Settings,
+    clock: C,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Settings {
+    node_id: u64,
+    layout: Layout,
+    epoch: u64,
+    time_unit: TimeUnit,
+    reverse_order: bool,
+    global_order: bool,
+    block_strategy: BlockStrategy,
+    max_future_skew: Option<Duration>,
+    obfuscation_key: Option<u64>,
+    namespace_bit: bool,
+    version: u8,
+    recent_ids: Option<usize>,
+    adaptive_node_bits: u32,
+    pool: Option<&'static dyn SlotPool>,
+    fixed_thread_id: Option<u8>,
+    preferred_thread_id: Option<u8>,
+    registry: Option<&'static GeneratorRegistry>,
+    state_log: Option<Arc<Mutex<StateLog>>>,
+    reserved_drop_policy: ReservedDropPolicy,
+    overflow_strategy: OverflowStrategy,
+    max_rate_per_second: Option<u64>,
+    coordinator: Option<&'static SequenceCoordinator>,
+    #[cfg(feature = "getrandom")]
+    random_start_sequence: bool,
+}
+
+impl Settings {
+    /// The pool to take a slot from; `None` when the thread id is fixed, or
+    /// the layout has no thread field to fill and no pool was asked for.
+    fn pool(&self) -> Option<&'static dyn SlotPool> {
+        match self.pool {
+            _ if self.fixed_thread_id.is_some() || self.coordinator.is_some() => None,
+            None if self.layout.thread_bits == 0 => None,
+            pool => Some(pool.unwrap_or(ThreadIdPool::global())),
+        }
+    }
+
+    /// What a generator holds when it takes no pool slot: the fixed thread
+    /// id, or slot 0 when the layout has no thread field or the sequence
+    /// comes from a coordinator.
+    fn unpooled(&self) -> SlotLease {
+        SlotLease {
+            thread_id: self.fixed_thread_id.unwrap_or(0),
+            generation: 0,
+        }
+    }
+
+    /// Sizes the thread field for a custom pool, then checks the result.
+    fn check(&mut self) -> Result<(), SwitflakeError> {
+        if self.coordinator.is_some()
+            && (self.fixed_thread_id.is_some()
+                || self.pool.is_some()
+                || self.adaptive_node_bits > 0
+                || self.global_order)
+        {
+            return Err(SwitflakeError::InvalidLayout(
+                "sequence coordinator replaces the thread id and counter",
+            ));
+        }
+        if let Some(thread_id) = self.fixed_thread_id {
+            if thread_id as u64 > self.layout.thread_mask() {
+                return Err(SwitflakeError::FieldOverflow("thread"));
+            }
+        } else if let Some(pool) = self.pool {
+            let sequence_bits = self.layout.sequence_bits();
+            if pool.thread_bits() >= sequence_bits {
+                return Err(SwitflakeError::InvalidLayout(
+                    "sequence field too narrow for the pool",
+                ));
+            }
+            // 풀에 필요 없는 스레드 비트는 모두 카운터로
+            let counter_bits = sequence_bits - pool.thread_bits();
+            self.layout.counter_bits = counter_bits;
+            self.layout.thread_bits = sequence_bits - counter_bits;
+        }
+        self.layout.check_fields()?;
+        if self.namespace_bit && self.layout.reserved_mask() & NAMESPACE_BIT == 0 {
+            return Err(SwitflakeError::InvalidLayout(
+                "namespace bit overlaps a field",
+            ));
+        }
+        // 스레드 id가 카운터 비트로 넘치면 조용히 중복 ID가 생김
+        if let Some(pool) = self.pool() {
+            if pool.capacity() as u64 - 1 > self.layout.thread_mask() {
+                return Err(SwitflakeError::InvalidLayout(
+                    "thread field too narrow for every pool slot",
+                ));
+            }
+        }
+        if self.adaptive_node_bits > self.layout.node_bits {
+            return Err(SwitflakeError::InvalidLayout(
+                "adaptive counter borrows more bits than the node field has",
+            ));
+        }
+        let real_node_bits = self.layout.node_bits - self.adaptive_node_bits;
+        if self.adaptive_node_bits > 0
+            && self.node_id & self.layout.node_mask() & !crate::layout::mask(real_node_bits) != 0
+        {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        if !self.layout.versioned {
+            if self.version != 0 {
+                return Err(SwitflakeError::InvalidLayout(
+                    "version needs a versioned layout",
+                ));
+            }
+        } else if self.adaptive_node_bits > 0 {
+            return Err(SwitflakeError::InvalidLayout(
+                "adaptive counter cannot borrow from a versioned node field",
+            ));
+        } else if self.version as u64 > crate::layout::mask(VERSION_BITS) {
+            return Err(SwitflakeError::FieldOverflow("version"));
+        } else if self.node_id & !crate::layout::mask(self.layout.node_id_bits()) != 0 {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        if self.layout.kinded {
+            if self.adaptive_node_bits > 0 {
+                return Err(SwitflakeError::InvalidLayout(
+                    "adaptive counter cannot borrow from a kinded node field",
+                ));
+            } else if self.node_id & !crate::layout::mask(self.layout.node_id_bits()) != 0 {
+                return Err(SwitflakeError::FieldOverflow("node"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SwitflakeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C: Clock> SwitflakeBuilder<C> {
+    /// Node id embedded in every ID, truncated to the layout's node width.
+    pub fn node_id(mut self, node_id: u64) -> Self {
+        self.settings.node_id = node_id;
+        self
+    }
+
+    /// Fields of the IDs to generate.
+    ///
+    /// A layout with `thread_bits == 0` leaves no room for a thread id, so
+    /// `build` then takes no pool slot at all, unless a [`pool`](Self::pool)
+    /// is set, and the counter can use the whole sequence field. Nothing
+    /// stops a second such generator with the same node id from minting the
+    /// same IDs: use it only where one generator per node is guaranteed.
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.settings.layout = layout;
+        self
+    }
+
+    /// Measures timestamps from `unix_millis` instead of the Unix epoch,
+    /// stretching how long the timestamp field lasts. Clock readings before
+    /// the epoch fail with [`SwitflakeError::ClockMovedBackwards`].
+    pub fn epoch(mut self, unix_millis: u64) -> Self {
+        self.settings.epoch = unix_millis;
+        self
+    }
+
+    /// Length of one timestamp tick; milliseconds by default. The counter
+    /// restarts every tick, so coarser units trade throughput for a longer
+    /// lasting timestamp field.
+    pub fn time_unit(mut self, unit: TimeUnit) -> Self {
+        self.settings.time_unit = unit;
+        self
+    }
+
+    /// Stores the timestamp field counted down from its maximum, so newer
+    /// IDs are numerically smaller and an ascending scan sees the latest
+    /// first. Only the timestamp is inverted: IDs from the same millisecond
+    /// still ascend by counter. [`Switflake::timestamp`] and
+    /// [`Switflake::age`] undo the inversion.
+    pub fn reverse_order(mut self, enabled: bool) -> Self {
+        self.settings.reverse_order = enabled;
+        self
+    }
+
+    /// Sets bit 63 of every ID, so IDs of two namespaces (say events and
+    /// entities) can share a column without colliding: generators with the
+    /// bit set and clear never mint the same ID, and
+    /// [`DecomposedId::namespace`](crate::DecomposedId::namespace) tells
+    /// them apart.
+    ///
+    /// The layout must leave bit 63 out of every field, which the default
+    /// layout doesn't: its timestamp reaches the top bit. Shrink the
+    /// timestamp to 40 bits and pick a recent [`epoch`](Self::epoch), or
+    /// `build` fails with [`SwitflakeError::InvalidLayout`]. IDs with the
+    /// bit set are negative when read as `i64`, and sort after every ID
+    /// without it.
+    pub fn namespace_bit(mut self, enabled: bool) -> Self {
+        self.settings.namespace_bit = enabled;
+        self
+    }
+
+    /// Version stored in the top [`VERSION_BITS`] of the node field of every
+    /// ID, read back by [`id_version`](crate::id_version), so IDs minted
+    /// under an old and a new scheme can be told apart and decoded
+    /// accordingly.
+    ///
+    /// The layout must be [`versioned`](Layout::versioned), the version
+    /// must be below 16 and the node id must fit in the node bits left, or
+    /// `build` fails with [`SwitflakeError::InvalidLayout`] or
+    /// [`SwitflakeError::FieldOverflow`]. Can't be combined with
+    /// [`adaptive_counter`](Self::adaptive_counter), which borrows the same
+    /// bits.
+    pub fn version(mut self, version: u8) -> Self {
+        self.settings.version = version;
+        self
+    }
+
+    /// Fills the counter field from a token shared by every generator in the
+    /// process that has this enabled, instead of from a per-generator count.
+    ///
+    /// Among IDs from the same millisecond, a larger counter field then means
+    /// a later `generate` call, whichever generator made it, so the order of
+    /// calls can be recovered by sorting on (timestamp, counter). The token
+    /// wraps at the counter width: a generator whose next token would be
+    /// smaller than its last one gets [`SwitflakeError::SequenceExhausted`]
+    /// for the rest of the millisecond. Process-wide throughput per
+    /// millisecond is therefore shared by all such generators instead of
+    /// being per generator, and order is only recoverable while fewer than
+    /// 2^`counter_bits` IDs are minted per millisecond.
+    pub fn global_order(mut self, enabled: bool) -> Self {
+        self.settings.global_order = enabled;
+        self
+    }
+
+    /// How [`Switflake::generate_id_blocking`] waits for the next
+    /// millisecond once the current one is used up. Defaults to
+    /// [`BlockStrategy::Spin`].
+    pub fn block_strategy(mut self, strategy: BlockStrategy) -> Self {
+        self.settings.block_strategy = strategy;
+        self
+    }
+
+    /// What [`Switflake::generate_id`] does when a millisecond's sequence
+    /// runs out. Defaults to [`OverflowStrategy::Fail`].
+    pub fn overflow_strategy(mut self, strategy: OverflowStrategy) -> Self {
+        self.settings.overflow_strategy = strategy;
+        self
+    }
+
+    /// Refuses clock readings that jump more than `skew` ahead, failing
+    /// with [`SwitflakeError::ClockJumpedForward`] instead of minting IDs
+    /// dated in the future during a clock glitch.
+    ///
+    /// A new tick is compared with the previous one plus the monotonic
+    /// time elapsed since, so a generator left idle isn't flagged. A
+    /// rejected reading leaves the generator as it was: once the clock
+    /// settles, or once that much monotonic time has passed, it carries on.
+    pub fn max_future_skew(mut self, skew: Duration) -> Self {
+        self.settings.max_future_skew = Some(skew);
+        self
+    }
+
+    /// Places the timestamp, node and sequence fields at arbitrary bit
+    /// offsets, keeping the current field widths. The fields must not overlap
+    /// and must fit in 64 bits, which `build` checks.
+    pub fn field_offsets(
+        mut self,
+        timestamp_shift: u32,
+        node_shift: u32,
+        sequence_shift: u32,
+    ) -> Self {
+        self.settings.layout.timestamp_shift = timestamp_shift;
+        self.settings.layout.node_shift = node_shift;
+        self.settings.layout.sequence_shift = sequence_shift;
+        self
+    }
+
+    /// Passes every generated ID through [`obfuscate`](crate::obfuscate)
+    /// with `key`.
+    pub fn obfuscation_key(mut self, key: u64) -> Self {
+        self.settings.obfuscation_key = Some(key);
+        self
+    }
+
+    /// Remembers the last `capacity` IDs and refuses to emit any of them
+    /// again, catching clock or layout bugs that would produce duplicates.
+    ///
+    /// The check only exists in debug builds, where a repeat panics, or with
+    /// the `paranoid` feature, where it returns
+    /// [`SwitflakeError::DuplicateId`] instead. Release builds without
+    /// `paranoid` ignore this setting.
+    pub fn recent_ids_guard(mut self, capacity: usize) -> Self {
+        self.settings.recent_ids = Some(capacity);
+        self
+    }
+
+    /// Trips a circuit breaker once the generator has issued `limit` IDs
+    /// within the last second: until older IDs slide out of that window,
+    /// generation fails with [`SwitflakeError::RateExceeded`]. Meant to
+    /// catch a runaway loop minting IDs before it floods downstream
+    /// systems, not to pace callers; no limit by default.
+    pub fn max_rate_per_second(mut self, limit: u64) -> Self {
+        self.settings.max_rate_per_second = Some(limit);
+        self
+    }
+
+    /// Draws the whole sequence field, thread bits included, from
+    /// `coordinator` instead of a pool slot and a counter, so more
+    /// generators than the pool has slots can share one node id; see
+    /// [`SequenceCoordinator`]. Can't be combined with a fixed thread id, a
+    /// custom pool, an adaptive counter or global order.
+    pub fn sequence_coordinator(mut self, coordinator: &'static SequenceCoordinator) -> Self {
+        self.settings.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Lets a generator keep going when a millisecond's counter runs out by
+    /// borrowing the top `spare_node_bits` bits of the node field as extra
+    /// counter bits, multiplying the per-millisecond capacity by up to
+    /// 2^`spare_node_bits`. Meant for clocks that report milliseconds but
+    /// tick faster in practice.
+    ///
+    /// Uniqueness rests on no real node id ever using the borrowed bits:
+    /// `build` fails with [`SwitflakeError::FieldOverflow`] if this
+    /// generator's node id does, and every other node sharing the ID space
+    /// must be numbered below 2^(`node_bits - spare_node_bits`) too. IDs
+    /// keep increasing as long as the node field sits above the sequence
+    /// field, as in the default layout. Each widening is counted in the
+    /// `switflake_counter_widened_total` metric.
+    pub fn adaptive_counter(mut self, spare_node_bits: u32) -> Self {
+        self.settings.adaptive_node_bits = spare_node_bits;
+        self
+    }
+
+    /// Starts every millisecond's counter at a random value instead of 0, so
+    /// the first IDs after a restart or rollover are harder to predict.
+    ///
+    /// IDs stay unique and increasing within the generator, but the counter
+    /// values skipped at the start of each millisecond are lost: on average
+    /// only half of the per-millisecond capacity remains.
+    #[cfg(feature = "getrandom")]
+    pub fn random_start_sequence(mut self, enabled: bool) -> Self {
+        self.settings.random_start_sequence = enabled;
+        self
+    }
+
+    /// Takes thread ids from `pool` instead of the global pool. When
+    /// building, the sequence field is split anew: the thread field shrinks
+    /// to [`ThreadIdPool::THREAD_BITS`] and the counter gets the rest, so a
+    /// 1-slot pool hands the whole sequence field to the counter; building
+    /// fails if that makes the counter wider than 16 bits. Generators are
+    /// only unique among those sharing a pool: give every pool in a process
+    /// its own node id.
+    pub fn pool<const N: usize>(mut self, pool: &'static ThreadIdPool<N>) -> Self
+    where
+        Slots<N>: SlotBitmap,
+    {
+        self.settings.pool = Some(pool);
+        self
+    }
+
+    /// Appends the start of every new tick to `log` before issuing IDs from
+    /// it, so [`Switflake::resume_from_log`] can pick up safely after a
+    /// crash. Generators built from clones of this builder share the log.
+    pub fn state_log(mut self, log: StateLog) -> Self {
+        self.settings.state_log = Some(Arc::new(Mutex::new(log)));
+        self
+    }
+
+    /// What a [`ReservedRange`](crate::ReservedRange) from
+    /// [`Switflake::reserve_range`] does when dropped with IDs unused: with
+    /// [`ReservedDropPolicy::Guard`] a generator that takes the same slot of
+    /// the global pool in the same tick won't reissue them.
+    pub fn reserved_drop_policy(mut self, policy: ReservedDropPolicy) -> Self {
+        self.settings.reserved_drop_policy = policy;
+        self
+    }
+
+    /// Puts `thread_id` in every ID's thread field instead of taking a pool
+    /// slot, so building never fails with
+    /// [`SwitflakeError::PoolExhausted`] and dropping frees nothing. Any
+    /// [`pool`](Self::pool) is ignored. Nothing then keeps two generators
+    /// apart: give each generator with the same node id its own thread id,
+    /// and don't mix them with pooled generators on that node.
+    pub fn fixed_thread_id(mut self, thread_id: u8) -> Self {
+        self.settings.fixed_thread_id = Some(thread_id);
+        self
+    }
+
+    /// Takes pool slot `thread_id` if it is free, and any free slot
+    /// otherwise, e.g. to get the same thread ids from run to run while
+    /// debugging. Only [`build`](Self::build) looks at the preference.
+    pub fn preferred_thread_id(mut self, thread_id: u8) -> Self {
+        self.settings.preferred_thread_id = Some(thread_id);
+        self
+    }
+
+    /// Registers the generator with `registry` instead of the global one,
+    /// so a supervisor can wait for just its own workers' generators.
+    pub fn registry(mut self, registry: &'static GeneratorRegistry) -> Self {
+        self.settings.registry = Some(registry);
+        self
+    }
+
+    /// Like [`pool`](Self::pool), but takes thread ids from the calling
+    /// thread's partition of a [`NumaThreadIdPool`] first.
+    pub fn numa_pool<D: NumaDetector + 'static, const N: usize>(
+        mut self,
+        pool: &'static NumaThreadIdPool<D, N>,
+    ) -> Self
+    where
+        Slots<N>: SlotBitmap,
+    {
+        self.settings.pool = Some(pool);
+        self
+    }
+
+    /// Reads timestamps from `clock` instead of the system clock.
+    pub fn clock<D: Clock>(self, clock: D) -> SwitflakeBuilder<D> {
+        SwitflakeBuilder {
+            settings: self.settings,
+            clock,
+        }
+    }
+
+    pub fn build(self) -> Result<Switflake<C>, SwitflakeError> {
+        self.build_keeping_free(0)
+    }
+
+    pub(crate) fn build_keeping_free(
+        mut self,
+        keep_free: u32,
+    ) -> Result<Switflake<C>, SwitflakeError> {
+        self.settings.check()?;
+        let lease = match self.settings.pool() {
+            Some(pool) if pool.is_full() => return Err(SwitflakeError::PoolExhausted),
+            Some(pool) => {
+                let preferred = match self.settings.preferred_thread_id {
+                    Some(thread_id) => 1u64.checked_shl(thread_id.into()).unwrap_or(0),
+                    None => u64::MAX,
+                };
+                // 쿨다운 중인 슬롯은 다른 빈 슬롯이 없을 때만 사용
+                let cooling = cooling_slots(pool, &self.clock);
+                let eligible = match preferred & !cooling {
+                    0 => !cooling,
+                    eligible => eligible,
+                };
+                pool.acquire_preferring(keep_free, eligible)?
+            }
+            None => self.settings.unpooled(),
+        };
+        Ok(self.finish(lease))
+    }
+
+    /// Like [`build`](Self::build), but if the pool is full waits for a slot
+    /// to be freed instead of failing. Gives up with
+    /// [`SwitflakeError::PoolExhausted`] once `timeout` elapses; `None`
+    /// waits indefinitely. Fails right away instead if the pool already has
+    /// as many waiters as [`ThreadIdPool::set_max_waiters`] allows.
+    pub fn build_blocking(
+        mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Switflake<C>, SwitflakeError> {
+        self.settings.check()?;
+        let lease = match self.settings.pool() {
+            Some(pool) => pool.acquire_blocking(timeout)?,
+            None => self.settings.unpooled(),
+        };
+        Ok(self.finish(lease))
+    }
+
+    /// Like [`build_blocking`](Self::build_blocking) without a timeout, but
+    /// waits for a pool slot without blocking the thread, so async services
+    /// can create generators per request.
+    #[cfg(feature = "async")]
+    pub async fn build_async(mut self) -> Result<Switflake<C>, SwitflakeError> {
+        self.settings.check()?;
+        let lease = match self.settings.pool() {
+            Some(pool) => {
+                let mut waiter = None;
+                std::future::poll_fn(|cx| pool.poll_acquire(cx, &mut waiter)).await?
+            }
+            None => self.settings.unpooled(),
+        };
+        Ok(self.finish(lease))
+    }
+
+    /// Like [`build`](Self::build), but makes a single attempt at taking a
+    /// pool slot and returns `Ok(None)` if none is free right now.
+    pub fn try_build(mut self) -> Result<Option<Switflake<C>>, SwitflakeError> {
+        self.settings.check()?;
+        let lease = match self.settings.pool() {
+            Some(pool) => pool.try_acquire_lease(),
+            None => Some(self.settings.unpooled()),
+        };
+        Ok(lease.map(|lease| self.finish(lease)))
+    }
+
+    /// Builds a [`LazyGenerator`] that takes a pool slot only while in use
+    /// and returns it after `idle_timeout` without generating. Only the
+    /// global pool is supported.
+    pub fn build_lazy(mut self, idle_timeout: Duration) -> Result<LazyGenerator<C>, SwitflakeError>
+    where
+        C: Clone,
+    {
+        if self.settings.pool.is_some() || self.settings.fixed_thread_id.is_some() {
+            return Err(SwitflakeError::InvalidLayout(
+                "lazy generators only use the global pool",
+            ));
+        }
+        self.settings.check()?;
+        Ok(LazyGenerator::new_unchecked(self, idle_timeout))
+    }
+
+    /// Builds `count` generators sharing these settings, taking all their
+    /// pool slots at once: either every generator is returned or, if fewer
+    /// than `count` slots are free, none are and the error is
+    /// [`SwitflakeError::PoolExhausted`].
+    pub fn build_batch(mut self, count: usize) -> Result<Vec<Switflake<C>>, SwitflakeError>
+    where
+        C: Clone,
+    {
+        self.settings.check()?;
+        let leases = match self.settings.pool() {
+            Some(pool) => pool.acquire_many(count)?,
+            None if count <= 1 => vec![self.settings.unpooled(); count],
+            None => return Err(SwitflakeError::PoolExhausted),
+        };
+        Ok(leases
+            .into_iter()
+            .map(|lease| self.clone().finish(lease))
+            .collect())
+    }
+
+    /// Builds a [`MultiSlotGenerator`] over `slots` pool slots, taken all at
+    /// once as in [`build_batch`](Self::build_batch).
+    pub fn build_multi_slot(
+        mut self,
+        slots: usize,
+    ) -> Result<MultiSlotGenerator<C>, SwitflakeError> {
+        if slots == 0 {
+            return Err(SwitflakeError::InvalidLayout(
+                "a multi-slot generator needs at least one slot",
+            ));
+        }
+        self.settings.check()?;
+        let leases = match self.settings.pool() {
+            Some(pool) => pool.acquire_many(slots)?,
+            None if slots == 1 => vec![self.settings.unpooled()],
+            None => return Err(SwitflakeError::PoolExhausted),
+        };
+        Ok(self.finish_multi_slot(leases))
+    }
+
+    /// Builds a [`MultiSlotGenerator`] over `leases`, already taken; the
+    /// first becomes the generator's own slot.
+    pub(crate) fn finish_multi_slot(self, mut leases: Vec<SlotLease>) -> MultiSlotGenerator<C> {
+        let first = leases.remove(0);
+        MultiSlotGenerator::from_slots(self.finish(first), leases)
+    }
+
+    fn finish(self, lease: SlotLease) -> Switflake<C> {
+        let settings = self.settings;
+        let pool = settings.pool();
+        let registry = settings.registry.unwrap_or(GeneratorRegistry::global());
+        registry.register();
+        let mut swit = Switflake {
+            node_id: settings.node_id & settings.layout.node_mask(),
+            thread_id: lease.thread_id,
+            generation: lease.generation,
+            last_timestamp: 0,
+            local_counter: 0,
+            prefix: 0,
+            layout: settings.layout,
+            epoch: settings.epoch,
+            time_unit: settings.time_unit,
+            reverse_order: settings.reverse_order,
+            global_order: settings.global_order,
+            block_strategy: settings.block_strategy,
+            max_future_skew: settings.max_future_skew,
+            skew_reference: None,
+            obfuscation_key: settings.obfuscation_key,
+            namespace_bit: settings.namespace_bit,
+            version: settings.version,
+            kind: 0,
+            adaptive_node_bits: settings.adaptive_node_bits,
+            borrowed: 0,
+            generated: 0,
+            first_generated_micros: None,
+            pool,
+            registry,
+            state_log: settings.state_log,
+            reserved_drop_policy: settings.reserved_drop_policy,
+            #[cfg(any(debug_assertions, feature = "paranoid"))]
+            recent_ids: settings.recent_ids.map(RecentIds::new),
+            #[cfg(feature = "getrandom")]
+            random_start_sequence: settings.random_start_sequence,
+            on_drop: None,
+            overflow_strategy: settings.overflow_strategy,
+            borrowed_slots: Vec::new(),
+            rate_breaker: settings.max_rate_per_second.map(RateBreaker::new),
+            coordinator: settings.coordinator,
+            coordinated_block: 0..0,
+            clock: self.clock,
+        };
+        #[cfg(feature = "paranoid")]
+        if swit.pool.is_none() && swit.coordinator.is_none() {
+            crate::live_pairs::claim(swit.node_id, swit.thread_id);
+        }
+        swit.refresh_prefix();
+        swit.resume_high_water();
+        swit.sit_out_cooldown();
+        swit
+    }
+}
+
+/// Slots of `pool` still cooling down by `clock`, as a bit mask.
+pub(crate) fn cooling_slots<C: Clock>(pool: &dyn SlotPool, clock: &C) -> u64 {
+    let mut now = None;
+    let mut cooling = 0;
+    for id in 0..pool.capacity() {
+        let until = pool.cooling_until(id as u8);
+        if until != 0 && until > *now.get_or_insert_with(|| clock.now_micros().unwrap_or(0)) {
+            cooling |= 1 << id;
+        }
+    }
+    cooling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::decompose;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_sequence_in_high_bits() {
+        let _guard = serial_guard();
+        let now = 1_700_000_000_000;
+        let mut swit = Switflake::builder()
+            .node_id(77)
+            .field_offsets(0, 41, 53)
+            .clock(MockClock::at(now))
+            .build()
+            .expect("Failed to build Switflake");
+        let layout = swit.layout();
+
+        for expected_counter in 0..10 {
+            let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+            assert_eq!(decompose(id, &layout), parts);
+            assert_eq!(parts.node_id, 77);
+            assert_eq!(parts.counter, expected_counter);
+            assert_eq!(parts.timestamp, now);
+            // 시퀀스가 최상위 비트에 위치
+            assert_eq!(
+                id >> 53,
+                ((parts.thread_id as u64) << 8) | parts.counter as u64
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_in_top_node_bits() {
+        let _guard = serial_guard();
+        let layout = Layout {
+            versioned: true,
+            ..Layout::default()
+        };
+        let mut swit = Switflake::builder()
+            .node_id(201)
+            .layout(layout)
+            .version(2)
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to build Switflake");
+        let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert_eq!(crate::id_version(id, &layout), 2);
+        assert_eq!(decompose(id, &layout), parts);
+        assert_eq!((parts.node_id, parts.version), (201, 2));
+        // 버전 없는 레이아웃으로 읽으면 노드 필드 전체가 노드 id
+        assert_eq!(decompose(id, &Layout::default()).node_id, 2 << 8 | 201);
+        assert_eq!(crate::id_version(id, &Layout::default()), 0);
+        drop(swit);
+
+        let build = |node_id, layout, version| {
+            Switflake::builder()
+                .node_id(node_id)
+                .layout(layout)
+                .version(version)
+                .build()
+                .map(|_| ())
+        };
+        assert_eq!(
+            build(1, layout, 16),
+            Err(SwitflakeError::FieldOverflow("version"))
+        );
+        assert_eq!(
+            build(256, layout, 2),
+            Err(SwitflakeError::FieldOverflow("node"))
+        );
+        assert_eq!(
+            build(1, Layout::default(), 2),
+            Err(SwitflakeError::InvalidLayout(
+                "version needs a versioned layout"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_thread_field_narrower_than_pool_rejected() {
+        let _guard = serial_guard();
+        let layout = Layout {
+            thread_bits: 2,
+            node_bits: 13,
+            node_shift: 10,
+            ..Layout::default()
+        };
+        // 전역 풀의 스레드 id 4..8은 2비트에 들어가지 않음
+        let expected = Err(SwitflakeError::InvalidLayout(
+            "thread field too narrow for every pool slot",
+        ));
+        assert_eq!(
+            Switflake::builder().layout(layout).build().map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            Switflake::builder().layout(layout).try_build().map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            Switflake::builder()
+                .layout(layout)
+                .build_batch(2)
+                .map(|_| ()),
+            expected
+        );
+        let widened = Layout {
+            thread_bits: 3,
+            node_bits: 12,
+            node_shift: 11,
+            ..layout
+        };
+        assert!(Switflake::builder().layout(widened).build().is_ok());
+    }
+
+    #[test]
+    fn test_overlapping_fields_rejected() {
+        let result = Switflake::builder().field_offsets(23, 20, 0).build();
+        assert!(matches!(result, Err(SwitflakeError::InvalidLayout(_))));
+
+        let result = Switflake::builder().field_offsets(30, 11, 0).build();
+        assert_eq!(
+            result.err(),
+            Some(SwitflakeError::InvalidLayout(
+                "timestamp field does not fit in 64 bits"
+            ))
+        );
+    }
+}
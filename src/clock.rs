@@ -0,0 +1,644 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{telemetry, SwitflakeError};
+
+/// Source of the timestamps embedded in IDs.
+pub trait Clock {
+    /// Milliseconds since the Unix epoch.
+    fn now_millis(&self) -> Result<u64, SwitflakeError>;
+
+    /// Microseconds since the Unix epoch, used to work out how long a
+    /// blocked generator has to wait. Defaults to the start of the current
+    /// millisecond.
+    fn now_micros(&self) -> Result<u64, SwitflakeError> {
+        self.now_millis().map(|millis| millis * 1_000)
+    }
+
+    /// Blocks the calling thread for `duration`; see
+    /// [`BlockStrategy`](crate::BlockStrategy).
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Length of one tick of the timestamp field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TimeUnit {
+    Microseconds,
+    #[default]
+    Milliseconds,
+    /// Sonyflake-style 10 ms ticks: a 41-bit field then lasts about 700
+    /// years, at a tenth of the per-tick throughput.
+    TenMilliseconds,
+}
+
+impl TimeUnit {
+    pub const fn as_micros(self) -> u64 {
+        match self {
+            TimeUnit::Microseconds => 1,
+            TimeUnit::Milliseconds => 1_000,
+            TimeUnit::TenMilliseconds => 10_000,
+        }
+    }
+
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_micros(self.as_micros())
+    }
+
+    pub const fn ticks_per_second(self) -> u64 {
+        1_000_000 / self.as_micros()
+    }
+
+    /// Whole ticks in `millis` milliseconds.
+    #[inline]
+    pub(crate) const fn ticks_in_millis(self, millis: u64) -> u64 {
+        match self {
+            TimeUnit::Microseconds => millis * 1_000,
+            TimeUnit::Milliseconds => millis,
+            TimeUnit::TenMilliseconds => millis / 10,
+        }
+    }
+
+    /// Ticks since the Unix epoch according to `clock`.
+    #[inline]
+    pub(crate) fn now<C: Clock>(self, clock: &C) -> Result<u64, SwitflakeError> {
+        match self {
+            TimeUnit::Microseconds => clock.now_micros(),
+            TimeUnit::Milliseconds => clock.now_millis(),
+            TimeUnit::TenMilliseconds => clock.now_millis().map(|millis| millis / 10),
+        }
+    }
+}
+
+/// The wall clock, read through [`SystemTime`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .map_err(|_| SwitflakeError::ClockMovedBackwards)
+    }
+
+    #[inline]
+    fn now_micros(&self) -> Result<u64, SwitflakeError> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .map_err(|_| SwitflakeError::ClockMovedBackwards)
+    }
+}
+
+/// A clock stuck at a fixed instant, for golden-file tests; see
+/// [`Switflake::with_fixed_time`](crate::Switflake::with_fixed_time).
+///
+/// It never advances, not even when slept on, so a generator blocking for
+/// the next millisecond on it waits forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock {
+    pub millis: u64,
+}
+
+impl Clock for FixedClock {
+    #[inline]
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.millis)
+    }
+}
+
+/// A monotonic time source, abstracted so [`MonotonicSystemClock`] can be
+/// driven by a fake in tests.
+pub trait MonotonicSource {
+    /// Time elapsed since the source was created; never decreases.
+    fn elapsed(&self) -> Duration;
+}
+
+/// [`MonotonicSource`] backed by [`Instant`].
+#[derive(Debug, Clone, Copy)]
+pub struct InstantSource(Instant);
+
+impl InstantSource {
+    pub fn new() -> Self {
+        InstantSource(Instant::now())
+    }
+}
+
+impl Default for InstantSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MonotonicSource for InstantSource {
+    #[inline]
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Wall-clock time that can never go backwards.
+///
+/// The system time is read once, at construction; afterwards the clock only
+/// adds the monotonic time elapsed since then. Leap seconds and NTP steps
+/// after construction therefore never make it go backwards, at the cost of
+/// slowly drifting from the wall clock if the system clock is later
+/// corrected.
+#[derive(Debug, Clone)]
+pub struct MonotonicSystemClock<M = InstantSource> {
+    baseline_millis: u64,
+    source: M,
+}
+
+impl MonotonicSystemClock {
+    pub fn new() -> Result<Self, SwitflakeError> {
+        let source = InstantSource::new();
+        let baseline_millis = SystemClock.now_millis()?;
+        Ok(MonotonicSystemClock::with_source(baseline_millis, source))
+    }
+}
+
+impl<M: MonotonicSource> MonotonicSystemClock<M> {
+    /// Reports `baseline_millis` plus the time elapsed on `source`.
+    pub fn with_source(baseline_millis: u64, source: M) -> Self {
+        MonotonicSystemClock {
+            baseline_millis,
+            source,
+        }
+    }
+}
+
+impl<M: MonotonicSource> Clock for MonotonicSystemClock<M> {
+    #[inline]
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.baseline_millis + self.source.elapsed().as_millis() as u64)
+    }
+
+    #[inline]
+    fn now_micros(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.baseline_millis * 1_000 + self.source.elapsed().as_micros() as u64)
+    }
+}
+
+/// Wraps a clock that may fail to read, e.g. [`SystemClock`] on sandboxed
+/// platforms where `SystemTime` errors, and falls back to monotonic time
+/// whenever it does.
+///
+/// The fallback counts monotonic time from construction onwards, starting
+/// at the wrapped clock's reading at construction, or at the Unix epoch if
+/// that failed too. Fallback readings never go below the latest reading
+/// already given out, so generation stays monotonic instead of failing, but
+/// the timestamps drift from wall-clock time for as long as the wrapped
+/// clock is down; if it was down from the start they count from 1970 and a
+/// custom [`epoch`](crate::SwitflakeBuilder::epoch) makes generation fail.
+/// Readings of a working wrapped clock pass through untouched.
+///
+/// Every fallback reading is counted, in
+/// [`fallback_readings`](Self::fallback_readings) and, with the `metrics`
+/// feature, in `switflake_clock_fallback_total`. Clones share the counts.
+/// With the `log` feature, each switch from the wrapped clock to the
+/// fallback also logs a warning with the wrapped clock's error.
+#[derive(Debug, Clone)]
+pub struct ClockFallback<C = SystemClock, M = InstantSource> {
+    primary: C,
+    source: M,
+    start_micros: u64,
+    state: Arc<FallbackState>,
+}
+
+#[derive(Debug, Default)]
+struct FallbackState {
+    latest_micros: AtomicU64,
+    fallbacks: AtomicU64,
+    // Whether the last reading fell back, so only the switch gets logged.
+    falling_back: AtomicBool,
+}
+
+impl ClockFallback {
+    pub fn new() -> Self {
+        ClockFallback::with_source(SystemClock, InstantSource::new())
+    }
+}
+
+impl Default for ClockFallback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock, M: MonotonicSource> ClockFallback<C, M> {
+    /// Falls back from `primary` to time elapsed on `source`.
+    pub fn with_source(primary: C, source: M) -> Self {
+        let start_micros = primary.now_micros().unwrap_or(0);
+        ClockFallback {
+            primary,
+            source,
+            start_micros,
+            state: Arc::default(),
+        }
+    }
+
+    /// Number of readings so far that had to fall back.
+    pub fn fallback_readings(&self) -> u64 {
+        self.state.fallbacks.load(Ordering::Relaxed)
+    }
+
+    fn read_micros(&self) -> u64 {
+        let micros = match self.primary.now_micros() {
+            Ok(micros) => {
+                self.state.falling_back.store(false, Ordering::Relaxed);
+                micros
+            }
+            Err(error) => {
+                self.state.fallbacks.fetch_add(1, Ordering::Relaxed);
+                telemetry::clock_fallback();
+                if !self.state.falling_back.swap(true, Ordering::Relaxed) {
+                    telemetry::clock_fallback_started(&error);
+                }
+                let elapsed = self.source.elapsed().as_micros() as u64;
+                (self.start_micros + elapsed).max(self.state.latest_micros.load(Ordering::SeqCst))
+            }
+        };
+        self.state.latest_micros.fetch_max(micros, Ordering::SeqCst);
+        micros
+    }
+}
+
+impl<C: Clock, M: MonotonicSource> Clock for ClockFallback<C, M> {
+    #[inline]
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.read_micros() / 1_000)
+    }
+
+    #[inline]
+    fn now_micros(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.read_micros())
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.primary.sleep(duration);
+    }
+}
+
+/// Wraps a clock so that every generator built on a clone of it shares one
+/// cached millisecond reading instead of each reading the clock itself.
+///
+/// The first reading in a millisecond goes to the wrapped clock, and
+/// whichever generator takes it publishes it for all; the others return
+/// the cached value until `source` says that millisecond is over, so a
+/// cached reading is never older than one tick. Sleeping on the clock, as
+/// a blocked generator does, forces the next reading to be real. Readings
+/// never go backwards. Microsecond readings, used for waiting, for the
+/// [reuse cooldown](crate::ThreadIdPool::set_reuse_cooldown) and with
+/// [`TimeUnit::Microseconds`], always go to the wrapped clock and publish
+/// what they read, so a millisecond reading never lags them and IDs stay
+/// unique when a generator is dropped and its slot taken again.
+#[derive(Debug, Clone)]
+pub struct SharedTickClock<C = SystemClock, M = InstantSource> {
+    shared: Arc<SharedTick<C, M>>,
+}
+
+#[derive(Debug)]
+struct SharedTick<C, M> {
+    clock: C,
+    source: M,
+    millis: AtomicU64,
+    // Nanoseconds on `source` at which the cached millisecond ends.
+    fresh_until: AtomicU64,
+    real_reads: AtomicU64,
+}
+
+impl SharedTickClock {
+    pub fn new() -> Self {
+        SharedTickClock::with_clock(SystemClock)
+    }
+}
+
+impl Default for SharedTickClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> SharedTickClock<C> {
+    /// Shares readings of `clock`, timing them with [`Instant`].
+    pub fn with_clock(clock: C) -> Self {
+        SharedTickClock::with_source(clock, InstantSource::new())
+    }
+}
+
+impl<C: Clock, M: MonotonicSource> SharedTickClock<C, M> {
+    /// Shares readings of `clock`, timing how long each stays current on
+    /// `source`.
+    pub fn with_source(clock: C, source: M) -> Self {
+        SharedTickClock {
+            shared: Arc::new(SharedTick {
+                clock,
+                source,
+                millis: AtomicU64::new(0),
+                fresh_until: AtomicU64::new(0),
+                real_reads: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Readings so far that went to the wrapped clock.
+    pub fn real_reads(&self) -> u64 {
+        self.shared.real_reads.load(Ordering::Relaxed)
+    }
+
+    /// Reads the wrapped clock and publishes the reading until the
+    /// millisecond it falls in is over.
+    fn read_micros(&self) -> Result<u64, SwitflakeError> {
+        let shared = &*self.shared;
+        // 시계를 읽기 전의 경과 시간을 기준으로 해야 만료가 늦어지지 않음
+        let read_at = shared.source.elapsed().as_nanos() as u64;
+        let micros = shared.clock.now_micros()?;
+        shared.real_reads.fetch_add(1, Ordering::Relaxed);
+        let left_nanos = (1_000 - micros % 1_000) * 1_000;
+        shared.millis.fetch_max(micros / 1_000, Ordering::AcqRel);
+        shared
+            .fresh_until
+            .fetch_max(read_at + left_nanos, Ordering::Release);
+        Ok(micros)
+    }
+}
+
+impl<C: Clock, M: MonotonicSource> Clock for SharedTickClock<C, M> {
+    #[inline]
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        let shared = &*self.shared;
+        let fresh_until = shared.fresh_until.load(Ordering::Acquire);
+        if (shared.source.elapsed().as_nanos() as u64) < fresh_until {
+            return Ok(shared.millis.load(Ordering::Acquire));
+        }
+        let millis = self.read_micros()? / 1_000;
+        // 다른 생성기가 먼저 더 늦은 값을 게시했으면 그 값을 따름
+        Ok(shared.millis.load(Ordering::Acquire).max(millis))
+    }
+
+    #[inline]
+    fn now_micros(&self) -> Result<u64, SwitflakeError> {
+        self.read_micros()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.shared.clock.sleep(duration);
+        self.shared.fresh_until.store(0, Ordering::Release);
+    }
+}
+
+/// Manually driven clock shared between a test and the generator under test.
+/// Sleeping on it advances it instead of blocking, and is counted.
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MockClock {
+    millis: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    sleeps: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub(crate) fn at(millis: u64) -> Self {
+        let clock = MockClock::default();
+        clock.set(millis);
+        clock
+    }
+
+    pub(crate) fn set(&self, millis: u64) {
+        self.millis
+            .store(millis, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub(crate) fn advance(&self, millis: u64) {
+        self.millis
+            .fetch_add(millis, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Number of `sleep` calls so far.
+    pub(crate) fn sleeps(&self) -> u64 {
+        self.sleeps.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.millis.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.sleeps
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let millis = ((duration.as_micros() + 999) / 1_000) as u64;
+        self.advance(millis.max(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[derive(Clone, Default)]
+    struct MockInstant(MockClock);
+
+    impl MonotonicSource for MockInstant {
+        fn elapsed(&self) -> Duration {
+            Duration::from_millis(self.0.now_millis().unwrap())
+        }
+    }
+
+    #[test]
+    fn test_monotonic_clock_survives_wall_clock_step() {
+        let _guard = serial_guard();
+        let wall = MockClock::at(1_700_000_000_000);
+        let instant = MockInstant::default();
+        let clock = MonotonicSystemClock::with_source(wall.now_millis().unwrap(), instant.clone());
+
+        let mut wall_swit = Switflake::builder()
+            .clock(wall.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let mut mono_swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        wall_swit.generate_id().expect("Failed to generate ID");
+        let mut last = mono_swit.generate_id().expect("Failed to generate ID");
+
+        // 윤초/NTP로 벽시계가 1초 뒤로 감
+        instant.0.advance(5);
+        wall.set(1_700_000_000_005 - 1_000);
+        assert_eq!(
+            wall_swit.generate_id(),
+            Err(SwitflakeError::ClockMovedBackwards)
+        );
+
+        let mut previous_millis = 0;
+        for _ in 0..5 {
+            let now = clock.now_millis().unwrap();
+            assert!(now > previous_millis);
+            previous_millis = now;
+            let id = mono_swit.generate_id().expect("Failed to generate ID");
+            assert!(id > last);
+            last = id;
+            instant.0.advance(1);
+        }
+        assert_eq!(previous_millis, 1_700_000_000_009);
+    }
+
+    #[derive(Clone)]
+    struct Flaky {
+        clock: MockClock,
+        broken: Arc<AtomicBool>,
+    }
+
+    impl Clock for Flaky {
+        fn now_millis(&self) -> Result<u64, SwitflakeError> {
+            if self.broken.load(Ordering::SeqCst) {
+                return Err(SwitflakeError::ClockMovedBackwards);
+            }
+            self.clock.now_millis()
+        }
+    }
+
+    #[test]
+    fn test_fallback_keeps_generating_when_clock_fails() {
+        let _guard = serial_guard();
+        let flaky = Flaky {
+            clock: MockClock::at(1_700_000_000_000),
+            broken: Arc::default(),
+        };
+        let instant = MockInstant::default();
+        let clock = ClockFallback::with_source(flaky.clone(), instant.clone());
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+
+        let mut last = swit.generate_id().expect("Failed to generate ID");
+        flaky.clock.advance(10);
+        assert!(swit.generate_id().unwrap() > last);
+        last = swit.generate_id().unwrap();
+        assert_eq!(clock.fallback_readings(), 0);
+
+        // 시스템 시계가 고장 나도 단조 시간으로 계속 생성
+        flaky.broken.store(true, Ordering::SeqCst);
+        for _ in 0..20 {
+            let id = swit.generate_id_blocking().expect("Failed to generate ID");
+            assert!(id > last);
+            last = id;
+            instant.0.advance(1);
+        }
+        assert!(clock.fallback_readings() >= 20);
+        assert_eq!(swit.layout().timestamp_of(last), 1_700_000_000_000 + 19);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_fallback_logs_each_switch() {
+        use std::sync::Mutex;
+
+        struct Capture(Mutex<Vec<String>>);
+
+        impl log::Log for Capture {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                if record.level() == log::Level::Warn {
+                    self.0.lock().unwrap().push(record.args().to_string());
+                }
+            }
+            fn flush(&self) {}
+        }
+
+        static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+        log::set_logger(&CAPTURE).expect("Only this test sets a logger");
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let flaky = Flaky {
+            clock: MockClock::at(1_700_000_000_000),
+            broken: Arc::default(),
+        };
+        let clock = ClockFallback::with_source(flaky.clone(), MockInstant::default());
+        clock.now_millis().expect("Failed to read clock");
+        // 고장 구간마다 전환 시점에 한 번만 경고
+        for broken in [true, false, true] {
+            flaky.broken.store(broken, Ordering::SeqCst);
+            for _ in 0..3 {
+                clock.now_millis().expect("Failed to read clock");
+            }
+        }
+        let logged = CAPTURE.0.lock().unwrap();
+        assert_eq!(logged.len(), 2, "{:?}", logged);
+        assert!(logged[0].contains("falling back"), "{}", logged[0]);
+        assert!(logged[0].contains(&SwitflakeError::ClockMovedBackwards.to_string()));
+        assert_eq!(clock.fallback_readings(), 6);
+    }
+
+    #[test]
+    #[cfg(not(loom))]
+    fn test_shared_tick_clock_keeps_ids_unique() {
+        use crate::ThreadIdPool;
+
+        static POOL: ThreadIdPool<8> = ThreadIdPool::new();
+        POOL.set_reuse_cooldown(true);
+        let _guard = serial_guard();
+        let mock = MockClock::at(1_700_000_000_000);
+        let clock = SharedTickClock::with_source(mock.clone(), MockInstant(mock.clone()));
+        let build = || {
+            Switflake::builder()
+                .pool(&POOL)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake")
+        };
+        let mut generators: Vec<_> = (0..8).map(|_| build()).collect();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut last = vec![0; generators.len()];
+        for round in 0..2_000 {
+            if round % 150 == 0 {
+                mock.advance(1);
+            }
+            if round % 75 == 0 {
+                // 같은 슬롯을 다시 받은 생성기도 이전 생성기의 ID를 반복하지 않음
+                let i = round / 75 % generators.len();
+                let slot = generators[i].lease().thread_id;
+                drop(generators.remove(i));
+                generators.insert(i, build());
+                assert_eq!(generators[i].lease().thread_id, slot);
+            }
+            for (swit, last) in generators.iter_mut().zip(&mut last) {
+                // 틱 용량을 넘으면 실패함
+                let id = match swit.generate_id() {
+                    Err(SwitflakeError::SequenceExhausted) => continue,
+                    result => result.expect("Failed to generate ID"),
+                };
+                assert!(id > *last);
+                *last = id;
+                assert!(seen.insert(id), "duplicate ID {}", id);
+            }
+        }
+        let readings = 2_000 * generators.len() as u64;
+        assert!(clock.real_reads() * 32 < readings);
+        assert!(seen.len() > 8 * 255);
+    }
+
+    #[test]
+    fn test_monotonic_clock_tracks_system_time() {
+        let clock = MonotonicSystemClock::new().expect("Clock before Unix epoch");
+        let wall = SystemClock.now_millis().unwrap();
+        let mono = clock.now_millis().unwrap();
+        assert!(mono.abs_diff(wall) < 1_000);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Clock, Switflake, SwitflakeError};
+
+/// A batch of IDs with each field in its own column, as returned by
+/// [`Switflake::generate_columnar`]. Row `i` of every column belongs to
+/// `ids[i]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnarIds {
+    pub ids: Vec<u64>,
+    pub timestamps: Vec<u64>,
+    pub nodes: Vec<u64>,
+    pub threads: Vec<u8>,
+    pub counters: Vec<u16>,
+}
+
+impl ColumnarIds {
+    fn with_capacity(n: usize) -> Self {
+        ColumnarIds {
+            ids: Vec::with_capacity(n),
+            timestamps: Vec::with_capacity(n),
+            nodes: Vec::with_capacity(n),
+            threads: Vec::with_capacity(n),
+            counters: Vec::with_capacity(n),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Generates `n` IDs and fills every column in the same pass, without
+    /// decoding the packed IDs again. Waits for the next millisecond like
+    /// [`generate_id_blocking`](Self::generate_id_blocking) when one runs out.
+    pub fn generate_columnar(&mut self, n: usize) -> Result<ColumnarIds, SwitflakeError> {
+        let mut columns = ColumnarIds::with_capacity(n);
+        while columns.len() < n {
+            let (id, parts) = match self.generate_decomposed() {
+                Err(SwitflakeError::SequenceExhausted) => {
+                    self.wait_for_next_tick()?;
+                    continue;
+                }
+                result => result?,
+            };
+            columns.ids.push(id);
+            columns.timestamps.push(parts.timestamp);
+            columns.nodes.push(parts.node_id);
+            columns.threads.push(parts.thread_id);
+            columns.counters.push(parts.counter);
+        }
+        Ok(columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pool::serial_guard;
+    use crate::{decompose, Switflake};
+
+    #[test]
+    fn test_columns_line_up() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(321).expect("Failed to create Switflake");
+        let columns = swit.generate_columnar(600).expect("Failed to generate IDs");
+        assert_eq!(columns.len(), 600);
+        for len in [
+            columns.timestamps.len(),
+            columns.nodes.len(),
+            columns.threads.len(),
+            columns.counters.len(),
+        ] {
+            assert_eq!(len, 600);
+        }
+        let layout = swit.layout();
+        for (i, &id) in columns.ids.iter().enumerate() {
+            let parts = decompose(id, &layout);
+            assert_eq!(parts.timestamp, columns.timestamps[i]);
+            assert_eq!(parts.node_id, columns.nodes[i]);
+            assert_eq!(parts.thread_id, columns.threads[i]);
+            assert_eq!(parts.counter, columns.counters[i]);
+        }
+        assert!(columns.ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(swit.generate_columnar(0).expect("empty batch").is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use crate::{Clock, Layout, Switflake, SwitflakeBuilder, SwitflakeError, SystemClock, TimeUnit};
+
+/// Settings for [`Switflake::from_config`], e.g. read from a service's
+/// config file.
+///
+/// With the `serde` feature it (de)serializes with the field names below;
+/// everything but `node_id` may be left out and unknown fields are
+/// rejected:
+///
+/// ```json
+/// {"node_id": 7, "epoch": 1600000000000, "time_unit": "ten_milliseconds"}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct SwitflakeConfig {
+    pub node_id: u64,
+    /// Unix time, in milliseconds, that timestamp 0 stands for.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub epoch: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub time_unit: TimeUnit,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub layout: Layout,
+}
+
+impl Switflake {
+    /// Builds a generator from `config`, taking a slot from the global pool.
+    ///
+    /// Unlike the builder, which truncates node ids, this checks everything
+    /// up front: a node id wider than the node field fails with
+    /// [`SwitflakeError::FieldOverflow`], an unusable layout with
+    /// [`SwitflakeError::InvalidLayout`], an epoch in the future with
+    /// [`SwitflakeError::ClockMovedBackwards`] and one so far back that the
+    /// timestamp field is already full with
+    /// [`SwitflakeError::TimestampOverflow`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn from_config(config: SwitflakeConfig) -> Result<Self, SwitflakeError> {
+        if config.node_id & !config.layout.node_mask() != 0 {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        // 밀리초로 먼저 비교해야 틱 변환이 넘치지 않음
+        if config.epoch > SystemClock.now_millis()? {
+            return Err(SwitflakeError::ClockMovedBackwards);
+        }
+        let elapsed = config
+            .time_unit
+            .now(&SystemClock)?
+            .saturating_sub(config.time_unit.ticks_in_millis(config.epoch));
+        if elapsed > config.layout.timestamp_mask() {
+            return Err(SwitflakeError::TimestampOverflow);
+        }
+        SwitflakeBuilder::new()
+            .node_id(config.node_id)
+            .layout(config.layout)
+            .epoch(config.epoch)
+            .time_unit(config.time_unit)
+            .build()
+    }
+}
+
+/// Snapshot of a generator's configuration, for diagnostics endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwitflakeConfigView {
+    pub node_id: u64,
+    pub thread_id: u8,
+    /// Unix time, in milliseconds, that timestamp 0 stands for.
+    pub epoch: u64,
+    /// Length of one timestamp tick.
+    pub time_unit: Duration,
+    /// Number of generators this generator's thread-id pool can hold at
+    /// once; 1 for a generator that took no pool slot.
+    pub pool_capacity: usize,
+    pub layout: Layout,
+}
+
+impl<C> Switflake<C> {
+    /// The generator's live configuration as plain values.
+    pub fn config(&self) -> SwitflakeConfigView {
+        SwitflakeConfigView {
+            node_id: self.node_id,
+            thread_id: self.thread_id,
+            epoch: self.epoch,
+            time_unit: self.time_unit.as_duration(),
+            pool_capacity: self.pool.map_or(1, |pool| pool.capacity()),
+            layout: self.layout,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_view_reflects_builder() {
+        let _guard = serial_guard();
+        let epoch = 1_600_000_000_000;
+        let mut swit = Switflake::builder()
+            .node_id(99)
+            .epoch(epoch)
+            .field_offsets(0, 41, 53)
+            .clock(MockClock::at(epoch + 1_234))
+            .build()
+            .expect("Failed to create Switflake");
+        let config = swit.config();
+        assert_eq!(config.node_id, 99);
+        assert_eq!(config.thread_id, swit.lease().thread_id);
+        assert_eq!(config.epoch, epoch);
+        assert_eq!(config.time_unit, Duration::from_millis(1));
+        assert_eq!(config.pool_capacity, 8);
+        assert_eq!(config.layout, swit.layout());
+        assert_eq!(config.layout.sequence_shift, 53);
+
+        // 타임스탬프는 epoch 기준
+        let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert_eq!(parts.timestamp, 1_234);
+        assert_eq!(
+            swit.timestamp(id),
+            std::time::UNIX_EPOCH + Duration::from_millis(epoch + 1_234)
+        );
+        assert!(format!("{:?}", config).contains("epoch: 1600000000000"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_generator_from_json_config() {
+        let _guard = serial_guard();
+        let json = r#"{"node_id": 1234, "epoch": 1600000000000, "time_unit": "ten_milliseconds"}"#;
+        let config: SwitflakeConfig = serde_json::from_str(json).expect("Failed to parse config");
+        assert_eq!(config.layout, Layout::default());
+        let mut swit = Switflake::from_config(config).expect("Failed to create Switflake");
+        let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert_eq!(crate::decompose(id, &swit.layout()).node_id, 1234);
+        assert_eq!(parts.node_id, 1234);
+        assert_eq!(swit.config().time_unit, Duration::from_millis(10));
+        let round_trip: SwitflakeConfig =
+            serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(round_trip, config);
+
+        // 빌더와 달리 잘라내지 않고 거부함
+        let too_wide = SwitflakeConfig {
+            node_id: 4096,
+            ..config
+        };
+        assert_eq!(
+            Switflake::from_config(too_wide).map(|_| ()),
+            Err(SwitflakeError::FieldOverflow("node"))
+        );
+        let future = SwitflakeConfig {
+            epoch: u64::MAX / 2,
+            ..config
+        };
+        assert_eq!(
+            Switflake::from_config(future).map(|_| ()),
+            Err(SwitflakeError::ClockMovedBackwards)
+        );
+        assert!(serde_json::from_str::<SwitflakeConfig>(r#"{"epoch": 0}"#).is_err());
+        assert!(serde_json::from_str::<SwitflakeConfig>(r#"{"node_id": 1, "nodes": 2}"#).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::ops::Range;
+use std::sync::Mutex;
+
+use crate::SwitflakeError;
+
+// Sequence values handed out per claim, so generators come back for more
+// only every few IDs.
+const BLOCK_SIZE: u64 = 16;
+
+/// Shares each tick's sequence space of a node id among any number of
+/// generators, for processes needing more generators on one node than the
+/// [`ThreadIdPool`](crate::ThreadIdPool) has slots.
+///
+/// Generators built with
+/// [`SwitflakeBuilder::sequence_coordinator`](crate::SwitflakeBuilder::sequence_coordinator)
+/// take no pool slot. They claim disjoint blocks of 16 sequence values of
+/// the current tick from the coordinator instead, and fill the whole
+/// sequence field, thread bits and counter alike, from them. Together they
+/// issue as many IDs per tick as a node's sequence field holds, less one,
+/// however many of them there are; past that they fail with
+/// [`SwitflakeError::SequenceExhausted`] until the next tick.
+///
+/// Every generator using a node id must go through the same coordinator,
+/// with the same layout, epoch and time unit. A generator whose clock
+/// trails the node's latest tick joins that tick rather than failing, as
+/// readings behind the latest tick do in
+/// [`SharedSwitflake`](crate::SharedSwitflake), since the values of older
+/// ticks are no longer tracked.
+#[derive(Debug, Default)]
+pub struct SequenceCoordinator {
+    // (node field, latest tick, next free sequence value) per node id.
+    nodes: Mutex<Vec<(u64, u64, u64)>>,
+}
+
+impl SequenceCoordinator {
+    pub const fn new() -> Self {
+        SequenceCoordinator {
+            nodes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The coordinator for code that doesn't need one of its own.
+    pub fn global() -> &'static SequenceCoordinator {
+        static GLOBAL: SequenceCoordinator = SequenceCoordinator::new();
+        &GLOBAL
+    }
+
+    /// Claims the next block of sequence values below `limit` for `node`
+    /// in `tick`, or in the node's latest tick if that is later, and
+    /// returns the tick with the block.
+    pub(crate) fn claim(
+        &self,
+        node: u64,
+        tick: u64,
+        limit: u64,
+    ) -> Result<(u64, Range<u64>), SwitflakeError> {
+        let mut nodes = self.nodes.lock().unwrap_or_else(|e| e.into_inner());
+        let index = match nodes.iter().position(|&(n, _, _)| n == node) {
+            Some(index) => index,
+            None => {
+                nodes.push((node, tick, 0));
+                nodes.len() - 1
+            }
+        };
+        let (_, latest, next) = &mut nodes[index];
+        if tick > *latest {
+            (*latest, *next) = (tick, 0);
+        }
+        if *next >= limit {
+            return Err(SwitflakeError::SequenceExhausted);
+        }
+        let block = *next..(*next + BLOCK_SIZE).min(limit);
+        *next = block.end;
+        Ok((*latest, block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{Switflake, ThreadIdPool};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_twelve_generators_share_one_node() {
+        let _guard = serial_guard();
+        static COORDINATOR: SequenceCoordinator = SequenceCoordinator::new();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut generators: Vec<Switflake<MockClock>> = (0..12)
+            .map(|_| {
+                Switflake::builder()
+                    .node_id(42)
+                    .sequence_coordinator(&COORDINATOR)
+                    .clock(clock.clone())
+                    .build()
+                    .expect("Failed to create Switflake")
+            })
+            .collect();
+        // 풀 슬롯은 하나도 쓰지 않음
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+
+        let mut ids = HashSet::new();
+        let mut exhausted = vec![false; generators.len()];
+        while exhausted.contains(&false) {
+            for (swit, done) in generators.iter_mut().zip(&mut exhausted) {
+                match swit.generate_id() {
+                    Ok(id) => assert!(ids.insert(id), "Duplicate ID found: {}", id),
+                    Err(SwitflakeError::SequenceExhausted) => *done = true,
+                    Err(e) => panic!("Failed to generate ID: {}", e),
+                }
+            }
+        }
+        // 11비트 시퀀스 공간 전체(최상위 값 제외)를 나눠 씀
+        assert_eq!(ids.len(), 2_047);
+
+        clock.advance(1);
+        for swit in &mut generators {
+            let id = swit.generate_id().expect("Failed to generate ID");
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+    }
+
+    #[test]
+    // paranoid의 워터마크 검사는 서로 어긋난 시계를 잡아냄
+    #[cfg(not(feature = "paranoid"))]
+    fn test_trailing_clock_joins_latest_tick() {
+        use crate::decompose;
+
+        let _guard = serial_guard();
+        static COORDINATOR: SequenceCoordinator = SequenceCoordinator::new();
+        let ahead = MockClock::at(1_700_000_000_005);
+        let behind = MockClock::at(1_700_000_000_000);
+        let build = |clock: &MockClock| {
+            Switflake::builder()
+                .node_id(7)
+                .sequence_coordinator(&COORDINATOR)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake")
+        };
+        let mut a = build(&ahead);
+        let mut b = build(&behind);
+
+        let mut ids = HashSet::new();
+        for _ in 0..40 {
+            assert!(ids.insert(a.generate_id().expect("Failed to generate ID")));
+        }
+        // 뒤처진 시계도 실패하지 않고 노드의 최신 틱으로 발급
+        let mut last = 0;
+        for _ in 0..40 {
+            let id = b.generate_id().expect("Failed to generate ID");
+            assert_eq!(decompose(id, &b.layout()).timestamp, 1_700_000_000_005);
+            assert!(id > last);
+            last = id;
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+        behind.advance(1);
+        assert_eq!(
+            decompose(b.generate_id().unwrap(), &b.layout()).timestamp,
+            1_700_000_000_005
+        );
+        behind.advance(5);
+        assert_eq!(
+            decompose(b.generate_id().unwrap(), &b.layout()).timestamp,
+            1_700_000_000_006
+        );
+    }
+}
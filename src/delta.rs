@@ -0,0 +1,235 @@
+//! Compact storage for runs of IDs.
+//!
+//! The first ID is written in full and every later one as its difference
+//! from the previous ID, as a zigzag LEB128 varint. Consecutive IDs from one
+//! generator usually differ by 1, which takes a single byte; a new
+//! millisecond under the default layout costs about four.
+//!
+//! [`encode_frame`] wraps such a run in a header naming its layout, so a
+//! receiver can decode the IDs without being configured to match.
+
+use crate::{Layout, SwitflakeError};
+
+// Version of the frame format written by `encode_frame`.
+const FRAME_VERSION: u8 = 1;
+// Version byte, seven layout widths and offsets, flags, then the ID count.
+const FRAME_HEADER_LEN: usize = 1 + 7 + 1 + 4;
+const FLAG_VERSIONED: u8 = 1;
+const FLAG_KINDED: u8 = 2;
+
+/// Encodes `ids` as described in the [module docs](self). Any order is
+/// accepted, but sorted runs encode smallest.
+pub fn delta_encode(ids: &[u64]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(ids.len() * 2);
+    let mut previous = 0u64;
+    for &id in ids {
+        let delta = id.wrapping_sub(previous) as i64;
+        // zigzag: 작은 음수도 짧게 인코딩
+        write_varint(&mut out, ((delta << 1) ^ (delta >> 63)) as u64);
+        previous = id;
+    }
+    out
+}
+
+/// Reverses [`delta_encode`].
+pub fn delta_decode(bytes: &[u8]) -> Result<Vec<u64>, SwitflakeError> {
+    let mut ids = Vec::new();
+    let mut previous = 0u64;
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (zigzag, len) = read_varint(rest)?;
+        rest = &rest[len..];
+        let delta = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+        previous = previous.wrapping_add(delta as u64);
+        ids.push(previous);
+    }
+    Ok(ids)
+}
+
+/// Encodes `ids` as a self-describing frame for shipping a batch over the
+/// wire: a 13-byte header holding the format version, every width and
+/// offset of `layout` and the number of IDs, followed by the IDs as
+/// [`delta_encode`] writes them.
+///
+/// # Panics
+///
+/// Panics if `ids` holds more than `u32::MAX` IDs.
+pub fn encode_frame(ids: &[u64], layout: &Layout) -> Vec<u8> {
+    let count = u32::try_from(ids.len()).expect("frame holds at most u32::MAX IDs");
+    let mut out = Vec::with_capacity(FRAME_HEADER_LEN + ids.len() * 2);
+    out.push(FRAME_VERSION);
+    for value in [
+        layout.timestamp_bits,
+        layout.timestamp_shift,
+        layout.node_bits,
+        layout.node_shift,
+        layout.thread_bits,
+        layout.counter_bits,
+        layout.sequence_shift,
+    ] {
+        out.push(value as u8);
+    }
+    let mut flags = 0;
+    if layout.versioned {
+        flags |= FLAG_VERSIONED;
+    }
+    if layout.kinded {
+        flags |= FLAG_KINDED;
+    }
+    out.push(flags);
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&delta_encode(ids));
+    out
+}
+
+/// Reverses [`encode_frame`], returning the layout the IDs were sent under
+/// along with them.
+///
+/// Fails with [`SwitflakeError::ParseError`] if the header is cut short,
+/// comes from an unknown format version or announces a different number
+/// of IDs than follow it, and with [`SwitflakeError::InvalidLayout`] if the
+/// layout in it is unusable.
+pub fn decode_frame(bytes: &[u8]) -> Result<(Layout, Vec<u64>), SwitflakeError> {
+    if bytes.len() < FRAME_HEADER_LEN {
+        return Err(SwitflakeError::ParseError("truncated frame header"));
+    }
+    let (header, body) = bytes.split_at(FRAME_HEADER_LEN);
+    if header[0] != FRAME_VERSION {
+        return Err(SwitflakeError::ParseError("unsupported frame version"));
+    }
+    let field = |i: usize| header[1 + i] as u32;
+    let layout = Layout {
+        timestamp_bits: field(0),
+        timestamp_shift: field(1),
+        node_bits: field(2),
+        node_shift: field(3),
+        thread_bits: field(4),
+        counter_bits: field(5),
+        sequence_shift: field(6),
+        versioned: header[8] & FLAG_VERSIONED != 0,
+        kinded: header[8] & FLAG_KINDED != 0,
+    };
+    layout.check_fields()?;
+    let count = u32::from_le_bytes(header[9..].try_into().unwrap());
+    let ids = delta_decode(body)?;
+    if ids.len() != count as usize {
+        return Err(SwitflakeError::ParseError(
+            "frame ID count does not match its contents",
+        ));
+    }
+    Ok((layout, ids))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8]) -> Result<(u64, usize), SwitflakeError> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        if shift >= 64 || (shift == 63 && byte > 1) {
+            return Err(SwitflakeError::ParseError("delta varint overflows u64"));
+        }
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(SwitflakeError::ParseError("truncated delta varint"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[test]
+    fn test_round_trip_monotonic_run() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(17).expect("Failed to create Switflake");
+        let ids: Vec<u64> = swit.iter().take(2_000).collect();
+
+        let encoded = delta_encode(&ids);
+        assert_eq!(delta_decode(&encoded), Ok(ids.clone()));
+        assert!(
+            encoded.len() * 4 < ids.len() * 8,
+            "{} bytes for {} IDs",
+            encoded.len(),
+            ids.len()
+        );
+    }
+
+    #[test]
+    fn test_frame_round_trip_carries_layout() {
+        let _guard = serial_guard();
+        let layout = Layout {
+            timestamp_shift: 0,
+            node_shift: 41,
+            sequence_shift: 53,
+            versioned: true,
+            ..Layout::default()
+        };
+        let mut swit = Switflake::builder()
+            .node_id(17)
+            .layout(layout)
+            .version(3)
+            .build()
+            .expect("Failed to create Switflake");
+        let ids: Vec<u64> = swit.iter().take(500).collect();
+
+        let frame = encode_frame(&ids, &layout);
+        let (decoded_layout, decoded) = decode_frame(&frame).expect("Failed to decode frame");
+        assert_eq!(decoded_layout, layout);
+        assert_eq!(decoded, ids);
+        assert_eq!(encode_frame(&[], &layout).len(), FRAME_HEADER_LEN);
+        assert_eq!(
+            decode_frame(&encode_frame(&[], &layout)),
+            Ok((layout, Vec::new()))
+        );
+    }
+
+    #[test]
+    fn test_malformed_frames_rejected() {
+        let frame = encode_frame(&[1, 2, 3], &Layout::default());
+        // 헤더가 잘린 프레임
+        for len in 0..FRAME_HEADER_LEN {
+            assert_eq!(
+                decode_frame(&frame[..len]),
+                Err(SwitflakeError::ParseError("truncated frame header"))
+            );
+        }
+        assert_eq!(
+            decode_frame(&frame[..frame.len() - 1]),
+            Err(SwitflakeError::ParseError(
+                "frame ID count does not match its contents"
+            ))
+        );
+        let mut future = frame.clone();
+        future[0] = FRAME_VERSION + 1;
+        assert_eq!(
+            decode_frame(&future),
+            Err(SwitflakeError::ParseError("unsupported frame version"))
+        );
+        let mut overlapping = frame;
+        overlapping[4] = 30;
+        assert!(matches!(
+            decode_frame(&overlapping),
+            Err(SwitflakeError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsorted_and_edge_values() {
+        let ids = [u64::MAX, 0, 1 << 63, 5, 4, u64::MAX];
+        assert_eq!(delta_decode(&delta_encode(&ids)), Ok(ids.to_vec()));
+        assert_eq!(delta_decode(&[]), Ok(Vec::new()));
+        assert!(delta_decode(&[0x80]).is_err());
+        assert!(delta_decode(&[0xFF; 11]).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use crate::{
+    decode_base32, decode_base36, decode_base62, decode_base62_padded, decode_with_check,
+    encode_base32, encode_base36, encode_base62, encode_base62_padded, encode_with_check, from_hex,
+    to_hex, Alphabet, Clock, Switflake, SwitflakeError,
+};
+
+/// A text encoding of IDs, for code that picks one at runtime, e.g. from
+/// configuration. Every built-in encoding has an implementation, and
+/// custom ones plug in alongside them in an [`EncoderRegistry`].
+pub trait IdEncoder: Send + Sync {
+    fn encode(&self, id: u64) -> String;
+
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError>;
+}
+
+/// [`encode_base62`] and [`decode_base62`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Base62;
+
+/// [`encode_base62_padded`] and [`decode_base62_padded`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Base62Padded;
+
+/// [`encode_with_check`] and [`decode_with_check`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Base62Checked;
+
+/// [`encode_base32`] and [`decode_base32`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crockford;
+
+/// [`encode_base36`] and [`decode_base36`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Base36;
+
+/// [`to_hex`] and [`from_hex`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hex;
+
+impl IdEncoder for Base62 {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        encode_base62(id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        decode_base62(s)
+    }
+}
+
+impl IdEncoder for Base62Padded {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        encode_base62_padded(id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        decode_base62_padded(s)
+    }
+}
+
+impl IdEncoder for Base62Checked {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        encode_with_check(id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        decode_with_check(s)
+    }
+}
+
+impl IdEncoder for Crockford {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        encode_base32(id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        decode_base32(s)
+    }
+}
+
+impl IdEncoder for Base36 {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        encode_base36(id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        decode_base36(s)
+    }
+}
+
+impl IdEncoder for Hex {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        to_hex(id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        from_hex(s)
+    }
+}
+
+impl IdEncoder for Alphabet {
+    #[inline]
+    fn encode(&self, id: u64) -> String {
+        Alphabet::encode(self, id)
+    }
+
+    #[inline]
+    fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        Alphabet::decode(self, s)
+    }
+}
+
+/// Encoders looked up by name.
+#[derive(Default)]
+pub struct EncoderRegistry {
+    encoders: HashMap<String, Box<dyn IdEncoder>>,
+}
+
+impl EncoderRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry holding the built-in encoders as `base62`,
+    /// `base62_padded`, `base62_checked`, `crockford`, `base36` and `hex`.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register("base62", Base62);
+        registry.register("base62_padded", Base62Padded);
+        registry.register("base62_checked", Base62Checked);
+        registry.register("crockford", Crockford);
+        registry.register("base36", Base36);
+        registry.register("hex", Hex);
+        registry
+    }
+
+    /// Adds `encoder` under `name`, replacing any encoder already there.
+    pub fn register(&mut self, name: impl Into<String>, encoder: impl IdEncoder + 'static) {
+        self.encoders.insert(name.into(), Box::new(encoder));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn IdEncoder> {
+        self.encoders.get(name).map(|encoder| &**encoder)
+    }
+}
+
+impl<C> Switflake<C> {
+    /// Encodes `id` with `encoder`, e.g. one picked from an
+    /// [`EncoderRegistry`].
+    pub fn encode_with(&self, id: u64, encoder: &dyn IdEncoder) -> String {
+        encoder.encode(id)
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Generates an ID and returns it along with its encoding by
+    /// `encoder`, e.g. the raw value to store and the string to respond
+    /// with, so the two can't drift apart.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_encoded(
+        &mut self,
+        encoder: &dyn IdEncoder,
+    ) -> Result<(u64, String), SwitflakeError> {
+        let id = self.generate_id()?;
+        Ok((id, encoder.encode(id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+
+    // 뒤집은 10진수: 트레이트 객체로 다룰 수 있는지만 확인
+    struct ReversedDecimal;
+
+    impl IdEncoder for ReversedDecimal {
+        fn encode(&self, id: u64) -> String {
+            id.to_string().chars().rev().collect()
+        }
+
+        fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+            let digits: String = s.chars().rev().collect();
+            digits
+                .parse()
+                .map_err(|_| SwitflakeError::ParseError("invalid reversed decimal"))
+        }
+    }
+
+    #[test]
+    fn test_custom_encoder_round_trips() {
+        let _guard = serial_guard();
+        let mut registry = EncoderRegistry::with_builtins();
+        registry.register("reversed", ReversedDecimal);
+        let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+        let id = swit.generate_id().expect("Failed to generate ID");
+
+        let encoder = registry.get("reversed").expect("encoder not registered");
+        let encoded = swit.encode_with(id, encoder);
+        assert_eq!(encoded, id.to_string().chars().rev().collect::<String>());
+        assert_eq!(encoder.decode(&encoded), Ok(id));
+        assert!(registry.get("missing").is_none());
+    }
+
+    #[test]
+    fn test_generate_encoded_pairs_id_with_string() {
+        let _guard = serial_guard();
+        let registry = EncoderRegistry::with_builtins();
+        let mut swit = Switflake::new(3).expect("Failed to create Switflake");
+        for name in ["base62", "hex"] {
+            let encoder = registry.get(name).expect("encoder not registered");
+            let (id, encoded) = swit
+                .generate_encoded(encoder)
+                .expect("Failed to generate ID");
+            assert_eq!(encoded, encoder.encode(id));
+            assert_eq!(encoder.decode(&encoded), Ok(id));
+        }
+    }
+
+    #[test]
+    fn test_builtin_encoders_match_free_functions() {
+        let registry = EncoderRegistry::with_builtins();
+        let id = 7_159_857_416_126_464_001;
+        let cases = [
+            ("base62", encode_base62(id)),
+            ("base62_padded", encode_base62_padded(id)),
+            ("base62_checked", encode_with_check(id)),
+            ("crockford", encode_base32(id)),
+            ("base36", encode_base36(id)),
+            ("hex", to_hex(id)),
+        ];
+        for (name, expected) in cases {
+            let encoder = registry.get(name).expect("encoder not registered");
+            assert_eq!(encoder.encode(id), expected, "encoder {}", name);
+            assert_eq!(encoder.decode(&expected), Ok(id), "encoder {}", name);
+        }
+    }
+}
@@ -0,0 +1,758 @@
+use std::fmt;
+
+use crate::{Clock, Switflake, SwitflakeError};
+
+pub(crate) const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+const BASE36_ALPHABET: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Crockford's base32: no I, L, O or U.
+pub(crate) const BASE32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+pub(crate) const INVALID: u8 = 0xFF;
+
+// Reverse lookup: ASCII byte -> digit value, INVALID when not in the alphabet.
+pub(crate) const BASE62_TABLE: [u8; 256] = reverse_table(BASE62_ALPHABET);
+const BASE32_TABLE: [u8; 256] = crockford_table();
+const BASE36_TABLE: [u8; 256] = case_insensitive_table(BASE36_ALPHABET);
+const HEX_TABLE: [u8; 256] = case_insensitive_table(b"0123456789abcdef");
+
+// Longest encoding of a u64 in any supported radix (base32 needs 13 digits).
+const MAX_DIGITS: usize = 13;
+
+const fn reverse_table(alphabet: &[u8]) -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < alphabet.len() {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+// Decoding is case-insensitive and reads the look-alikes I/L as 1 and O as 0.
+const fn crockford_table() -> [u8; 256] {
+    let mut table = reverse_table(BASE32_ALPHABET);
+    let mut i = 0;
+    while i < BASE32_ALPHABET.len() {
+        let c = BASE32_ALPHABET[i];
+        if c.is_ascii_uppercase() {
+            table[c.to_ascii_lowercase() as usize] = i as u8;
+        }
+        i += 1;
+    }
+    table[b'I' as usize] = 1;
+    table[b'i' as usize] = 1;
+    table[b'L' as usize] = 1;
+    table[b'l' as usize] = 1;
+    table[b'O' as usize] = 0;
+    table[b'o' as usize] = 0;
+    table
+}
+
+// For lowercase alphabets whose uppercase letters decode the same.
+const fn case_insensitive_table(alphabet: &[u8]) -> [u8; 256] {
+    let mut table = reverse_table(alphabet);
+    let mut i = 0;
+    while i < alphabet.len() {
+        let c = alphabet[i];
+        if c.is_ascii_lowercase() {
+            table[c.to_ascii_uppercase() as usize] = i as u8;
+        }
+        i += 1;
+    }
+    table
+}
+
+/// Writes `id` as big-endian digits of `alphabet.len()` into the tail of
+/// `buf`, returning the written part.
+#[inline]
+fn encode_into<'a>(id: u64, alphabet: &[u8], buf: &'a mut [u8; MAX_DIGITS]) -> &'a str {
+    let radix = alphabet.len() as u64;
+    let mut i = buf.len();
+    let mut n = id;
+    loop {
+        i -= 1;
+        buf[i] = alphabet[(n % radix) as usize];
+        n /= radix;
+        if n == 0 {
+            break;
+        }
+    }
+    // 알파벳은 모두 ASCII
+    std::str::from_utf8(&buf[i..]).expect("alphabet is ASCII")
+}
+
+/// Decodes `s` as big-endian digits of `radix`, using `table` to map bytes
+/// to digit values. Never allocates.
+#[inline]
+pub(crate) fn decode_digits(s: &str, radix: u64, table: &[u8; 256]) -> Option<u64> {
+    if s.is_empty() {
+        return None;
+    }
+    let mut value: u64 = 0;
+    for &byte in s.as_bytes() {
+        let digit = table[byte as usize];
+        if digit == INVALID || digit as u64 >= radix {
+            return None;
+        }
+        value = value.checked_mul(radix)?.checked_add(digit as u64)?;
+    }
+    Some(value)
+}
+
+#[must_use]
+pub fn encode_base62(id: u64) -> String {
+    encode_into(id, BASE62_ALPHABET, &mut [0; MAX_DIGITS]).to_owned()
+}
+
+pub fn decode_base62(s: &str) -> Result<u64, SwitflakeError> {
+    decode_digits(s, 62, &BASE62_TABLE).ok_or(SwitflakeError::ParseError(
+        "invalid base62 string or value overflows u64",
+    ))
+}
+
+/// Decodes base62 straight from the bytes of `s`, with no allocation and
+/// no error to build: `None` on any character outside the alphabet or a
+/// value past `u64::MAX`. The fast-reject path for untrusted input, e.g.
+/// IDs in request paths.
+#[inline]
+pub fn try_decode_base62_nonalloc(s: &str) -> Option<u64> {
+    decode_digits(s, 62, &BASE62_TABLE)
+}
+
+/// Whether `s` is base62 that decodes to a `u64`.
+#[inline]
+pub fn is_valid_base62(s: &str) -> bool {
+    try_decode_base62_nonalloc(s).is_some()
+}
+
+// Digits in base62 u64::MAX, the width of the padded encoding.
+const BASE62_WIDTH: usize = 11;
+
+/// Base62 left-padded with `0` to the full 11 digits, so that string order
+/// matches numeric order: use it for string keys that get range-scanned.
+#[must_use]
+pub fn encode_base62_padded(id: u64) -> String {
+    let mut buf = [BASE62_ALPHABET[0]; MAX_DIGITS];
+    encode_into(id, BASE62_ALPHABET, &mut buf);
+    std::str::from_utf8(&buf[MAX_DIGITS - BASE62_WIDTH..])
+        .expect("alphabet is ASCII")
+        .to_owned()
+}
+
+/// Decodes the output of [`encode_base62_padded`]. Anything but exactly 11
+/// digits is rejected, so keys of the wrong width can't slip into a range.
+pub fn decode_base62_padded(s: &str) -> Result<u64, SwitflakeError> {
+    if s.len() != BASE62_WIDTH {
+        return Err(SwitflakeError::ParseError(
+            "padded base62 must be exactly 11 digits",
+        ));
+    }
+    decode_base62(s)
+}
+
+/// Luhn mod 62 check character for base62 `digits`.
+fn luhn_check_char(digits: &[u8]) -> u8 {
+    let mut sum = 0;
+    // 오른쪽 끝(검사 문자 바로 앞)부터 한 자리씩 걸러 두 배
+    for (i, &digit) in digits.iter().rev().enumerate() {
+        let mut value = BASE62_TABLE[digit as usize] as u32;
+        if i % 2 == 0 {
+            value *= 2;
+            value = value / 62 + value % 62;
+        }
+        sum += value;
+    }
+    BASE62_ALPHABET[((62 - sum % 62) % 62) as usize]
+}
+
+/// Base62 followed by a Luhn mod 62 check character, for IDs that people
+/// type in by hand, e.g. on support tickets. The check catches every
+/// single mistyped character and most swaps of two neighbours.
+#[must_use]
+pub fn encode_with_check(id: u64) -> String {
+    let mut encoded = encode_base62(id);
+    let check = luhn_check_char(encoded.as_bytes());
+    encoded.push(check as char);
+    encoded
+}
+
+/// Decodes the output of [`encode_with_check`], failing with
+/// [`SwitflakeError::ChecksumMismatch`] when the check character doesn't
+/// match.
+pub fn decode_with_check(s: &str) -> Result<u64, SwitflakeError> {
+    let (digits, check) = match s.as_bytes().split_last() {
+        Some((&check, digits)) if !digits.is_empty() => (digits, check),
+        _ => {
+            return Err(SwitflakeError::ParseError(
+                "checked base62 needs a digit and a check character",
+            ))
+        }
+    };
+    let id = decode_base62(std::str::from_utf8(digits).expect("split at an ASCII byte"))?;
+    if BASE62_TABLE[check as usize] == INVALID {
+        return Err(SwitflakeError::ParseError("invalid check character"));
+    }
+    if luhn_check_char(digits) != check {
+        return Err(SwitflakeError::ChecksumMismatch);
+    }
+    Ok(id)
+}
+
+/// Crockford base32, uppercase and without padding.
+#[must_use]
+pub fn encode_base32(id: u64) -> String {
+    encode_into(id, BASE32_ALPHABET, &mut [0; MAX_DIGITS]).to_owned()
+}
+
+/// Decodes Crockford base32, accepting lowercase and the I/L/O look-alikes.
+pub fn decode_base32(s: &str) -> Result<u64, SwitflakeError> {
+    decode_digits(s, 32, &BASE32_TABLE).ok_or(SwitflakeError::ParseError(
+        "invalid base32 string or value overflows u64",
+    ))
+}
+
+/// Lowercase base36 (`0-9a-z`) without padding, for systems that keep IDs
+/// in case-insensitive columns.
+#[must_use]
+pub fn encode_base36(id: u64) -> String {
+    encode_into(id, BASE36_ALPHABET, &mut [0; MAX_DIGITS]).to_owned()
+}
+
+/// Decodes base36 of either case.
+pub fn decode_base36(s: &str) -> Result<u64, SwitflakeError> {
+    decode_digits(s, 36, &BASE36_TABLE).ok_or(SwitflakeError::ParseError(
+        "invalid base36 string or value overflows u64",
+    ))
+}
+
+/// Lowercase hex left-padded to 16 digits, so that string order matches
+/// numeric order.
+#[must_use]
+pub fn to_hex(id: u64) -> String {
+    format!("{:016x}", id)
+}
+
+/// Parses exactly 16 hex digits of either case, optionally after `0x`.
+pub fn from_hex(s: &str) -> Result<u64, SwitflakeError> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    if digits.len() != 16 {
+        return Err(SwitflakeError::ParseError(
+            "hex ID must be exactly 16 digits",
+        ));
+    }
+    decode_digits(digits, 16, &HEX_TABLE).ok_or(SwitflakeError::ParseError("invalid hex string"))
+}
+
+/// Decodes an ID written in decimal, hex or base62, for importing data that
+/// mixes them. Tries them in that order: plain digits are read as decimal,
+/// then a `0x` prefix or exactly 16 hex digits as [`from_hex`], then
+/// anything else as [`decode_base62`]. A short base62 ID made only of digits
+/// is thus misread as decimal, and a 16-character one made only of hex
+/// digits as hex.
+pub fn decode_any(s: &str) -> Result<u64, SwitflakeError> {
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        if let Ok(id) = s.parse() {
+            return Ok(id);
+        }
+    }
+    let looks_hex = s.starts_with("0x")
+        || s.starts_with("0X")
+        || (s.len() == 16 && s.bytes().all(|b| b.is_ascii_hexdigit()));
+    if looks_hex {
+        if let Ok(id) = from_hex(s) {
+            return Ok(id);
+        }
+    }
+    try_decode_base62_nonalloc(s).ok_or(SwitflakeError::ParseError(
+        "not a decimal, hex or base62 ID",
+    ))
+}
+
+/// [`decode_any`] for every line of `input`, with surrounding whitespace
+/// trimmed, so results line up with line numbers.
+pub fn decode_lines(input: &str) -> Vec<Result<u64, SwitflakeError>> {
+    input.lines().map(|line| decode_any(line.trim())).collect()
+}
+
+/// Crockford base32 left-padded with `0` to the full 13 digits, so that
+/// string order matches numeric order.
+#[inline]
+pub(crate) fn encode_base32_fixed(id: u64) -> [u8; MAX_DIGITS] {
+    let mut buf = [BASE32_ALPHABET[0]; MAX_DIGITS];
+    encode_into(id, BASE32_ALPHABET, &mut buf);
+    buf
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Generates an ID and returns it as `prefix` followed by 13 digits of
+    /// Crockford base32, e.g. `evt#01N9ZQ3V5K0G1`.
+    ///
+    /// The encoding is always zero-padded to full width, so keys sharing a
+    /// prefix sort as strings in the same order as the IDs themselves.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_sortable_key(&mut self, prefix: &str) -> Result<String, SwitflakeError> {
+        let id = self.generate_id()?;
+        let digits = encode_base32_fixed(id);
+        let mut key = String::with_capacity(prefix.len() + MAX_DIGITS);
+        key.push_str(prefix);
+        key.push_str(std::str::from_utf8(&digits).expect("alphabet is ASCII"));
+        Ok(key)
+    }
+}
+
+/// A caller-supplied digit alphabet, for interop with systems that expect
+/// a particular ordering, such as Bitcoin's or Flickr's base58:
+///
+/// ```
+/// use switflake::Alphabet;
+///
+/// let base58 =
+///     Alphabet::new("123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz").unwrap();
+/// assert_eq!(base58.encode(57), "z");
+/// assert_eq!(base58.decode("21"), Ok(58));
+/// ```
+///
+/// The radix is the alphabet's length. It also plugs into an
+/// [`EncoderRegistry`](crate::EncoderRegistry) as an
+/// [`IdEncoder`](crate::IdEncoder).
+#[derive(Clone)]
+pub struct Alphabet {
+    digits: Box<[u8]>,
+    table: [u8; 256],
+}
+
+impl Alphabet {
+    /// Fails with [`SwitflakeError::InvalidAlphabet`] unless `digits` is 32
+    /// to 64 printable ASCII characters, none of them repeated.
+    pub fn new(digits: &str) -> Result<Self, SwitflakeError> {
+        let bytes = digits.as_bytes();
+        if !(32..=64).contains(&bytes.len()) {
+            return Err(SwitflakeError::InvalidAlphabet(
+                "alphabet must have 32 to 64 characters",
+            ));
+        }
+        if !bytes.iter().all(u8::is_ascii_graphic) {
+            return Err(SwitflakeError::InvalidAlphabet(
+                "alphabet must be printable ASCII",
+            ));
+        }
+        let table = reverse_table(bytes);
+        if bytes
+            .iter()
+            .enumerate()
+            .any(|(i, &c)| table[c as usize] != i as u8)
+        {
+            return Err(SwitflakeError::InvalidAlphabet(
+                "alphabet repeats a character",
+            ));
+        }
+        Ok(Alphabet {
+            digits: bytes.into(),
+            table,
+        })
+    }
+
+    pub fn radix(&self) -> u64 {
+        self.digits.len() as u64
+    }
+
+    #[must_use]
+    pub fn encode(&self, id: u64) -> String {
+        encode_into(id, &self.digits, &mut [0; MAX_DIGITS]).to_owned()
+    }
+
+    pub fn decode(&self, s: &str) -> Result<u64, SwitflakeError> {
+        decode_digits(s, self.radix(), &self.table).ok_or(SwitflakeError::ParseError(
+            "character outside the alphabet or value overflows u64",
+        ))
+    }
+}
+
+impl fmt::Debug for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 검사를 거쳤으므로 ASCII
+        let digits = std::str::from_utf8(&self.digits).expect("alphabet is ASCII");
+        f.debug_tuple("Alphabet").field(&digits).finish()
+    }
+}
+
+/// Formats the wrapped ID like [`encode_base62`] without allocating, e.g.
+/// `write!(log, "id={}", Base62Display(id))`. Width and alignment flags
+/// apply as for any string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Base62Display(pub u64);
+
+impl fmt::Display for Base62Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(encode_into(self.0, BASE62_ALPHABET, &mut [0; MAX_DIGITS]))
+    }
+}
+
+impl From<Base62Display> for String {
+    fn from(display: Base62Display) -> Self {
+        encode_base62(display.0)
+    }
+}
+
+/// Formats the wrapped ID like [`encode_base32`] without allocating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Base32Display(pub u64);
+
+impl fmt::Display for Base32Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(encode_into(self.0, BASE32_ALPHABET, &mut [0; MAX_DIGITS]))
+    }
+}
+
+impl From<Base32Display> for String {
+    fn from(display: Base32Display) -> Self {
+        encode_base32(display.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base62_round_trip() {
+        for id in [0, 1, 61, 62, 3843, 3844, 1 << 40, u64::MAX - 1, u64::MAX] {
+            assert_eq!(decode_base62(&encode_base62(id)), Ok(id));
+        }
+        assert_eq!(encode_base62(0), "0");
+        assert_eq!(encode_base62(61), "z");
+        assert_eq!(encode_base62(62), "10");
+        assert_eq!(encode_base62(u64::MAX), "LygHa16AHYF");
+    }
+
+    #[test]
+    fn test_base62_rejects_invalid() {
+        assert!(decode_base62("").is_err());
+        assert!(decode_base62("abc-").is_err());
+        // u64::MAX + 1
+        assert!(decode_base62("LygHa16AHYG").is_err());
+        assert!(decode_base62("zzzzzzzzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_nonalloc_base62_matches_decoder() {
+        let valid = [
+            "0",
+            "z",
+            "7n42DGM5Tfl",
+            "LygHa16AHYF",
+            &encode_base62(424_242),
+        ];
+        for s in valid {
+            assert!(is_valid_base62(s), "{}", s);
+            assert_eq!(try_decode_base62_nonalloc(s), decode_base62(s).ok());
+        }
+        // u64::MAX 다음 값은 넘침
+        let invalid = ["", "ab-c", "한글", " 1", "LygHa16AHYG", "zzzzzzzzzzzz"];
+        for s in invalid {
+            assert!(!is_valid_base62(s), "{}", s);
+            assert_eq!(try_decode_base62_nonalloc(s), None);
+            assert!(decode_base62(s).is_err());
+        }
+    }
+
+    #[test]
+    fn test_padded_base62_sorts_like_ids() {
+        assert_eq!(encode_base62_padded(0), "00000000000");
+        assert_eq!(encode_base62_padded(62), "00000000010");
+        assert_eq!(encode_base62_padded(u64::MAX), "LygHa16AHYF");
+        let mut ids = vec![
+            0,
+            1,
+            9,
+            10,
+            61,
+            62,
+            3843,
+            3844,
+            1 << 40,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..1_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            ids.push(state >> (state % 64));
+        }
+        for pair in ids.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            // 숫자 순서와 문자열 순서가 일치
+            assert_eq!(
+                a.cmp(&b),
+                encode_base62_padded(a).cmp(&encode_base62_padded(b))
+            );
+        }
+        for &id in &ids {
+            assert_eq!(decode_base62_padded(&encode_base62_padded(id)), Ok(id));
+        }
+        assert!(decode_base62_padded("10").is_err());
+        assert!(decode_base62_padded("000000000010").is_err());
+        assert!(decode_base62_padded("LygHa16AHYG").is_err());
+    }
+
+    #[test]
+    fn test_base36_round_trip() {
+        for id in [0, 1, 35, 36, 1 << 40, 7_159_857_416_126_464_001, u64::MAX] {
+            let encoded = encode_base36(id);
+            assert_eq!(decode_base36(&encoded), Ok(id));
+            assert_eq!(decode_base36(&encoded.to_uppercase()), Ok(id));
+        }
+        assert_eq!(encode_base36(35), "z");
+        assert_eq!(encode_base36(u64::MAX), "3w5e11264sgsf");
+        assert_eq!(decode_base36("3W5E11264sgsF"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn test_base36_rejects_invalid() {
+        assert!(decode_base36("").is_err());
+        assert!(decode_base36("abc_").is_err());
+        // u64::MAX + 1
+        assert!(decode_base36("3w5e11264sgsg").is_err());
+        assert!(decode_base36("zzzzzzzzzzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        for id in [0, 1, 255, 1 << 40, 7_159_857_416_126_464_001, u64::MAX] {
+            assert_eq!(from_hex(&to_hex(id)), Ok(id));
+            assert_eq!(from_hex(&format!("0x{}", to_hex(id))), Ok(id));
+            assert_eq!(from_hex(&to_hex(id).to_uppercase()), Ok(id));
+        }
+        assert_eq!(to_hex(255), "00000000000000ff");
+        assert_eq!(from_hex("0X00000000000000Ff"), Ok(255));
+        // 고정 폭이라 문자열 순서가 숫자 순서와 같음
+        assert!(to_hex(0xfff) < to_hex(0x1000));
+    }
+
+    #[test]
+    fn test_hex_rejects_invalid() {
+        assert!(from_hex("000000000000000").is_err());
+        assert!(from_hex("00000000000000g0").is_err());
+        assert!(from_hex("00000000000000000").is_err());
+        assert!(from_hex("0x").is_err());
+        assert!(from_hex("+000000000000000").is_err());
+    }
+
+    #[test]
+    fn test_checked_base62_round_trip() {
+        for id in [0, 1, 61, 62, 1 << 40, 7_159_857_416_126_464_001, u64::MAX] {
+            let encoded = encode_with_check(id);
+            assert_eq!(encoded.len(), encode_base62(id).len() + 1);
+            assert!(encoded.starts_with(&encode_base62(id)));
+            assert_eq!(decode_with_check(&encoded), Ok(id));
+        }
+        assert!(decode_with_check("").is_err());
+        assert!(decode_with_check("5").is_err());
+        assert!(decode_with_check("10-").is_err());
+    }
+
+    #[test]
+    fn test_checked_base62_detects_single_typo() {
+        let encoded = encode_with_check(7_159_857_416_126_464_001);
+        // 어느 자리든 한 글자만 틀리면 검사 문자로 잡힘
+        for position in 0..encoded.len() {
+            for &replacement in BASE62_ALPHABET.iter() {
+                let mut typo = encoded.clone().into_bytes();
+                if typo[position] == replacement {
+                    continue;
+                }
+                typo[position] = replacement;
+                let typo = String::from_utf8(typo).unwrap();
+                assert!(
+                    matches!(
+                        decode_with_check(&typo),
+                        Err(SwitflakeError::ChecksumMismatch | SwitflakeError::ParseError(_))
+                    ),
+                    "{} accepted",
+                    typo
+                );
+            }
+        }
+        let mut typo = encoded.into_bytes();
+        typo[3] = if typo[3] == b'x' { b'y' } else { b'x' };
+        assert_eq!(
+            decode_with_check(std::str::from_utf8(&typo).unwrap()),
+            Err(SwitflakeError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        for id in [0, 31, 32, 1 << 40, u64::MAX] {
+            assert_eq!(decode_base32(&encode_base32(id)), Ok(id));
+        }
+        assert_eq!(encode_base32(u64::MAX), "FZZZZZZZZZZZZ");
+        assert_eq!(decode_base32("fzzzzzzzzzzzz"), Ok(u64::MAX));
+        assert_eq!(decode_base32("1O"), decode_base32("10"));
+        assert_eq!(decode_base32("L"), Ok(1));
+        assert!(decode_base32("U").is_err());
+        assert!(decode_base32("G0000000000000").is_err());
+    }
+
+    #[test]
+    fn test_sortable_keys_sort_in_generation_order() {
+        use crate::clock::MockClock;
+        use crate::pool::serial_guard;
+
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let mut keys = Vec::new();
+        for i in 0..40 {
+            keys.push(
+                swit.generate_sortable_key("evt#")
+                    .expect("Failed to generate key"),
+            );
+            // 자릿수가 바뀌는 경계도 포함
+            clock.advance(if i % 10 == 0 { 1 << 20 } else { 0 });
+        }
+        assert!(keys
+            .iter()
+            .all(|k| k.len() == 4 + 13 && k.starts_with("evt#")));
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(sorted, keys);
+        assert_eq!(
+            decode_base32(&keys[0]["evt#".len()..]).map(|id| id >> 23),
+            Ok(1_700_000_000_000)
+        );
+        assert_eq!(&encode_base32_fixed(1), b"0000000000001");
+    }
+
+    #[test]
+    fn test_display_wrappers_match_encoders() {
+        use std::fmt::Write;
+
+        for id in [0, 12_345, 7_159_857_416_126_464_001, u64::MAX] {
+            assert_eq!(Base62Display(id).to_string(), encode_base62(id));
+            assert_eq!(Base32Display(id).to_string(), encode_base32(id));
+        }
+
+        let mut out = String::new();
+        write!(out, "id={}/{}", Base62Display(62), Base32Display(32)).unwrap();
+        assert_eq!(out, "id=10/10");
+        // 포매터 플래그(폭, 정렬)가 적용됨
+        assert_eq!(
+            format!("{:>5}|{:<4}|", Base62Display(62), Base32Display(1)),
+            "   10|1   |"
+        );
+    }
+
+    #[test]
+    fn test_display_wrappers_are_copy_values() {
+        fn assert_copy<T: Copy>(_: T) {}
+
+        let id = 7_159_857_416_126_464_001;
+        let display = Base62Display(id);
+        assert_copy(display);
+        assert_copy(Base32Display(id));
+        // 복사 후에도 같은 문자열
+        let copy = display;
+        assert_eq!(display.to_string(), copy.to_string());
+        assert_eq!(format!("{}", display), format!("{}", display));
+
+        let a: String = display.into();
+        let b: String = Base32Display(id).into();
+        assert_eq!((a, b), (encode_base62(id), encode_base32(id)));
+        let names: Vec<String> = [1, 62]
+            .map(Base62Display)
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(names, ["1", "10"]);
+    }
+
+    #[test]
+    fn test_custom_alphabet() {
+        let bitcoin = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+        let flickr = "123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+        let (bitcoin, flickr) = (
+            Alphabet::new(bitcoin).unwrap(),
+            Alphabet::new(flickr).unwrap(),
+        );
+        assert_eq!(bitcoin.radix(), 58);
+        for id in [0, 1, 57, 58, 1 << 40, u64::MAX] {
+            assert_eq!(bitcoin.decode(&bitcoin.encode(id)), Ok(id));
+            assert_eq!(flickr.decode(&flickr.encode(id)), Ok(id));
+        }
+        // 같은 값이라도 순서가 다르면 다른 문자열
+        assert_eq!(bitcoin.encode(0), "1");
+        assert_eq!(bitcoin.encode(9), "A");
+        assert_eq!(flickr.encode(9), "a");
+        assert!(bitcoin.decode("0").is_err());
+
+        assert_eq!(
+            Alphabet::new(&"0123456789abcdefghijklmnopqrstuvwxyz".replace('z', "a")).err(),
+            Some(SwitflakeError::InvalidAlphabet(
+                "alphabet repeats a character"
+            ))
+        );
+        assert_eq!(
+            Alphabet::new("0123456789").err(),
+            Some(SwitflakeError::InvalidAlphabet(
+                "alphabet must have 32 to 64 characters"
+            ))
+        );
+        assert_eq!(
+            Alphabet::new("0123456789 abcdefghijklmnopqrstuvwxyz").err(),
+            Some(SwitflakeError::InvalidAlphabet(
+                "alphabet must be printable ASCII"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_decode_any_formats() {
+        let id = 7_159_857_416_126_464_001;
+        assert_eq!(decode_any(&id.to_string()), Ok(id));
+        assert_eq!(decode_any(&to_hex(id)), Ok(id));
+        assert_eq!(decode_any(&format!("0x{}", to_hex(id))), Ok(id));
+        assert_eq!(decode_any(&encode_base62(id)), Ok(id));
+        // 10진수로 넘치는 숫자열은 base62로 시도
+        assert_eq!(
+            decode_any("18446744073709551616"),
+            Err(SwitflakeError::ParseError(
+                "not a decimal, hex or base62 ID"
+            ))
+        );
+        assert!(decode_any("").is_err());
+    }
+
+    #[test]
+    fn test_decode_lines_mixed() {
+        let id = 7_159_857_416_126_464_001u64;
+        let input = format!(
+            "{}\n  {}\n0x{}\nnot-an-id\n",
+            id,
+            encode_base62(id),
+            to_hex(id)
+        );
+        assert_eq!(
+            decode_lines(&input),
+            vec![
+                Ok(id),
+                Ok(id),
+                Ok(id),
+                Err(SwitflakeError::ParseError(
+                    "not a decimal, hex or base62 ID"
+                )),
+            ]
+        );
+    }
+}
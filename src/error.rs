@@ -0,0 +1,170 @@
+use std::error::Error;
+use std::fmt;
+
+/// With the `serde` feature, errors serialize as a machine-readable code
+/// plus, for variants carrying data, a `detail` field, e.g.
+/// `{"code":"pool_exhausted"}` or `{"code":"duplicate_id","detail":42}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "code", content = "detail", rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum SwitflakeError {
+    /// Every thread-id slot in the pool is currently held.
+    PoolExhausted,
+    /// The counter for the current millisecond has run out.
+    SequenceExhausted,
+    /// The clock reported a time before the Unix epoch or before an
+    /// already-issued timestamp.
+    ClockMovedBackwards,
+    /// The clock jumped further ahead than
+    /// [`SwitflakeBuilder::max_future_skew`](crate::SwitflakeBuilder::max_future_skew)
+    /// allows; `delta` is how far beyond the expected time it read.
+    ClockJumpedForward { delta: std::time::Duration },
+    /// The clock has run past the last timestamp the layout's timestamp
+    /// field can hold, counted from the generator's epoch.
+    TimestampOverflow,
+    /// A layout's fields overlap, don't fit in the ID, or are otherwise
+    /// unusable; the message names the problem.
+    InvalidLayout(&'static str),
+    /// The generator was about to emit an ID it had already issued; see
+    /// [`SwitflakeBuilder::recent_ids_guard`](crate::SwitflakeBuilder::recent_ids_guard).
+    DuplicateId(u64),
+    /// A string or raw value could not be decoded into an ID.
+    ParseError(&'static str),
+    /// A value is too wide for the ID field it was meant for; the message
+    /// names the field.
+    FieldOverflow(&'static str),
+    /// The operating system's random number generator failed.
+    EntropyUnavailable,
+    /// A string's check character doesn't match its digits, so it was
+    /// mistyped; see [`decode_with_check`](crate::decode_with_check).
+    ChecksumMismatch,
+    /// A required ID field was never given a value; the message names the
+    /// field. See [`IdBuilder`](crate::IdBuilder).
+    MissingField(&'static str),
+    /// A new tick could not be recorded in the generator's
+    /// [`StateLog`](crate::StateLog), so no ID from it was issued.
+    StateLogUnavailable,
+    /// A custom [`Alphabet`](crate::Alphabet) has the wrong length or
+    /// characters; the message names the problem.
+    InvalidAlphabet(&'static str),
+    /// Generation panicked; see
+    /// [`Switflake::generate_id_catch`](crate::Switflake::generate_id_catch).
+    Internal,
+    /// More IDs were asked for within a second than
+    /// [`SwitflakeBuilder::max_rate_per_second`](crate::SwitflakeBuilder::max_rate_per_second)
+    /// allows.
+    RateExceeded,
+}
+
+impl fmt::Display for SwitflakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwitflakeError::PoolExhausted => f.write_str("Thread pool full"),
+            SwitflakeError::SequenceExhausted => {
+                f.write_str("Sequence limit reached for this millisecond")
+            }
+            SwitflakeError::ClockMovedBackwards => f.write_str("Time went backwards"),
+            SwitflakeError::ClockJumpedForward { delta } => {
+                write!(f, "Time jumped {:?} ahead", delta)
+            }
+            SwitflakeError::TimestampOverflow => {
+                f.write_str("Timestamp no longer fits in the layout's timestamp field")
+            }
+            SwitflakeError::InvalidLayout(reason) => write!(f, "Invalid layout: {}", reason),
+            SwitflakeError::DuplicateId(id) => write!(f, "Duplicate ID {} about to be issued", id),
+            SwitflakeError::ParseError(reason) => write!(f, "Failed to parse ID: {}", reason),
+            SwitflakeError::FieldOverflow(field) => {
+                write!(f, "Value does not fit in the {} field", field)
+            }
+            SwitflakeError::EntropyUnavailable => f.write_str("System random source unavailable"),
+            SwitflakeError::ChecksumMismatch => f.write_str("Check character does not match"),
+            SwitflakeError::StateLogUnavailable => f.write_str("State log could not be written"),
+            SwitflakeError::MissingField(field) => {
+                write!(f, "No value given for the {} field", field)
+            }
+            SwitflakeError::InvalidAlphabet(reason) => write!(f, "Invalid alphabet: {}", reason),
+            SwitflakeError::Internal => f.write_str("Internal error while generating an ID"),
+            SwitflakeError::RateExceeded => f.write_str("Generation rate limit exceeded"),
+        }
+    }
+}
+
+impl Error for SwitflakeError {}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_error_codes() {
+        let cases = [
+            (
+                SwitflakeError::PoolExhausted,
+                r#"{"code":"pool_exhausted"}"#,
+            ),
+            (
+                SwitflakeError::SequenceExhausted,
+                r#"{"code":"sequence_exhausted"}"#,
+            ),
+            (
+                SwitflakeError::ClockMovedBackwards,
+                r#"{"code":"clock_moved_backwards"}"#,
+            ),
+            (
+                SwitflakeError::ClockJumpedForward {
+                    delta: std::time::Duration::from_secs(2),
+                },
+                r#"{"code":"clock_jumped_forward","detail":{"delta":{"secs":2,"nanos":0}}}"#,
+            ),
+            (
+                SwitflakeError::TimestampOverflow,
+                r#"{"code":"timestamp_overflow"}"#,
+            ),
+            (
+                SwitflakeError::InvalidLayout("fields overlap"),
+                r#"{"code":"invalid_layout","detail":"fields overlap"}"#,
+            ),
+            (
+                SwitflakeError::DuplicateId(42),
+                r#"{"code":"duplicate_id","detail":42}"#,
+            ),
+            (
+                SwitflakeError::ParseError("bad digit"),
+                r#"{"code":"parse_error","detail":"bad digit"}"#,
+            ),
+            (
+                SwitflakeError::FieldOverflow("node"),
+                r#"{"code":"field_overflow","detail":"node"}"#,
+            ),
+            (
+                SwitflakeError::EntropyUnavailable,
+                r#"{"code":"entropy_unavailable"}"#,
+            ),
+            (
+                SwitflakeError::ChecksumMismatch,
+                r#"{"code":"checksum_mismatch"}"#,
+            ),
+            (
+                SwitflakeError::MissingField("timestamp"),
+                r#"{"code":"missing_field","detail":"timestamp"}"#,
+            ),
+            (
+                SwitflakeError::StateLogUnavailable,
+                r#"{"code":"state_log_unavailable"}"#,
+            ),
+            (
+                SwitflakeError::InvalidAlphabet("alphabet repeats a character"),
+                r#"{"code":"invalid_alphabet","detail":"alphabet repeats a character"}"#,
+            ),
+            (SwitflakeError::Internal, r#"{"code":"internal"}"#),
+            (SwitflakeError::RateExceeded, r#"{"code":"rate_exceeded"}"#),
+        ];
+        for (error, json) in cases {
+            assert_eq!(serde_json::to_string(&error).unwrap(), json);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use crate::{decompose, Clock, Switflake};
+
+impl<C: Clock> Switflake<C> {
+    /// One-line human readable description of `id`, e.g.
+    /// `switflake{ minted_at: 2024-05-01T12:00:00.123Z, node: 42, thread: 3, seq: 17, age: 2.1s }`.
+    ///
+    /// The age is left out if the clock can't be read.
+    pub fn explain(&self, id: u64) -> String {
+        let parts = decompose(id, &self.layout());
+        let minted_at = OffsetDateTime::from(self.timestamp(id))
+            .format(format_description!(
+                "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
+            ))
+            .unwrap_or_else(|_| parts.timestamp.to_string());
+        let age = match self.age(id) {
+            Ok(age) => format!(", age: {:.1}s", age.as_secs_f64()),
+            Err(_) => String::new(),
+        };
+        format!(
+            "switflake{{ minted_at: {}, node: {}, thread: {}, seq: {}{} }}",
+            minted_at, parts.node_id, parts.thread_id, parts.counter, age
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[test]
+    fn test_explain_fresh_id() {
+        let _guard = serial_guard();
+        // 2024-05-01T12:00:00.123Z
+        let clock = MockClock::at(1_714_564_800_123);
+        let mut swit = Switflake::builder()
+            .node_id(42)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let id = swit.generate_id().expect("Failed to generate ID");
+        clock.advance(2_100);
+
+        let explained = swit.explain(id);
+        assert!(explained.contains("node: 42"), "{}", explained);
+        assert!(
+            explained.contains("minted_at: 2024-05-01T12:00:00.123Z"),
+            "{}",
+            explained
+        );
+        assert!(explained.contains("age: 2.1s"), "{}", explained);
+        assert_eq!(
+            explained,
+            format!(
+                "switflake{{ minted_at: 2024-05-01T12:00:00.123Z, node: 42, thread: {}, seq: 0, age: 2.1s }}",
+                swit.lease().thread_id
+            )
+        );
+    }
+
+    #[test]
+    fn test_explain_system_clock() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(7).expect("Failed to create Switflake");
+        let id = swit.generate_id().expect("Failed to generate ID");
+        let explained = swit.explain(id);
+        assert!(explained.contains("node: 7"), "{}", explained);
+        // 최근에 발급된 ID
+        assert!(explained.contains("age: 0."), "{}", explained);
+        assert!(explained.contains("minted_at: 20"), "{}", explained);
+    }
+}
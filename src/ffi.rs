@@ -0,0 +1,178 @@
+//! C interface for embedding switflake in other languages. Enabled by the
+//! `ffi` feature; build the crate as a `cdylib` or `staticlib` to link it,
+//! e.g. with `cargo rustc --features ffi --crate-type cdylib`.
+//!
+//! A generator is an opaque `Switflake *` handle. [`switflake_new`] hands
+//! one out and the caller owns it until passing it to [`switflake_free`],
+//! exactly once. A handle may move between threads but must not be used
+//! from two at the same time; give every thread its own. As in Rust,
+//! creating one takes a slot of the global thread-id pool until it is
+//! freed.
+//!
+//! Every call that can fail returns [`SWITFLAKE_OK`] or an error code, and
+//! never unwinds into the caller. The codes are stable:
+//!
+//! | Code | Meaning                      |
+//! |------|------------------------------|
+//! | -1   | a pointer argument was null  |
+//! | 0    | success                      |
+//! | 1    | `PoolExhausted`              |
+//! | 2    | `SequenceExhausted`          |
+//! | 3    | `ClockMovedBackwards`        |
+//! | 4    | `ClockJumpedForward`         |
+//! | 5    | `TimestampOverflow`          |
+//! | 6    | `InvalidLayout`              |
+//! | 7    | `DuplicateId`                |
+//! | 8    | `ParseError`                 |
+//! | 9    | `FieldOverflow`              |
+//! | 10   | `EntropyUnavailable`         |
+//! | 11   | `ChecksumMismatch`           |
+//! | 12   | `MissingField`               |
+//! | 13   | `StateLogUnavailable`        |
+//! | 14   | `InvalidAlphabet`            |
+//! | 15   | `Internal`                   |
+//! | 16   | `RateExceeded`               |
+
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{Switflake, SwitflakeError};
+
+pub const SWITFLAKE_OK: i32 = 0;
+pub const SWITFLAKE_ERR_NULL: i32 = -1;
+
+fn error_code(error: &SwitflakeError) -> i32 {
+    match error {
+        SwitflakeError::PoolExhausted => 1,
+        SwitflakeError::SequenceExhausted => 2,
+        SwitflakeError::ClockMovedBackwards => 3,
+        SwitflakeError::ClockJumpedForward { .. } => 4,
+        SwitflakeError::TimestampOverflow => 5,
+        SwitflakeError::InvalidLayout(_) => 6,
+        SwitflakeError::DuplicateId(_) => 7,
+        SwitflakeError::ParseError(_) => 8,
+        SwitflakeError::FieldOverflow(_) => 9,
+        SwitflakeError::EntropyUnavailable => 10,
+        SwitflakeError::ChecksumMismatch => 11,
+        SwitflakeError::MissingField(_) => 12,
+        SwitflakeError::StateLogUnavailable => 13,
+        SwitflakeError::InvalidAlphabet(_) => 14,
+        SwitflakeError::Internal => 15,
+        SwitflakeError::RateExceeded => 16,
+    }
+}
+
+/// Creates a generator for `node_id` and stores its handle in
+/// `*out_handle`, which is left untouched on failure.
+///
+/// # Safety
+///
+/// `out_handle` must be null or valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn switflake_new(node_id: u64, out_handle: *mut *mut Switflake) -> i32 {
+    if out_handle.is_null() {
+        return SWITFLAKE_ERR_NULL;
+    }
+    let result =
+        panic::catch_unwind(|| Switflake::new(node_id)).unwrap_or(Err(SwitflakeError::Internal));
+    match result {
+        Ok(swit) => {
+            // SAFETY: checked non-null above; the caller vouches for the rest
+            unsafe { *out_handle = Box::into_raw(Box::new(swit)) };
+            SWITFLAKE_OK
+        }
+        Err(e) => error_code(&e),
+    }
+}
+
+/// Generates the next ID into `*out_id`, which is left untouched on
+/// failure. Fails with code 2 once this millisecond's sequence is used up;
+/// call again on the next millisecond.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle from [`switflake_new`] not yet freed
+/// nor in use on another thread, and `out_id` null or valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn switflake_generate(handle: *mut Switflake, out_id: *mut u64) -> i32 {
+    if handle.is_null() || out_id.is_null() {
+        return SWITFLAKE_ERR_NULL;
+    }
+    // SAFETY: the caller guarantees a live handle used by this thread only
+    let swit = unsafe { &mut *handle };
+    match swit.generate_id_catch() {
+        Ok(id) => {
+            // SAFETY: checked non-null above; the caller vouches for the rest
+            unsafe { *out_id = id };
+            SWITFLAKE_OK
+        }
+        Err(e) => error_code(&e),
+    }
+}
+
+/// Frees a generator and its pool slot. Does nothing for a null handle.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle from [`switflake_new`] not yet freed
+/// nor in use on another thread. It is dangling afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn switflake_free(handle: *mut Switflake) {
+    if handle.is_null() {
+        return;
+    }
+    // SAFETY: the caller hands back ownership of a handle from `Box::into_raw`
+    let swit = unsafe { Box::from_raw(handle) };
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(swit)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::{decompose, Layout};
+    use std::ptr;
+
+    #[test]
+    fn test_ffi_new_generate_free() {
+        let _guard = serial_guard();
+        let mut handle = ptr::null_mut();
+        // SAFETY: valid out pointers; each handle is freed exactly once
+        unsafe {
+            assert_eq!(switflake_new(37, &mut handle), SWITFLAKE_OK);
+            assert!(!handle.is_null());
+
+            let (mut first, mut second) = (0, 0);
+            assert_eq!(switflake_generate(handle, &mut first), SWITFLAKE_OK);
+            assert_eq!(switflake_generate(handle, &mut second), SWITFLAKE_OK);
+            assert!(second > first);
+            assert_eq!(decompose(first, &Layout::default()).node_id, 37);
+
+            assert_eq!(
+                switflake_generate(handle, ptr::null_mut()),
+                SWITFLAKE_ERR_NULL
+            );
+            assert_eq!(
+                switflake_generate(ptr::null_mut(), &mut first),
+                SWITFLAKE_ERR_NULL
+            );
+            assert_eq!(switflake_new(37, ptr::null_mut()), SWITFLAKE_ERR_NULL);
+            switflake_free(handle);
+            switflake_free(ptr::null_mut());
+
+            // 풀이 가득 차면 1
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let mut handle = ptr::null_mut();
+                    assert_eq!(switflake_new(1, &mut handle), SWITFLAKE_OK);
+                    handle
+                })
+                .collect();
+            let mut extra = ptr::null_mut();
+            assert_eq!(switflake_new(1, &mut extra), 1);
+            assert!(extra.is_null());
+            handles
+                .into_iter()
+                .for_each(|handle| switflake_free(handle));
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::encoding::{decode_base32, decode_base62, encode_base32, encode_base62};
+use crate::SwitflakeError;
+
+/// A generated ID.
+///
+/// Parses from either its decimal form or its base62 form: strings made up
+/// only of ASCII digits are read as decimal, anything else as base62.
+///
+/// ```
+/// use switflake::Id;
+///
+/// let id = Id::from(7_159_857_416_126_464_001);
+/// let raw: u64 = id.into();
+/// assert_eq!(raw, id.as_u64());
+/// assert_eq!("7159857416126464001".parse::<Id>().unwrap(), id);
+/// ```
+///
+/// `Id` deliberately does not implement `Deref<Target = u64>`: IDs are
+/// opaque labels, and auto-deref would let `id + 1` or `*id % n` compile
+/// without anyone having thought about what arithmetic on an ID means.
+/// Convert explicitly with [`as_u64`](Self::as_u64) when the bits are needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(pub u64);
+
+impl Id {
+    #[inline]
+    pub const fn from_u64(value: u64) -> Self {
+        Id(value)
+    }
+
+    #[inline]
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Parses base62 only, unlike [`FromStr`], which reads all-digit
+    /// strings as decimal.
+    #[inline]
+    pub fn from_base62(s: &str) -> Result<Self, SwitflakeError> {
+        decode_base62(s).map(Id)
+    }
+
+    /// Parses Crockford base32, accepting lowercase and the I/L/O
+    /// look-alikes.
+    #[inline]
+    pub fn from_crockford(s: &str) -> Result<Self, SwitflakeError> {
+        decode_base32(s).map(Id)
+    }
+
+    #[must_use]
+    pub fn to_base62(self) -> String {
+        encode_base62(self.0)
+    }
+
+    /// Crockford base32, uppercase and without padding.
+    #[must_use]
+    pub fn to_crockford(self) -> String {
+        encode_base32(self.0)
+    }
+}
+
+impl From<u64> for Id {
+    #[inline]
+    fn from(value: u64) -> Self {
+        Id(value)
+    }
+}
+
+impl From<Id> for u64 {
+    #[inline]
+    fn from(id: Id) -> Self {
+        id.0
+    }
+}
+
+impl PartialEq<u64> for Id {
+    #[inline]
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Id> for u64 {
+    #[inline]
+    fn eq(&self, other: &Id) -> bool {
+        *self == other.0
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for Id {
+    type Err = SwitflakeError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 숫자만으로 된 입력은 표준 라이브러리 파서로 바로 처리
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            return s
+                .parse::<u64>()
+                .map(Id)
+                .map_err(|_| SwitflakeError::ParseError("decimal value overflows u64"));
+        }
+        decode_base62(s).map(Id)
+    }
+}
+
+impl TryFrom<&str> for Id {
+    type Error = SwitflakeError;
+
+    #[inline]
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::decode_digits;
+
+    // The table-driven path every input took before the decimal fast path.
+    fn parse_general(s: &str) -> Result<Id, SwitflakeError> {
+        const DECIMAL_TABLE: [u8; 256] = {
+            let mut table = [0xFF; 256];
+            let mut d = 0;
+            while d < 10 {
+                table[b'0' as usize + d] = d as u8;
+                d += 1;
+            }
+            table
+        };
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            return decode_digits(s, 10, &DECIMAL_TABLE)
+                .map(Id)
+                .ok_or(SwitflakeError::ParseError("decimal value overflows u64"));
+        }
+        decode_base62(s).map(Id)
+    }
+
+    #[test]
+    fn test_numeric_fast_path_matches_general_path() {
+        for s in ["0", "7", "0042", "1234567890", "18446744073709551615"] {
+            assert_eq!(Id::try_from(s), parse_general(s), "input {}", s);
+        }
+        assert_eq!(Id::try_from("18446744073709551615"), Ok(Id(u64::MAX)));
+        assert!(Id::try_from("18446744073709551616").is_err());
+        assert!(parse_general("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn test_u64_conversions() {
+        const ID: Id = Id::from_u64(42);
+        assert_eq!(ID.as_u64(), 42);
+        assert_eq!(Id::from(42u64), ID);
+        assert_eq!(u64::from(ID), 42);
+        let raw: u64 = Id(u64::MAX).into();
+        assert_eq!(raw, u64::MAX);
+    }
+
+    #[test]
+    fn test_compares_with_raw_u64() {
+        let id = Id(12345);
+        assert_eq!(id, 12345u64);
+        assert_eq!(12345u64, id);
+        assert_ne!(id, 12346u64);
+        assert_ne!(12346u64, id);
+    }
+
+    #[test]
+    fn test_non_numeric_uses_base62() {
+        let id = 7_159_857_416_126_464_001;
+        let encoded = encode_base62(id);
+        assert!(!encoded.bytes().all(|b| b.is_ascii_digit()));
+        assert_eq!(Id::try_from(encoded.as_str()), Ok(Id(id)));
+        assert!(Id::try_from("").is_err());
+        assert!(Id::try_from("not base62!").is_err());
+        assert_eq!(Id(42).to_string(), "42");
+    }
+
+    #[test]
+    fn test_encoding_methods_match_free_functions() {
+        for raw in [0, 9, 61, 62, 7_159_857_416_126_464_001, u64::MAX] {
+            let id = Id(raw);
+            assert_eq!(id.to_base62(), encode_base62(raw));
+            assert_eq!(id.to_crockford(), encode_base32(raw));
+            assert_eq!(Id::from_base62(&id.to_base62()), Ok(id));
+            assert_eq!(Id::from_crockford(&id.to_crockford()), Ok(id));
+        }
+        // FromStr와 달리 숫자만으로 된 문자열도 base62로 읽음
+        assert_eq!(Id::from_base62("10"), Ok(Id(62)));
+        assert_eq!(Id::from_crockford("1o"), Ok(Id(32)));
+        assert_eq!(Id::from_base62("abc-"), decode_base62("abc-").map(Id));
+        assert_eq!(Id::from_crockford("U"), decode_base32("U").map(Id));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{compose, Layout, SwitflakeError};
+
+/// Builds an ID field by field, for tests and admin tooling:
+///
+/// ```
+/// use switflake::{decompose, IdBuilder, Layout};
+///
+/// let layout = Layout::default();
+/// let id = IdBuilder::new(layout)
+///     .timestamp(1_000)
+///     .node(7)
+///     .thread(3)
+///     .counter(5)
+///     .build()
+///     .unwrap();
+/// assert_eq!(decompose(id, &layout).node_id, 7);
+/// ```
+///
+/// Each value is checked against its field's width as it is set, but
+/// errors only surface from [`build`](Self::build), which reports the first
+/// one. The timestamp and node are required; thread and counter default
+/// to 0. Packs like [`compose`], which takes all fields at once.
+#[derive(Debug, Clone)]
+#[must_use = "an IdBuilder does nothing until build is called"]
+pub struct IdBuilder {
+    layout: Layout,
+    timestamp: Option<u64>,
+    node: Option<u64>,
+    thread: u8,
+    counter: u16,
+    error: Option<SwitflakeError>,
+}
+
+impl IdBuilder {
+    pub fn new(layout: Layout) -> Self {
+        IdBuilder {
+            layout,
+            timestamp: None,
+            node: None,
+            thread: 0,
+            counter: 0,
+            error: None,
+        }
+    }
+
+    /// Ticks since the layout's epoch, as stored in the timestamp field.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.check(timestamp, self.layout.timestamp_mask(), "timestamp");
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn node(mut self, node: u64) -> Self {
+        self.check(node, self.layout.node_mask(), "node");
+        self.node = Some(node);
+        self
+    }
+
+    pub fn thread(mut self, thread: u8) -> Self {
+        self.check(thread as u64, self.layout.thread_mask(), "thread");
+        self.thread = thread;
+        self
+    }
+
+    pub fn counter(mut self, counter: u16) -> Self {
+        self.check(counter as u64, self.layout.counter_mask(), "counter");
+        self.counter = counter;
+        self
+    }
+
+    fn check(&mut self, value: u64, mask: u64, field: &'static str) {
+        if self.error.is_none() && value & !mask != 0 {
+            self.error = Some(SwitflakeError::FieldOverflow(field));
+        }
+    }
+
+    /// Packs the ID. Fails with the first [`SwitflakeError::FieldOverflow`]
+    /// met while setting fields, [`SwitflakeError::MissingField`] if the
+    /// timestamp or node was never set, or
+    /// [`SwitflakeError::InvalidLayout`] for an unusable layout.
+    pub fn build(self) -> Result<u64, SwitflakeError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let timestamp = self
+            .timestamp
+            .ok_or(SwitflakeError::MissingField("timestamp"))?;
+        let node = self.node.ok_or(SwitflakeError::MissingField("node"))?;
+        compose(timestamp, node, self.thread, self.counter, &self.layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compose_exact;
+
+    #[test]
+    fn test_full_build_matches_compose() {
+        let id = IdBuilder::new(Layout::default())
+            .timestamp(123_456)
+            .node(42)
+            .thread(3)
+            .counter(5)
+            .build()
+            .expect("Failed to build ID");
+        assert_eq!(id, compose_exact(123_456, 42, 3, 5).unwrap());
+    }
+
+    #[test]
+    fn test_out_of_range_field_rejected() {
+        // 기본 레이아웃의 스레드 필드는 3비트
+        let result = IdBuilder::new(Layout::default())
+            .timestamp(1)
+            .node(1)
+            .thread(8)
+            .counter(1)
+            .build();
+        assert_eq!(result, Err(SwitflakeError::FieldOverflow("thread")));
+    }
+
+    #[test]
+    fn test_missing_timestamp_rejected() {
+        let result = IdBuilder::new(Layout::default()).node(1).build();
+        assert_eq!(result, Err(SwitflakeError::MissingField("timestamp")));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{Clock, Switflake};
+
+/// Iterator over freshly generated IDs, created by [`Switflake::iter`] or by
+/// looping over `&mut Switflake`.
+///
+/// It never runs dry on its own: when a millisecond's sequence is exhausted
+/// it waits for the next one, so a plain `for` loop over it is infinite and
+/// needs a `break` or an adapter like `take`. Iteration only stops at the
+/// first unrecoverable error, such as the clock moving backwards; call
+/// [`Switflake::generate_id`] directly to observe the error itself.
+pub struct IdIter<'a, C: Clock> {
+    swit: &'a mut Switflake<C>,
+}
+
+impl<'a, C: Clock> IdIter<'a, C> {
+    pub(crate) fn new(swit: &'a mut Switflake<C>) -> Self {
+        IdIter { swit }
+    }
+}
+
+impl<C: Clock> Iterator for IdIter<'_, C> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        self.swit.generate_id_blocking().ok()
+    }
+}
+
+impl<'a, C: Clock> IntoIterator for &'a mut Switflake<C> {
+    type Item = u64;
+    type IntoIter = IdIter<'a, C>;
+
+    fn into_iter(self) -> IdIter<'a, C> {
+        IdIter::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_for_loop_crosses_milliseconds() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+        let ids: Vec<u64> = (&mut swit).into_iter().take(300).collect();
+        assert_eq!(ids.len(), 300);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "IDs not monotonic");
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 300);
+
+        let mut count = 0;
+        for _ in &mut swit {
+            count += 1;
+            if count == 10 {
+                break;
+            }
+        }
+        assert_eq!(count, 10);
+    }
+}
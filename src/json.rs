@@ -0,0 +1,98 @@
+use std::time::UNIX_EPOCH;
+
+use serde_json::{json, Value};
+
+use crate::{decompose, Switflake};
+
+impl<C> Switflake<C> {
+    /// Describes `id` as a JSON object for logging pipelines, e.g.
+    /// `{"id":"7159857416126464001","timestamp_ms":1714564800123,"datetime":"2024-05-01T12:00:00.123Z","node":42,"thread":3,"sequence":17}`.
+    ///
+    /// The ID itself is a string, since JavaScript numbers lose precision
+    /// above 2^53; every other field is a number, except the UTC `datetime`.
+    pub fn id_to_json(&self, id: u64) -> Value {
+        let parts = decompose(id, &self.layout);
+        let millis = self
+            .timestamp(id)
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        json!({
+            "id": id.to_string(),
+            "timestamp_ms": millis,
+            "datetime": format_utc_millis(millis),
+            "node": parts.node_id,
+            "thread": parts.thread_id,
+            "sequence": parts.counter,
+        })
+    }
+}
+
+/// RFC 3339 UTC rendering of a Unix time in milliseconds, to the
+/// millisecond.
+fn format_utc_millis(millis: u64) -> String {
+    let (days, millis_of_day) = (millis / 86_400_000, millis % 86_400_000);
+    // 1970-01-01부터 센 날짜를 그레고리력으로 (Hinnant의 civil_from_days)
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        millis_of_day / 3_600_000,
+        millis_of_day / 60_000 % 60,
+        millis_of_day / 1_000 % 60,
+        millis_of_day % 1_000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_id_to_json_has_every_field() {
+        let _guard = serial_guard();
+        // 2024-05-01T12:00:00.123Z
+        let clock = MockClock::at(1_714_564_800_123);
+        let mut swit = Switflake::builder()
+            .node_id(42)
+            .clock(clock)
+            .build()
+            .expect("Failed to create Switflake");
+        swit.generate_id().expect("Failed to generate ID");
+        let id = swit.generate_id().expect("Failed to generate ID");
+
+        let value = swit.id_to_json(id);
+        let object = value.as_object().expect("not a JSON object");
+        assert_eq!(object.len(), 6);
+        assert_eq!(value["id"], id.to_string());
+        assert_eq!(value["timestamp_ms"], 1_714_564_800_123u64);
+        assert_eq!(value["datetime"], "2024-05-01T12:00:00.123Z");
+        assert_eq!(value["node"], 42);
+        assert_eq!(value["thread"], swit.lease().thread_id);
+        assert_eq!(value["sequence"], 1);
+    }
+
+    #[test]
+    fn test_utc_formatting_across_calendar_edges() {
+        let cases = [
+            (0, "1970-01-01T00:00:00.000Z"),
+            (951_782_400_000, "2000-02-29T00:00:00.000Z"),
+            (1_709_251_199_999, "2024-02-29T23:59:59.999Z"),
+            (4_102_444_800_000, "2100-01-01T00:00:00.000Z"),
+        ];
+        for (millis, expected) in cases {
+            assert_eq!(format_utc_millis(millis), expected);
+        }
+    }
+}
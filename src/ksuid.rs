@@ -0,0 +1,92 @@
+//! Bridge from switflake IDs to KSUID-style strings.
+//!
+//! A KSUID is 160 bits: a 32-bit count of seconds since 2014-05-13
+//! (Unix time 1,400,000,000) followed by a 128-bit payload, written as 27
+//! base62 characters that sort the way the values do. Converting a
+//! switflake ID keeps its timestamp to the second and puts the whole ID at
+//! the low end of the payload, so [`from_ksuid_like`] gets it back, but
+//! KSUIDs minted elsewhere don't carry a switflake ID and decode to
+//! whatever their low 64 bits hold.
+
+use crate::encoding::{BASE62_ALPHABET, BASE62_TABLE, INVALID};
+use crate::{Layout, SwitflakeError};
+
+// KSUID's epoch in Unix seconds.
+const KSUID_EPOCH: u64 = 1_400_000_000;
+const KSUID_LEN: usize = 27;
+
+/// Formats `id`, read with the default layout and Unix epoch, as a
+/// 27-character KSUID-style string. Times before the KSUID epoch are
+/// clamped to it.
+pub fn to_ksuid_like(id: u64) -> String {
+    let seconds = Layout::default().timestamp_of(id) / 1_000;
+    let timestamp = seconds.saturating_sub(KSUID_EPOCH).min(u32::MAX as u64) as u32;
+    // 160비트 값을 큰 자리부터 32비트 조각 다섯 개로
+    let mut limbs = [timestamp, 0, 0, (id >> 32) as u32, id as u32];
+    let mut out = [BASE62_ALPHABET[0]; KSUID_LEN];
+    for digit in out.iter_mut().rev() {
+        let mut remainder = 0u64;
+        for limb in &mut limbs {
+            let value = (remainder << 32) | *limb as u64;
+            *limb = (value / 62) as u32;
+            remainder = value % 62;
+        }
+        *digit = BASE62_ALPHABET[remainder as usize];
+    }
+    String::from_utf8(out.to_vec()).expect("alphabet is ASCII")
+}
+
+/// Reverses [`to_ksuid_like`], returning the ID in the payload's low 64
+/// bits. Fails with [`SwitflakeError::ParseError`] unless `s` is 27 base62
+/// characters holding at most 160 bits.
+pub fn from_ksuid_like(s: &str) -> Result<u64, SwitflakeError> {
+    if s.len() != KSUID_LEN {
+        return Err(SwitflakeError::ParseError("KSUID must be 27 characters"));
+    }
+    let mut limbs = [0u32; 5];
+    for &byte in s.as_bytes() {
+        let digit = BASE62_TABLE[byte as usize];
+        if digit == INVALID {
+            return Err(SwitflakeError::ParseError("invalid base62 digit"));
+        }
+        let mut carry = digit as u64;
+        for limb in limbs.iter_mut().rev() {
+            let value = *limb as u64 * 62 + carry;
+            *limb = value as u32;
+            carry = value >> 32;
+        }
+        if carry != 0 {
+            return Err(SwitflakeError::ParseError("KSUID overflows 160 bits"));
+        }
+    }
+    Ok(((limbs[3] as u64) << 32) | limbs[4] as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ksuid_strings_sort_by_time() {
+        let layout = Layout::default();
+        let earlier = layout.pack(1_700_000_000_999, 4095, 7, 255);
+        let later = layout.pack(1_700_000_001_000, 0, 0, 0);
+        let (a, b) = (to_ksuid_like(earlier), to_ksuid_like(later));
+        assert_eq!((a.len(), b.len()), (27, 27));
+        assert!(a
+            .bytes()
+            .chain(b.bytes())
+            .all(|c| c.is_ascii_alphanumeric()));
+        assert!(a < b, "{} >= {}", a, b);
+        assert_eq!(
+            (from_ksuid_like(&a), from_ksuid_like(&b)),
+            (Ok(earlier), Ok(later))
+        );
+
+        // KSUID 에포크 이전은 0으로, 최대값은 표준 KSUID 최대 문자열
+        assert_eq!(to_ksuid_like(0), "0".repeat(27));
+        assert_eq!(from_ksuid_like("aWgEPTl1tmebfsQzFP4bxwgy80V"), Ok(u64::MAX));
+        assert!(from_ksuid_like("aWgEPTl1tmebfsQzFP4bxwgy80W").is_err());
+        assert!(from_ksuid_like("0000").is_err());
+    }
+}
@@ -0,0 +1,964 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::{SwitflakeError, TimeUnit};
+
+/// Integer type of the per-thread counter, wide enough for the counter to
+/// take a whole default-layout sequence field. [`Layout::check_fields`]
+/// rejects counters wider than this.
+pub(crate) type Counter = u16;
+
+/// Bit 63, set on IDs from generators built with
+/// [`namespace_bit`](crate::SwitflakeBuilder::namespace_bit).
+pub(crate) const NAMESPACE_BIT: u64 = 1 << 63;
+
+/// Width of the version field of [`versioned`](Layout::versioned) layouts.
+pub const VERSION_BITS: u32 = 4;
+
+/// Width of the kind field of [`kinded`](Layout::kinded) layouts.
+pub const KIND_BITS: u32 = 8;
+
+/// Bit layout of a 64-bit ID: the width and offset of every field.
+///
+/// The sequence field is `thread_bits + counter_bits` wide, with the thread id
+/// occupying its high bits and the per-thread counter its low bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layout {
+    pub timestamp_bits: u32,
+    pub timestamp_shift: u32,
+    pub node_bits: u32,
+    pub node_shift: u32,
+    pub thread_bits: u32,
+    pub counter_bits: u32,
+    pub sequence_shift: u32,
+    /// Whether the top [`VERSION_BITS`] of the node field hold a version
+    /// rather than node id bits, so decoders can tell apart IDs minted under
+    /// different schemes; see
+    /// [`SwitflakeBuilder::version`](crate::SwitflakeBuilder::version).
+    /// [`compose`] and [`remap_node`] still treat the node field as a whole,
+    /// version included.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub versioned: bool,
+    /// Whether the [`KIND_BITS`] of the node field below the version, if
+    /// any, hold an application kind tag rather than node id bits; see
+    /// [`Switflake::generate_kind`](crate::Switflake::generate_kind).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kinded: bool,
+}
+
+impl Layout {
+    #[inline]
+    pub const fn sequence_bits(&self) -> u32 {
+        self.thread_bits + self.counter_bits
+    }
+
+    #[inline]
+    pub const fn timestamp_mask(&self) -> u64 {
+        mask(self.timestamp_bits)
+    }
+
+    #[inline]
+    pub const fn node_mask(&self) -> u64 {
+        mask(self.node_bits)
+    }
+
+    #[inline]
+    pub const fn thread_mask(&self) -> u64 {
+        mask(self.thread_bits)
+    }
+
+    #[inline]
+    pub const fn counter_mask(&self) -> u64 {
+        mask(self.counter_bits)
+    }
+
+    #[inline]
+    pub const fn sequence_mask(&self) -> u64 {
+        mask(self.sequence_bits())
+    }
+
+    /// Width of the node field left for the node id once the version and
+    /// kind, if any, are taken out.
+    #[inline]
+    pub const fn node_id_bits(&self) -> u32 {
+        self.node_bits - self.version_bits() - self.kind_bits()
+    }
+
+    #[inline]
+    const fn version_bits(&self) -> u32 {
+        if self.versioned {
+            VERSION_BITS
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    const fn kind_bits(&self) -> u32 {
+        if self.kinded {
+            KIND_BITS
+        } else {
+            0
+        }
+    }
+
+    /// Where the version starts within the node field; the field's width
+    /// for layouts without a version.
+    #[inline]
+    const fn version_shift(&self) -> u32 {
+        self.node_bits - self.version_bits()
+    }
+
+    /// `version` moved to its place in the node field, or 0 for layouts
+    /// without a version.
+    #[inline]
+    pub(crate) const fn version_field(&self, version: u8) -> u64 {
+        if self.versioned {
+            (version as u64) << self.version_shift()
+        } else {
+            0
+        }
+    }
+
+    /// `kind` moved to its place in the node field, or 0 for layouts
+    /// without a kind.
+    #[inline]
+    pub(crate) const fn kind_field(&self, kind: u8) -> u64 {
+        if self.kinded {
+            (kind as u64) << self.node_id_bits()
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    pub const fn timestamp_of(&self, id: u64) -> u64 {
+        (id >> self.timestamp_shift) & self.timestamp_mask()
+    }
+
+    /// IDs a single generator can issue per second in the long run with
+    /// this layout and `unit`: a full counter every tick.
+    pub const fn max_throughput_per_second(&self, unit: TimeUnit) -> u64 {
+        self.counter_mask()
+            .saturating_add(1)
+            .saturating_mul(unit.ticks_per_second())
+    }
+
+    #[inline]
+    pub(crate) const fn pack(
+        &self,
+        timestamp: u64,
+        node_id: u64,
+        thread_id: u8,
+        counter: Counter,
+    ) -> u64 {
+        let sequence = ((thread_id as u64) << self.counter_bits) | counter as u64;
+        (timestamp << self.timestamp_shift)
+            | (node_id << self.node_shift)
+            | ((sequence & self.sequence_mask()) << self.sequence_shift)
+    }
+
+    /// Bits of an ID that belong to no field.
+    #[inline]
+    pub fn reserved_mask(&self) -> u64 {
+        !((self
+            .timestamp_mask()
+            .checked_shl(self.timestamp_shift)
+            .unwrap_or(0))
+            | (self.node_mask().checked_shl(self.node_shift).unwrap_or(0))
+            | (self
+                .sequence_mask()
+                .checked_shl(self.sequence_shift)
+                .unwrap_or(0)))
+    }
+
+    /// Checks that every field fits in 64 bits and no two fields overlap.
+    pub(crate) fn check_fields(&self) -> Result<(), SwitflakeError> {
+        let fields = [
+            (
+                self.timestamp_shift,
+                self.timestamp_bits,
+                "timestamp field does not fit in 64 bits",
+            ),
+            (
+                self.node_shift,
+                self.node_bits,
+                "node field does not fit in 64 bits",
+            ),
+            (
+                self.sequence_shift,
+                self.sequence_bits(),
+                "sequence field does not fit in 64 bits",
+            ),
+        ];
+        let mut used = 0u64;
+        for (shift, bits, too_wide) in fields {
+            if shift as u64 + bits as u64 > 64 {
+                return Err(SwitflakeError::InvalidLayout(too_wide));
+            }
+            let field = mask(bits).checked_shl(shift).unwrap_or(0);
+            if used & field != 0 {
+                return Err(SwitflakeError::InvalidLayout("fields overlap"));
+            }
+            used |= field;
+        }
+        if self.versioned && self.node_bits < VERSION_BITS {
+            return Err(SwitflakeError::InvalidLayout(
+                "node field too narrow for a version",
+            ));
+        }
+        if self.kinded && self.node_bits < self.version_bits() + KIND_BITS {
+            return Err(SwitflakeError::InvalidLayout(
+                "node field too narrow for a kind",
+            ));
+        }
+        if self.counter_bits > Counter::BITS {
+            return Err(SwitflakeError::InvalidLayout(
+                "counter wider than 16 bits is not supported",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The layout generators use unless told otherwise, for decoders that need
+/// it at compile time: a 41-bit timestamp at bit 23, a 12-bit node id at bit
+/// 11 and an 11-bit sequence at bit 0, made of a 3-bit thread id and an
+/// 8-bit counter.
+pub const DEFAULT_LAYOUT: Layout = Layout {
+    timestamp_bits: 41,
+    timestamp_shift: 23,
+    node_bits: 12,
+    node_shift: 11,
+    thread_bits: 3,
+    counter_bits: 8,
+    sequence_shift: 0,
+    versioned: false,
+    kinded: false,
+};
+
+impl Default for Layout {
+    /// [`DEFAULT_LAYOUT`].
+    fn default() -> Self {
+        DEFAULT_LAYOUT
+    }
+}
+
+/// Checks a layout from untrusted configuration before any generator is
+/// built from it, naming the offending field in the error.
+///
+/// Stricter than the check `build` performs: on top of every field fitting
+/// in 64 bits without overlapping, the widths must add up to exactly 64,
+/// the timestamp and counter must be at least one bit wide, and the thread
+/// field must either hold every slot of the
+/// [`ThreadIdPool`](crate::ThreadIdPool) or be absent, for a single
+/// generator that takes no slot.
+/// [`Switflake128`](crate::Switflake128) has a fixed layout and needs no
+/// validation.
+pub fn validate_layout(layout: &Layout) -> Result<(), SwitflakeError> {
+    layout.check_fields()?;
+    if layout.timestamp_bits == 0 {
+        return Err(SwitflakeError::InvalidLayout(
+            "timestamp field has zero width",
+        ));
+    }
+    if layout.counter_bits == 0 {
+        return Err(SwitflakeError::InvalidLayout(
+            "counter field has zero width",
+        ));
+    }
+    if layout.thread_bits != 0
+        && 1u64 << layout.thread_bits < <crate::ThreadIdPool>::CAPACITY as u64
+    {
+        return Err(SwitflakeError::InvalidLayout(
+            "thread field too narrow for every pool slot",
+        ));
+    }
+    if layout.timestamp_bits + layout.node_bits + layout.sequence_bits() != 64 {
+        return Err(SwitflakeError::InvalidLayout(
+            "field widths do not add up to 64 bits",
+        ));
+    }
+    Ok(())
+}
+
+/// The individual fields of an ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecomposedId {
+    pub timestamp: u64,
+    pub node_id: u64,
+    pub thread_id: u8,
+    pub counter: u16,
+    /// The version in a [`versioned`](Layout::versioned) layout, which
+    /// `node_id` then leaves out; 0 otherwise.
+    pub version: u8,
+    /// The kind in a [`kinded`](Layout::kinded) layout, which `node_id`
+    /// then leaves out; 0 otherwise.
+    pub kind: u8,
+    /// Whether bit 63 is set while the layout leaves it outside every field;
+    /// see [`SwitflakeBuilder::namespace_bit`](crate::SwitflakeBuilder::namespace_bit).
+    pub namespace: bool,
+}
+
+/// Splits `id` into its fields according to `layout`.
+#[inline]
+pub fn decompose(id: u64, layout: &Layout) -> DecomposedId {
+    let sequence = (id >> layout.sequence_shift) & layout.sequence_mask();
+    let node = (id >> layout.node_shift) & layout.node_mask();
+    DecomposedId {
+        timestamp: layout.timestamp_of(id),
+        node_id: node & mask(layout.node_id_bits()),
+        thread_id: ((sequence >> layout.counter_bits) & layout.thread_mask()) as u8,
+        counter: (sequence & layout.counter_mask()) as u16,
+        version: id_version(id, layout),
+        kind: id_kind(id, layout),
+        namespace: id & layout.reserved_mask() & NAMESPACE_BIT != 0,
+    }
+}
+
+/// The version `id` was minted under, for routing decoding by version when
+/// several ID schemes are in use at once; 0 if `layout` isn't
+/// [`versioned`](Layout::versioned).
+#[inline]
+pub fn id_version(id: u64, layout: &Layout) -> u8 {
+    if !layout.versioned {
+        return 0;
+    }
+    (((id >> layout.node_shift) & layout.node_mask()) >> layout.version_shift()) as u8
+}
+
+/// The whole sequence field of `id` as one number, thread id in its high
+/// bits and counter in its low bits, for tooling that reads the sequence
+/// as an opaque counter the way plain snowflake IDs have it.
+#[inline]
+pub fn raw_sequence(id: u64, layout: &Layout) -> u64 {
+    (id >> layout.sequence_shift) & layout.sequence_mask()
+}
+
+/// The kind tag `id` was generated with by
+/// [`Switflake::generate_kind`](crate::Switflake::generate_kind), for
+/// filtering IDs by kind without a lookup; 0 if `layout` isn't
+/// [`kinded`](Layout::kinded).
+#[inline]
+pub fn id_kind(id: u64, layout: &Layout) -> u8 {
+    if !layout.kinded {
+        return 0;
+    }
+    (((id >> layout.node_shift) & layout.node_mask()) >> layout.node_id_bits()) as u8
+}
+
+/// Decomposes every ID in `ids` into the matching slot of `out`, like
+/// calling [`decompose`] per element but written so the compiler can
+/// vectorize it: the shifts and masks are read from `layout` once, before
+/// the loop.
+///
+/// # Panics
+///
+/// Panics if `ids` and `out` differ in length.
+pub fn decompose_slice(ids: &[u64], out: &mut [DecomposedId], layout: &Layout) {
+    assert_eq!(ids.len(), out.len(), "ids and out differ in length");
+    let (timestamp_shift, timestamp_mask) = (layout.timestamp_shift, layout.timestamp_mask());
+    let (node_shift, node_mask) = (layout.node_shift, layout.node_mask());
+    let (node_id_bits, node_id_mask) = (layout.node_id_bits(), mask(layout.node_id_bits()));
+    let (version_shift, kind_mask) = (layout.version_shift(), mask(layout.kind_bits()));
+    let (sequence_shift, sequence_mask) = (layout.sequence_shift, layout.sequence_mask());
+    let (counter_bits, thread_mask, counter_mask) = (
+        layout.counter_bits,
+        layout.thread_mask(),
+        layout.counter_mask(),
+    );
+    let namespace_mask = layout.reserved_mask() & NAMESPACE_BIT;
+    for (&id, parts) in ids.iter().zip(out.iter_mut()) {
+        let sequence = (id >> sequence_shift) & sequence_mask;
+        let node = (id >> node_shift) & node_mask;
+        *parts = DecomposedId {
+            timestamp: (id >> timestamp_shift) & timestamp_mask,
+            node_id: node & node_id_mask,
+            thread_id: ((sequence >> counter_bits) & thread_mask) as u8,
+            counter: (sequence & counter_mask) as u16,
+            version: node.checked_shr(version_shift).unwrap_or(0) as u8,
+            kind: (node.checked_shr(node_id_bits).unwrap_or(0) & kind_mask) as u8,
+            namespace: id & namespace_mask != 0,
+        };
+    }
+}
+
+/// Packs explicit field values into an ID under `layout`; the inverse of
+/// [`decompose`].
+///
+/// Fails with [`SwitflakeError::FieldOverflow`] if any value is wider than
+/// its field, or with [`SwitflakeError::InvalidLayout`] if the layout itself
+/// is unusable.
+pub fn compose(
+    timestamp: u64,
+    node_id: u64,
+    thread_id: u8,
+    counter: u16,
+    layout: &Layout,
+) -> Result<u64, SwitflakeError> {
+    layout.check_fields()?;
+    let fields = [
+        (timestamp, layout.timestamp_mask(), "timestamp"),
+        (node_id, layout.node_mask(), "node"),
+        (thread_id as u64, layout.thread_mask(), "thread"),
+        (counter as u64, layout.counter_mask(), "counter"),
+    ];
+    for (value, mask, field) in fields {
+        if value & !mask != 0 {
+            return Err(SwitflakeError::FieldOverflow(field));
+        }
+    }
+    Ok(layout.pack(timestamp, node_id, thread_id, counter))
+}
+
+/// Rebuilds an ID of the default layout from logged components, bit for
+/// bit, e.g. for idempotent re-ingestion. A pure function: no clock is read
+/// and no generator or pool is touched. Fails like [`compose`], whose
+/// `layout` argument takes other layouts.
+#[inline]
+pub fn compose_exact(
+    timestamp: u64,
+    node_id: u64,
+    thread_id: u8,
+    counter: u16,
+) -> Result<u64, SwitflakeError> {
+    compose(timestamp, node_id, thread_id, counter, &Layout::default())
+}
+
+/// Decomposes with the default layout. Its fields cover all 64 bits and
+/// every value of each field can occur, so any `u64` is a valid default
+/// layout ID and the conversion can't fail; use [`decompose`] for other
+/// layouts.
+impl From<u64> for DecomposedId {
+    fn from(id: u64) -> Self {
+        decompose(id, &Layout::default())
+    }
+}
+
+/// Rewrites the node field of `id` through `map`, leaving every other bit
+/// as it was. Meant for displaying historical IDs under a new node
+/// numbering; the stored IDs themselves don't change.
+///
+/// Node ids missing from `map` pass through unchanged. Fails with
+/// [`SwitflakeError::FieldOverflow`] if the mapped node id doesn't fit in
+/// the node field.
+pub fn remap_node(
+    id: u64,
+    map: &HashMap<u64, u64>,
+    layout: &Layout,
+) -> Result<u64, SwitflakeError> {
+    layout.check_fields()?;
+    let node_id = (id >> layout.node_shift) & layout.node_mask();
+    let new_node_id = match map.get(&node_id) {
+        Some(&mapped) => mapped,
+        None => return Ok(id),
+    };
+    if new_node_id & !layout.node_mask() != 0 {
+        return Err(SwitflakeError::FieldOverflow("node"));
+    }
+    let node_bits = layout.node_mask() << layout.node_shift;
+    Ok((id & !node_bits) | (new_node_id << layout.node_shift))
+}
+
+/// Rewrites the timestamp field of `id`, minted with timestamps counted
+/// from `old_epoch`, to count the same instant from `new_epoch`, leaving
+/// every other bit as it was. Epochs are in the layout's timestamp unit
+/// (Unix milliseconds for millisecond generators).
+///
+/// Every stored ID rebased the same way keeps its order relative to the
+/// others, and to IDs minted afterwards with `new_epoch`, so a table can be
+/// rewritten in place when moving to a more recent epoch. Fails with
+/// [`SwitflakeError::FieldOverflow`] naming the timestamp if `id` predates
+/// `new_epoch`, whose timestamps can't go below 0, and with
+/// [`SwitflakeError::TimestampOverflow`] if the rebased timestamp no longer
+/// fits in the timestamp field.
+pub fn rebase_id(
+    id: u64,
+    old_epoch: u64,
+    new_epoch: u64,
+    layout: &Layout,
+) -> Result<u64, SwitflakeError> {
+    layout.check_fields()?;
+    let timestamp = layout
+        .timestamp_of(id)
+        .checked_add(old_epoch)
+        .ok_or(SwitflakeError::TimestampOverflow)?
+        .checked_sub(new_epoch)
+        .ok_or(SwitflakeError::FieldOverflow("timestamp"))?;
+    if timestamp > layout.timestamp_mask() {
+        return Err(SwitflakeError::TimestampOverflow);
+    }
+    let timestamp_bits = layout.timestamp_mask() << layout.timestamp_shift;
+    Ok((id & !timestamp_bits) | (timestamp << layout.timestamp_shift))
+}
+
+/// Applies [`rebase_id`] lazily to each of `ids` in turn, for rewriting a
+/// file or table of IDs without holding them all in memory. Order is
+/// preserved, and an ID that can't be rebased yields its error in its
+/// place without ending the stream, so the caller decides whether to stop,
+/// skip it or log it.
+pub fn rebase_stream(
+    ids: impl Iterator<Item = u64>,
+    old_epoch: u64,
+    new_epoch: u64,
+    layout: &Layout,
+) -> impl Iterator<Item = Result<u64, SwitflakeError>> {
+    let layout = *layout;
+    ids.map(move |id| rebase_id(id, old_epoch, new_epoch, &layout))
+}
+
+#[inline]
+pub(crate) const fn mask(bits: u32) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
+
+/// Orders two IDs by timestamp first, breaking ties on the remaining
+/// node/thread/counter bits.
+///
+/// Because the timestamp occupies the most significant bits this is exactly
+/// the numeric order of the IDs; use it instead of hand-rolled comparisons so
+/// the intent stays explicit.
+#[inline]
+pub fn cmp_ids(a: u64, b: u64) -> Ordering {
+    a.cmp(&b)
+}
+
+/// Returns `true` if both IDs were minted in the same timestamp tick under
+/// `layout`, regardless of their node or sequence.
+#[inline]
+pub fn is_same_millisecond(a: u64, b: u64, layout: &Layout) -> bool {
+    layout.timestamp_of(a) == layout.timestamp_of(b)
+}
+
+/// The Unix millisecond at which the `bucket_millis`-wide window holding
+/// `id` starts, for grouping IDs into fixed windows with integer math
+/// alone. Windows are aligned to the Unix epoch, so 1000 ms buckets start
+/// on whole seconds whatever the generator's `epoch` (Unix milliseconds);
+/// `layout` must hold millisecond timestamps.
+///
+/// # Panics
+///
+/// Panics if `bucket_millis` is 0.
+#[inline]
+pub fn time_bucket(id: u64, bucket_millis: u64, epoch: u64, layout: &Layout) -> u64 {
+    let millis = epoch + layout.timestamp_of(id);
+    millis - millis % bucket_millis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    fn pack(layout: &Layout, timestamp: u64, node: u64, sequence: u64) -> u64 {
+        (timestamp << layout.timestamp_shift)
+            | (node << layout.node_shift)
+            | (sequence << layout.sequence_shift)
+    }
+
+    #[test]
+    fn test_max_throughput_per_second() {
+        let wide_counter = Layout {
+            thread_bits: 0,
+            counter_bits: 11,
+            ..DEFAULT_LAYOUT
+        };
+        let cases = [
+            (DEFAULT_LAYOUT, TimeUnit::Milliseconds, 256_000),
+            (DEFAULT_LAYOUT, TimeUnit::TenMilliseconds, 25_600),
+            (DEFAULT_LAYOUT, TimeUnit::Microseconds, 256_000_000),
+            (wide_counter, TimeUnit::Milliseconds, 2_048_000),
+        ];
+        for (layout, unit, expected) in cases {
+            assert_eq!(layout.max_throughput_per_second(unit), expected);
+        }
+    }
+
+    #[test]
+    fn test_same_millisecond_orders_by_node() {
+        let layout = Layout::default();
+        let a = pack(&layout, 1_000, 1, 0);
+        let b = pack(&layout, 1_000, 2, 0);
+        assert!(is_same_millisecond(a, b, &layout));
+        assert_eq!(cmp_ids(a, b), Ordering::Less);
+        assert_eq!(cmp_ids(b, a), Ordering::Greater);
+        assert_eq!(cmp_ids(a, a), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_cross_millisecond_orders_by_timestamp() {
+        let layout = Layout::default();
+        // 높은 노드/시퀀스라도 이전 밀리초가 먼저
+        let earlier = pack(&layout, 1_000, 0xFFF, 0x7FF);
+        let later = pack(&layout, 1_001, 0, 0);
+        assert!(!is_same_millisecond(earlier, later, &layout));
+        assert_eq!(cmp_ids(earlier, later), Ordering::Less);
+
+        let mut ids = vec![later, earlier];
+        ids.sort_by(|a, b| cmp_ids(*a, *b));
+        assert_eq!(ids, vec![earlier, later]);
+    }
+
+    #[test]
+    fn test_compose_inverts_decompose() {
+        let _guard = crate::pool::serial_guard();
+        let mut swit = crate::Switflake::builder()
+            .node_id(4095)
+            .field_offsets(0, 41, 53)
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = swit.layout();
+        for _ in 0..20 {
+            let id = swit.generate_id().expect("Failed to generate ID");
+            let p = decompose(id, &layout);
+            assert_eq!(
+                compose(p.timestamp, p.node_id, p.thread_id, p.counter, &layout),
+                Ok(id)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compose_rejects_out_of_range_fields() {
+        let layout = Layout::default();
+        assert_eq!(
+            compose(1 << 41, 0, 0, 0, &layout),
+            Err(SwitflakeError::FieldOverflow("timestamp"))
+        );
+        assert_eq!(
+            compose(0, 4096, 0, 0, &layout),
+            Err(SwitflakeError::FieldOverflow("node"))
+        );
+        assert_eq!(
+            compose(0, 0, 8, 0, &layout),
+            Err(SwitflakeError::FieldOverflow("thread"))
+        );
+        let narrow = Layout {
+            counter_bits: 4,
+            ..layout
+        };
+        assert_eq!(
+            compose(0, 0, 0, 16, &narrow),
+            Err(SwitflakeError::FieldOverflow("counter"))
+        );
+        assert_eq!(compose(0, 0, 0, 255, &layout), Ok(255));
+    }
+
+    #[test]
+    fn test_decompose_slice_matches_decompose() {
+        let layouts = [
+            Layout::default(),
+            Layout {
+                timestamp_shift: 0,
+                node_shift: 41,
+                sequence_shift: 53,
+                ..Layout::default()
+            },
+        ];
+        // xorshift64으로 재현 가능한 임의 입력 생성
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let ids: Vec<u64> = (0..100_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            })
+            .collect();
+        for layout in &layouts {
+            let mut out = vec![decompose(0, layout); ids.len()];
+            decompose_slice(&ids, &mut out, layout);
+            for (id, parts) in ids.iter().zip(&out) {
+                assert_eq!(*parts, decompose(*id, layout));
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_layout() {
+        let layout = Layout::default();
+        assert_eq!(validate_layout(&layout), Ok(()));
+        let invalid = |layout: Layout| match validate_layout(&layout) {
+            Err(SwitflakeError::InvalidLayout(reason)) => reason,
+            other => panic!("expected InvalidLayout, got {:?}", other),
+        };
+
+        // 필드 합이 64비트를 넘음
+        assert_eq!(
+            invalid(Layout {
+                timestamp_bits: 42,
+                ..layout
+            }),
+            "timestamp field does not fit in 64 bits"
+        );
+        assert_eq!(
+            invalid(Layout {
+                node_shift: 10,
+                ..layout
+            }),
+            "fields overlap"
+        );
+        assert_eq!(
+            invalid(Layout {
+                counter_bits: 0,
+                timestamp_bits: 49,
+                timestamp_shift: 15,
+                node_shift: 3,
+                ..layout
+            }),
+            "counter field has zero width"
+        );
+        assert_eq!(
+            invalid(Layout {
+                thread_bits: 2,
+                ..layout
+            }),
+            "thread field too narrow for every pool slot"
+        );
+        assert_eq!(
+            invalid(Layout {
+                timestamp_bits: 40,
+                ..layout
+            }),
+            "field widths do not add up to 64 bits"
+        );
+    }
+
+    #[test]
+    fn test_compose_exact_reproduces_generated_id() {
+        let _guard = crate::pool::serial_guard();
+        let mut swit = crate::Switflake::new(4095).expect("Failed to create Switflake");
+        for _ in 0..3 {
+            let id = swit.generate_id().expect("Failed to generate ID");
+            let p = decompose(id, &swit.layout());
+            assert_eq!(
+                compose_exact(p.timestamp, p.node_id, p.thread_id, p.counter),
+                Ok(id)
+            );
+        }
+        assert_eq!(
+            compose_exact(1 << 41, 0, 0, 0),
+            Err(SwitflakeError::FieldOverflow("timestamp"))
+        );
+        assert_eq!(
+            compose_exact(0, 0, 8, 0),
+            Err(SwitflakeError::FieldOverflow("thread"))
+        );
+    }
+
+    #[test]
+    fn test_remap_node() {
+        let _guard = crate::pool::serial_guard();
+        let mut swit = crate::Switflake::new(1).expect("Failed to create Switflake");
+        let layout = swit.layout();
+        let id = swit.generate_id().expect("Failed to generate ID");
+        let map = HashMap::from([(1, 5), (2, 4096)]);
+
+        let remapped = remap_node(id, &map, &layout).expect("Failed to remap");
+        let (before, after) = (decompose(id, &layout), decompose(remapped, &layout));
+        assert_eq!(after.node_id, 5);
+        assert_eq!(after.timestamp, before.timestamp);
+        assert_eq!(
+            (after.thread_id, after.counter),
+            (before.thread_id, before.counter)
+        );
+
+        // 매핑에 없는 노드는 그대로 통과
+        assert_eq!(remap_node(remapped, &map, &layout), Ok(remapped));
+        let node2 = pack(&layout, 1_000, 2, 0);
+        assert_eq!(
+            remap_node(node2, &map, &layout),
+            Err(SwitflakeError::FieldOverflow("node"))
+        );
+    }
+
+    #[test]
+    fn test_rebase_preserves_order() {
+        let layout = Layout::default();
+        let old_epoch = 0;
+        let new_epoch = 1_600_000_000_000;
+        let mut ids: Vec<u64> = [
+            (1_700_000_000_000, 3, 9),
+            (1_700_000_000_000, 3, 10),
+            (1_700_000_000_000, 4, 0),
+            (1_700_000_000_001, 1, 0),
+            (1_800_000_000_000, 0, 0),
+        ]
+        .iter()
+        .map(|&(millis, node, sequence)| pack(&layout, millis, node, sequence))
+        .collect();
+        ids.reverse();
+
+        let mut rebased: Vec<u64> = ids
+            .iter()
+            .map(|&id| rebase_id(id, old_epoch, new_epoch, &layout).expect("Failed to rebase"))
+            .collect();
+        // 재기준 전후의 정렬 순서가 같아야 함
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_by_key(|&i| ids[i]);
+        let mut rebased_order: Vec<usize> = (0..ids.len()).collect();
+        rebased_order.sort_by_key(|&i| rebased[i]);
+        assert_eq!(order, rebased_order);
+
+        rebased.sort_unstable();
+        let first = decompose(rebased[0], &layout);
+        assert_eq!(first.timestamp, 100_000_000_000);
+        assert_eq!((first.node_id, first.thread_id, first.counter), (3, 0, 9));
+        assert_eq!(
+            rebase_id(rebased[0], new_epoch, old_epoch, &layout),
+            Ok(ids[4])
+        );
+
+        assert_eq!(
+            rebase_id(ids[4], old_epoch, 1_800_000_000_000, &layout),
+            Err(SwitflakeError::FieldOverflow("timestamp"))
+        );
+        assert_eq!(
+            rebase_id(ids[0], new_epoch, 0, &layout),
+            Err(SwitflakeError::TimestampOverflow)
+        );
+    }
+
+    #[test]
+    fn test_rebase_stream_reports_errors_in_place() {
+        let layout = Layout::default();
+        let new_epoch = 1_650_000_000_000;
+        let millis = [
+            1_700_000_000_000,
+            1_700_000_000_500,
+            1_600_000_000_000,
+            1_750_000_000_000,
+        ];
+        let ids = millis.iter().map(|&ms| pack(&layout, ms, 9, 1));
+
+        let rebased: Vec<_> = rebase_stream(ids, 0, new_epoch, &layout).collect();
+        assert_eq!(rebased.len(), 4);
+        // 세 번째만 새 에포크 이전이라 실패하고, 뒤의 ID는 계속 나옴
+        assert_eq!(rebased[2], Err(SwitflakeError::FieldOverflow("timestamp")));
+        for i in [0, 1, 3] {
+            let parts = decompose(*rebased[i].as_ref().unwrap(), &layout);
+            assert_eq!(parts.timestamp, millis[i] - new_epoch);
+            assert_eq!((parts.node_id, parts.counter), (9, 1));
+        }
+        let kept: Vec<u64> = rebased.into_iter().filter_map(Result::ok).collect();
+        assert!(kept.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_time_bucket_groups_by_window() {
+        let layout = Layout::default();
+        let epoch = 1_600_000_000_123;
+        let id_at = |unix_millis: u64, sequence| pack(&layout, unix_millis - epoch, 5, sequence);
+        let start = 1_700_000_000_000;
+
+        let bucket = time_bucket(id_at(start, 0), 1_000, epoch, &layout);
+        assert_eq!(bucket, start);
+        for (offset, sequence) in [(1, 3), (500, 0), (999, 255)] {
+            assert_eq!(
+                time_bucket(id_at(start + offset, sequence), 1_000, epoch, &layout),
+                bucket
+            );
+        }
+        // 인접한 창은 다른 버킷
+        assert_eq!(
+            time_bucket(id_at(start + 1_000, 0), 1_000, epoch, &layout),
+            start + 1_000
+        );
+        assert_eq!(
+            time_bucket(id_at(start - 1, 0), 1_000, epoch, &layout),
+            start - 1_000
+        );
+    }
+
+    #[test]
+    fn test_from_u64() {
+        let id = pack(&Layout::default(), 1_700_000_000_000, 42, (5 << 8) | 17);
+        let parts: DecomposedId = id.into();
+        assert_eq!(parts, decompose(id, &Layout::default()));
+        // 기본 레이아웃에는 예약 비트가 없어 모든 값이 유효함
+        assert_eq!(Layout::default().reserved_mask(), 0);
+        assert_eq!(
+            DecomposedId::from(u64::MAX),
+            decompose(u64::MAX, &Layout::default())
+        );
+    }
+
+    #[test]
+    fn test_decompose_default_layout() {
+        let layout = Layout::default();
+        let id = pack(&layout, 1_700_000_000_000, 42, (5 << 8) | 17);
+        assert_eq!(
+            decompose(id, &layout),
+            DecomposedId {
+                timestamp: 1_700_000_000_000,
+                node_id: 42,
+                thread_id: 5,
+                counter: 17,
+                version: 0,
+                kind: 0,
+                namespace: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_raw_sequence_joins_thread_and_counter() {
+        let _guard = crate::pool::serial_guard();
+        let mut swit = crate::Switflake::new(77).expect("Failed to create Switflake");
+        let layout = swit.layout();
+        for _ in 0..20 {
+            let id = swit.generate_id().expect("Failed to generate ID");
+            let parts = decompose(id, &layout);
+            assert_eq!(
+                raw_sequence(id, &layout),
+                ((parts.thread_id as u64) << layout.counter_bits) | parts.counter as u64
+            );
+        }
+        assert_eq!(raw_sequence(u64::MAX, &layout), 0x7FF);
+    }
+
+    #[test]
+    fn test_default_layout_constant_packs_like_generator() {
+        const NODE_MASK: u64 = DEFAULT_LAYOUT.node_mask();
+        assert_eq!(NODE_MASK, 0xFFF);
+        assert_eq!(Layout::default(), DEFAULT_LAYOUT);
+
+        let tuples = [
+            (0, 0, 0, 0),
+            (1, 1, 1, 1),
+            (1_700_000_000_000, 42, 5, 200),
+            (DEFAULT_LAYOUT.timestamp_mask(), 0xFFF, 7, 254),
+        ];
+        for (timestamp, node, thread, counter) in tuples {
+            let expected = timestamp << 23 | node << 11 | (thread as u64) << 8 | counter as u64;
+            assert_eq!(
+                compose(timestamp, node, thread, counter, &DEFAULT_LAYOUT),
+                Ok(expected)
+            );
+        }
+
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(42).expect("Failed to create Switflake");
+        for _ in 0..100 {
+            let id = swit.generate_id_blocking().expect("Failed to generate ID");
+            let parts = decompose(id, &DEFAULT_LAYOUT);
+            assert_eq!(parts.node_id, 42);
+            assert_eq!(
+                compose(
+                    parts.timestamp,
+                    parts.node_id,
+                    parts.thread_id,
+                    parts.counter,
+                    &DEFAULT_LAYOUT
+                ),
+                Ok(id)
+            );
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Logical generators that hold a pool slot only while they are busy.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::layout::Counter;
+use crate::{Clock, Switflake, SwitflakeBuilder, SwitflakeError, SystemClock, ThreadIdPool};
+
+// Last (timestamp, next counter) emitted on each slot by a lazy generator
+// that has since let it go. The next lazy generator to take the slot resumes
+// from here, so it cannot repeat a (timestamp, thread, counter) triple that
+// was already handed out in the same millisecond.
+static HANDOFF: Mutex<[(u64, Counter); <ThreadIdPool>::CAPACITY]> =
+    Mutex::new([(0, 0); <ThreadIdPool>::CAPACITY]);
+
+/// A generator that takes a thread-id slot from the pool only while it is
+/// in use and gives it back once it has been idle for `idle_timeout`.
+///
+/// This lets an application keep more logical generators than the pool has
+/// slots, as long as no more than [`ThreadIdPool::CAPACITY`] of them are busy
+/// at once. The idle check runs on every call to
+/// [`generate_id`](Self::generate_id) and [`release_if_idle`](Self::release_if_idle);
+/// nothing happens in the background. A zero timeout returns the slot right
+/// after every ID.
+///
+/// IDs stay unique across lazy generators sharing a slot, provided they use
+/// the same epoch and layout. Ordinary [`Switflake`]s that take the same slot
+/// in between do not see the hand-off state.
+pub struct LazyGenerator<C = SystemClock> {
+    builder: SwitflakeBuilder<C>,
+    idle_timeout: Duration,
+    active: Option<(Switflake<C>, Instant)>,
+}
+
+impl LazyGenerator {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new(node_id: u64, idle_timeout: Duration) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .build_lazy(idle_timeout)
+    }
+}
+
+impl<C: Clock + Clone> LazyGenerator<C> {
+    pub(crate) fn new_unchecked(builder: SwitflakeBuilder<C>, idle_timeout: Duration) -> Self {
+        LazyGenerator {
+            builder,
+            idle_timeout,
+            active: None,
+        }
+    }
+
+    /// Generates an ID, taking a pool slot first if this generator does not
+    /// hold one. Fails with [`SwitflakeError::PoolExhausted`] if every slot
+    /// is busy.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id(&mut self) -> Result<u64, SwitflakeError> {
+        self.release_if_idle();
+        if self.active.is_none() {
+            let mut swit = self.builder.clone().build()?;
+            let (timestamp, counter) = lock_handoff()[swit.thread_id as usize];
+            swit.last_timestamp = timestamp;
+            swit.local_counter = counter;
+            swit.refresh_prefix();
+            self.active = Some((swit, Instant::now()));
+        }
+        let (swit, last_used) = self.active.as_mut().expect("slot acquired above");
+        *last_used = Instant::now();
+        let result = swit.generate_id();
+        if self.idle_timeout.is_zero() {
+            self.release();
+        }
+        result
+    }
+}
+
+impl<C> LazyGenerator<C> {
+    /// Whether this generator currently holds a pool slot.
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Gives the slot back if it has been unused for `idle_timeout`.
+    /// Returns whether a slot was released.
+    pub fn release_if_idle(&mut self) -> bool {
+        match &self.active {
+            Some((_, last_used)) if last_used.elapsed() >= self.idle_timeout => {
+                self.release();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Gives the slot back immediately.
+    pub fn release(&mut self) {
+        if let Some((swit, _)) = self.active.take() {
+            let mut handoff = lock_handoff();
+            let entry = &mut handoff[swit.thread_id as usize];
+            let state = (swit.last_timestamp, swit.local_counter);
+            if state > *entry {
+                *entry = state;
+            }
+            // 상태를 기록한 뒤 슬롯 반환 (drop 시 release)
+            drop(swit);
+        }
+    }
+}
+
+impl<C> Drop for LazyGenerator<C> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn lock_handoff() -> std::sync::MutexGuard<'static, [(u64, Counter); <ThreadIdPool>::CAPACITY]> {
+    HANDOFF.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+fn reset_handoff() {
+    *lock_handoff() = [(0, 0); <ThreadIdPool>::CAPACITY];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_more_logical_generators_than_slots() {
+        let _guard = serial_guard();
+        reset_handoff();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut lazies: Vec<_> = (0..20)
+            .map(|_| {
+                Switflake::builder()
+                    .node_id(5)
+                    .clock(clock.clone())
+                    .build_lazy(Duration::ZERO)
+                    .expect("Failed to create LazyGenerator")
+            })
+            .collect();
+
+        // 같은 밀리초 안에서 슬롯을 계속 주고받음
+        let mut ids = HashSet::new();
+        for round in 0..30 {
+            for lazy in &mut lazies {
+                let id = lazy.generate_id().expect("Failed to generate ID");
+                assert!(ids.insert(id), "Duplicate ID found: {}", id);
+            }
+            if round % 10 == 9 {
+                clock.advance(1);
+            }
+        }
+        assert_eq!(ids.len(), 20 * 30);
+    }
+
+    #[test]
+    fn test_busy_generators_hold_their_slots() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let build = || {
+            Switflake::builder()
+                .clock(clock.clone())
+                .build_lazy(Duration::from_secs(3600))
+                .expect("Failed to create LazyGenerator")
+        };
+        let mut busy: Vec<_> = (0..8).map(|_| build()).collect();
+        for lazy in &mut busy {
+            lazy.generate_id().expect("Failed to generate ID");
+            assert!(lazy.is_active());
+        }
+        let mut extra = build();
+        assert_eq!(extra.generate_id(), Err(SwitflakeError::PoolExhausted));
+
+        busy[0].release();
+        assert!(!busy[0].is_active());
+        assert!(extra.generate_id().is_ok());
+    }
+}
@@ -1,103 +1,1001 @@
-use std::sync::atomic::{AtomicU8, Ordering};
-use std::sync::OnceLock;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::num::NonZeroU64;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-struct ThreadIdPool {
-    used_ids: AtomicU8,
+mod audit;
+mod block;
+mod builder;
+mod clock;
+mod columnar;
+mod config;
+mod coordinator;
+mod delta;
+mod encoder;
+mod encoding;
+mod error;
+#[cfg(feature = "time")]
+mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod id;
+mod id_builder;
+mod iter;
+#[cfg(feature = "serde")]
+mod json;
+mod ksuid;
+mod layout;
+mod lazy;
+#[cfg(feature = "paranoid")]
+mod live_pairs;
+#[cfg(feature = "memmap")]
+mod mapped;
+mod multi;
+mod numa;
+mod obfuscate;
+mod object_id;
+mod on_drop;
+mod order;
+mod pool;
+mod preset;
+mod priority;
+mod proto;
+#[cfg(feature = "getrandom")]
+mod random;
+mod range;
+mod rate;
+#[cfg(any(debug_assertions, feature = "paranoid"))]
+mod recent;
+mod reconfigure;
+mod region;
+mod registry;
+mod relative;
+mod reserve;
+mod service;
+mod shard;
+mod shared;
+mod state_log;
+mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod ulid;
+mod uniqueness;
+#[cfg(feature = "paranoid")]
+mod watermark;
+mod wide;
+
+pub use audit::{
+    audit_ids, first_monotonicity_violation, node_distribution, suspicious_node_zero_ratio,
+    AuditReport,
+};
+pub use block::{BlockStrategy, GenerateCost, OverflowStrategy};
+pub use builder::SwitflakeBuilder;
+pub use clock::{
+    Clock, ClockFallback, FixedClock, InstantSource, MonotonicSource, MonotonicSystemClock,
+    SharedTickClock, SystemClock, TimeUnit,
+};
+pub use columnar::ColumnarIds;
+pub use config::{SwitflakeConfig, SwitflakeConfigView};
+pub use coordinator::SequenceCoordinator;
+pub use delta::{decode_frame, delta_decode, delta_encode, encode_frame};
+pub use encoder::{
+    Base36, Base62, Base62Checked, Base62Padded, Crockford, EncoderRegistry, Hex, IdEncoder,
+};
+pub use encoding::{
+    decode_any, decode_base32, decode_base36, decode_base62, decode_base62_padded, decode_lines,
+    decode_with_check, encode_base32, encode_base36, encode_base62, encode_base62_padded,
+    encode_with_check, from_hex, is_valid_base62, to_hex, try_decode_base62_nonalloc, Alphabet,
+    Base32Display, Base62Display,
+};
+pub use error::SwitflakeError;
+pub use id::Id;
+pub use id_builder::IdBuilder;
+pub use iter::IdIter;
+pub use ksuid::{from_ksuid_like, to_ksuid_like};
+pub use layout::{
+    cmp_ids, compose, compose_exact, decompose, decompose_slice, id_kind, id_version,
+    is_same_millisecond, raw_sequence, rebase_id, rebase_stream, remap_node, time_bucket,
+    validate_layout, DecomposedId, Layout, DEFAULT_LAYOUT, KIND_BITS, VERSION_BITS,
+};
+pub use lazy::LazyGenerator;
+#[cfg(feature = "memmap")]
+pub use mapped::MappedIds;
+pub use multi::{GeneratorSet, MultiSlotGenerator};
+pub use numa::{NumaDetector, NumaThreadIdPool};
+pub use obfuscate::{deobfuscate, obfuscate};
+pub use object_id::{to_object_id_hex, ObjectIdGenerator};
+pub use on_drop::GeneratorState;
+pub use pool::{
+    PoolOrdering, PoolStats, ReleaseAllToken, SlotBitmap, SlotLease, Slots, ThreadIdPool,
+};
+pub use preset::{detect_layout, LayoutPreset};
+pub use priority::{Priority, PriorityPool};
+pub use proto::{from_proto_sfixed64, from_proto_uint64, to_proto_sfixed64, to_proto_uint64};
+#[cfg(feature = "getrandom")]
+pub use random::RandomIdGenerator;
+pub use range::{max_id_at, min_id_at};
+pub use region::{decompose_regional, id_to_region, Region, REGION_BITS};
+pub use registry::GeneratorRegistry;
+pub use relative::{id_to_relative_millis, RelativeClock};
+pub use reserve::{ReservedDropPolicy, ReservedRange};
+pub use service::GeneratorService;
+pub use shard::{
+    from_zorder, id_hash32, id_to_shard, scramble, to_composite_key, to_zorder, unscramble,
+};
+pub use shared::SharedSwitflake;
+pub use state_log::StateLog;
+pub use ulid::{format_ulid, to_ulid_like};
+pub use uniqueness::{DuplicateVerdict, StreamingUniquenessChecker};
+pub use wide::{decompose128, DecomposedId128, Switflake128};
+
+pub struct Switflake<C = SystemClock> {
+    node_id: u64,
+    thread_id: u8,
+    generation: u32,
+    last_timestamp: u64,
+    local_counter: layout::Counter,
+    // Every bit of this tick's IDs except the counter, repacked only when
+    // the tick or the borrowed node bits change.
+    prefix: u64,
+    layout: Layout,
+    epoch: u64,
+    time_unit: TimeUnit,
+    reverse_order: bool,
+    global_order: bool,
+    block_strategy: BlockStrategy,
+    max_future_skew: Option<Duration>,
+    // When the current tick was first seen, for the future skew guard.
+    skew_reference: Option<Instant>,
+    obfuscation_key: Option<u64>,
+    namespace_bit: bool,
+    // Kept in the top node bits when the layout is versioned.
+    version: u8,
+    // Kind bits of the ID being generated; 0 outside `generate_kind`.
+    kind: u8,
+    // Top node bits usable as extra counter bits, and how many of their
+    // values this tick has already used.
+    adaptive_node_bits: u32,
+    borrowed: u64,
+    // IDs issued through the checked paths, and the Unix time in
+    // microseconds of the tick the first of them carries.
+    generated: u64,
+    first_generated_micros: Option<u64>,
+    // None when the layout has no thread field and no slot was taken.
+    pool: Option<&'static dyn pool::SlotPool>,
+    registry: &'static GeneratorRegistry,
+    state_log: Option<std::sync::Arc<std::sync::Mutex<StateLog>>>,
+    reserved_drop_policy: ReservedDropPolicy,
+    #[cfg(any(debug_assertions, feature = "paranoid"))]
+    recent_ids: Option<recent::RecentIds>,
+    #[cfg(feature = "getrandom")]
+    random_start_sequence: bool,
+    on_drop: Option<on_drop::DropCallback>,
+    overflow_strategy: OverflowStrategy,
+    // Extra slots taken this tick under `OverflowStrategy::BorrowSlot`.
+    borrowed_slots: Vec<SlotLease>,
+    rate_breaker: Option<rate::RateBreaker>,
+    coordinator: Option<&'static SequenceCoordinator>,
+    // Sequence values left in the block last claimed from `coordinator`.
+    coordinated_block: std::ops::Range<u64>,
+    clock: C,
 }
 
-impl ThreadIdPool {
-    fn global() -> &'static ThreadIdPool {
-        static POOL: OnceLock<ThreadIdPool> = OnceLock::new();
-        POOL.get_or_init(|| ThreadIdPool {
-            used_ids: AtomicU8::new(0),
-        })
+impl Switflake {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new(node_id: u64) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new().node_id(node_id).build()
+    }
+
+    /// Like [`new`](Self::new), but returns `Ok(None)` instead of waiting or
+    /// failing when no pool slot is free right now.
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn try_new(node_id: u64) -> Result<Option<Self>, SwitflakeError> {
+        SwitflakeBuilder::new().node_id(node_id).try_build()
+    }
+
+    /// Like [`new`](Self::new), but waits up to `timeout` (forever if
+    /// `None`) for a pool slot to be freed instead of failing with
+    /// [`SwitflakeError::PoolExhausted`]; see
+    /// [`SwitflakeBuilder::build_blocking`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new_blocking(node_id: u64, timeout: Option<Duration>) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .build_blocking(timeout)
+    }
+
+    /// Like [`new_blocking`](Self::new_blocking) without a timeout, but
+    /// awaits a free pool slot instead of blocking the thread; see
+    /// [`SwitflakeBuilder::build_async`].
+    #[cfg(feature = "async")]
+    pub async fn new_async(node_id: u64) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new().node_id(node_id).build_async().await
+    }
+
+    /// Like [`new`](Self::new), but takes thread id `preferred` if that
+    /// pool slot is free rather than the lowest free one; see
+    /// [`SwitflakeBuilder::preferred_thread_id`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new_preferring(node_id: u64, preferred: u8) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .preferred_thread_id(preferred)
+            .build()
     }
 
-    fn acquire(&self) -> Result<u8, &'static str> {
+    /// Creates a generator that never touches the pool: `thread_id` is put
+    /// in every ID as is, so creation only fails for a `node_id` or
+    /// `thread_id` too wide for the default layout, with
+    /// [`SwitflakeError::FieldOverflow`]. Keeping thread ids apart is then
+    /// up to the caller; see [`SwitflakeBuilder::fixed_thread_id`].
+    pub fn new_poolless(node_id: u64, thread_id: u8) -> Result<Self, SwitflakeError> {
+        if node_id & !DEFAULT_LAYOUT.node_mask() != 0 {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .fixed_thread_id(thread_id)
+            .build()
+    }
+
+    /// Creates `count` generators for `node_id` or none at all; see
+    /// [`SwitflakeBuilder::build_batch`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn try_new_batch(node_id: u64, count: usize) -> Result<Vec<Self>, SwitflakeError> {
+        SwitflakeBuilder::new().node_id(node_id).build_batch(count)
+    }
+
+    pub fn builder() -> SwitflakeBuilder {
+        SwitflakeBuilder::new()
+    }
+
+    /// Like [`new`](Self::new), but every generated ID is passed through
+    /// [`obfuscate`] with `key`. Holders of the key recover the plain ID with
+    /// [`deobfuscate`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new_obfuscated(node_id: u64, key: u64) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .obfuscation_key(key)
+            .build()
+    }
+
+    /// Creates a generator without being given a node id, for scripts and
+    /// tests where assigning one is overkill: the node id is a hash of the
+    /// process id and a random salt drawn once per process, so every
+    /// ephemeral generator in a process shares it and the pool keeps them
+    /// apart as usual.
+    ///
+    /// Separate processes are only unlikely, not guaranteed, to differ:
+    /// with the 12-bit default node field two processes share a node id
+    /// one time in 4,096, and among `n` processes some pair does with odds
+    /// of about `n² / 8192`. IDs from processes sharing a node id can
+    /// collide. Give long-lived or numerous processes real node ids.
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn ephemeral() -> Result<Self, SwitflakeError> {
+        static SALT: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+        let salt = *SALT.get_or_init(|| {
+            use std::hash::{BuildHasher, Hasher};
+            // RandomState는 프로세스마다 무작위 키로 시작
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_u64(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as u64),
+            );
+            hasher.finish()
+        });
+        Switflake::ephemeral_for(std::process::id(), salt)
+    }
+
+    fn ephemeral_for(pid: u32, salt: u64) -> Result<Self, SwitflakeError> {
+        let node_id = id_hash32(salt ^ pid as u64) as u64 & DEFAULT_LAYOUT.node_mask();
+        Switflake::new(node_id)
+    }
+}
+
+impl Switflake<FixedClock> {
+    /// Creates a generator whose clock is frozen at `fixed_millis`, so its
+    /// IDs differ only by counter and are reproducible: created from the
+    /// same pool state (it takes the lowest free thread id), two such
+    /// generators mint the same IDs. Once the millisecond's counter runs out
+    /// every call fails with [`SwitflakeError::SequenceExhausted`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn with_fixed_time(node_id: u64, fixed_millis: u64) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .clock(FixedClock {
+                millis: fixed_millis,
+            })
+            .build()
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Creates a generator for `node_id` that continues after `max_id`, the
+    /// highest ID already stored for this node, so a restart never emits an
+    /// ID at or below it, even within the same millisecond.
+    ///
+    /// If `max_id` comes from the current millisecond, the counter resumes
+    /// right after `max_id`'s when the new generator got the same thread id,
+    /// and the rest of the millisecond is skipped when it got a lower one. An
+    /// older `max_id` changes nothing; one from the future makes generation
+    /// fail with [`SwitflakeError::ClockMovedBackwards`] until `clock`
+    /// catches up. Fails with [`SwitflakeError::SequenceExhausted`] if
+    /// `max_id`'s counter is already at its maximum.
+    pub fn resume_from_max_id(node_id: u64, max_id: u64, clock: C) -> Result<Self, SwitflakeError> {
+        let mut swit = SwitflakeBuilder::new()
+            .node_id(node_id)
+            .clock(clock)
+            .build()?;
+        let max = decompose(max_id, &swit.layout);
+        let counter_mask = swit.layout.counter_mask();
+        let counter = match swit.thread_id.cmp(&max.thread_id) {
+            std::cmp::Ordering::Equal if max.counter as u64 >= counter_mask => {
+                return Err(SwitflakeError::SequenceExhausted);
+            }
+            std::cmp::Ordering::Equal => max.counter + 1,
+            std::cmp::Ordering::Greater => 0,
+            std::cmp::Ordering::Less => counter_mask as layout::Counter,
+        };
+        // 쿨다운이나 보호된 범위로 이미 더 늦은 상태에서 시작하면 그대로 둠
+        if (max.timestamp, 0, counter) > (swit.last_timestamp, swit.borrowed, swit.local_counter) {
+            swit.last_timestamp = max.timestamp;
+            swit.local_counter = counter;
+            swit.borrowed = 0;
+            swit.refresh_prefix();
+        }
+        Ok(swit)
+    }
+}
+
+impl<C> Switflake<C> {
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Unix time, in milliseconds, that timestamp 0 stands for.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
+
+    /// IDs this generator can sustain per second, for capacity planning:
+    /// [`Layout::max_throughput_per_second`] for its layout and time unit,
+    /// counting the node bits it may borrow as counter bits.
+    pub fn max_throughput_per_second(&self) -> u64 {
+        layout::mask(self.layout.counter_bits + self.adaptive_node_bits)
+            .saturating_add(1)
+            .saturating_mul(self.time_unit.ticks_per_second())
+    }
+
+    /// The epoch in ticks of the time unit since the Unix epoch.
+    #[inline]
+    fn epoch_ticks(&self) -> u64 {
+        self.time_unit.ticks_in_millis(self.epoch)
+    }
+
+    /// Unix time in microseconds at which the last tick ends.
+    fn tick_end_micros(&self) -> u64 {
+        (self.epoch_ticks() + self.last_timestamp + 1) * self.time_unit.as_micros()
+    }
+
+    /// The pool slot this generator holds, for use with
+    /// [`ThreadIdPool::force_release`].
+    pub fn lease(&self) -> SlotLease {
+        SlotLease {
+            thread_id: self.thread_id,
+            generation: self.generation,
+        }
+    }
+
+    /// How many IDs this generator has issued, for measuring its rate
+    /// together with [`first_generated_at`](Self::first_generated_at). A
+    /// plain local count, independent of the `metrics` feature;
+    /// [`generate_id_unchecked`](Self::generate_id_unchecked) doesn't add
+    /// to it.
+    pub fn generated_count(&self) -> u64 {
+        self.generated
+    }
+
+    /// The counter's position within the current tick: the value the next
+    /// ID of this tick will carry. Read as of the last ID: the first ID of a
+    /// later tick starts over at 0.
+    pub fn current_sequence(&self) -> u16 {
+        self.local_counter
+    }
+
+    /// How many more IDs fit in the current tick's counter field before
+    /// [`SwitflakeError::SequenceExhausted`], as of the last ID. An
+    /// [`adaptive counter`](SwitflakeBuilder::adaptive_counter) may still
+    /// widen past it.
+    pub fn sequence_remaining(&self) -> u16 {
+        (self.layout.counter_mask() as u16).saturating_sub(self.local_counter)
+    }
+
+    /// Start, by this generator's clock, of the tick the first ID of this
+    /// generator carries, or `None` before the first ID.
+    pub fn first_generated_at(&self) -> Option<SystemTime> {
+        self.first_generated_micros
+            .map(|micros| UNIX_EPOCH + Duration::from_micros(micros))
+    }
+
+    /// Wall-clock time at which `id` was minted, according to this
+    /// generator's layout.
+    pub fn timestamp(&self, id: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_micros(self.unix_micros_of(id))
+    }
+
+    fn unix_micros_of(&self, id: u64) -> u64 {
+        let ticks = self.epoch_ticks() + self.timestamp_field(self.layout.timestamp_of(id));
+        ticks * self.time_unit.as_micros()
+    }
+
+    /// The node id plus the version and kind, if any, and the counter bits
+    /// currently borrowed from the node field.
+    #[inline]
+    fn node_field(&self) -> u64 {
+        let shift = self.layout.node_bits - self.adaptive_node_bits;
+        self.node_id
+            | self.layout.version_field(self.version)
+            | self.layout.kind_field(self.kind)
+            | self.borrowed.checked_shl(shift).unwrap_or(0)
+    }
+
+    /// Repacks `prefix`; call after changing anything but the counter.
+    #[inline]
+    fn refresh_prefix(&mut self) {
+        let timestamp = self.last_timestamp & self.layout.timestamp_mask();
+        let mut prefix = self.layout.pack(
+            self.timestamp_field(timestamp),
+            self.node_field(),
+            self.current_thread_id(),
+            0,
+        );
+        if self.namespace_bit {
+            prefix |= layout::NAMESPACE_BIT;
+        }
+        self.prefix = prefix;
+    }
+
+    /// Maps a timestamp to the value stored in the timestamp field, and
+    /// back: the identity unless IDs are generated in reverse order.
+    #[inline]
+    fn timestamp_field(&self, timestamp: u64) -> u64 {
+        if self.reverse_order {
+            self.layout.timestamp_mask() - timestamp
+        } else {
+            timestamp
+        }
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    #[inline]
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id(&mut self) -> Result<u64, SwitflakeError> {
+        self.generate_decomposed().map(|(id, _)| id)
+    }
+
+    /// Generates an ID and also returns its fields, built directly rather
+    /// than by decoding the packed value. For obfuscated generators the fields
+    /// are those of the deobfuscated ID; in reverse order mode the timestamp
+    /// is the stored, inverted value, as [`decompose`] would return it.
+    #[inline]
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_decomposed(&mut self) -> Result<(u64, DecomposedId), SwitflakeError> {
+        self.generate_parts().map(|(id, parts, _)| (id, parts))
+    }
+
+    /// Like [`generate_id`](Self::generate_id), but also reports whether this
+    /// call started a new millisecond, i.e. the first ID of a fresh
+    /// generator or the first since the clock moved on. Useful for cutting
+    /// batches at millisecond boundaries.
+    #[inline]
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id_tracked(&mut self) -> Result<(u64, bool), SwitflakeError> {
+        self.generate_parts()
+            .map(|(id, _, new_tick)| (id, new_tick))
+    }
+
+    /// Generates one ID carrying `tenant_node` in its node field instead of
+    /// the configured node id, and returns it along with `tenant_node`. The
+    /// configured node id is untouched, so one generator can mint IDs for
+    /// many tenants; as with node ids in general, no other generator may
+    /// be using `tenant_node` as its own at the same time.
+    ///
+    /// Fails with [`SwitflakeError::FieldOverflow`] if `tenant_node` does
+    /// not fit the node field.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_for_tenant(&mut self, tenant_node: u64) -> Result<(u64, u64), SwitflakeError> {
+        let node_bits = self.layout.node_id_bits() - self.adaptive_node_bits;
+        if tenant_node & !layout::mask(node_bits) != 0 {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        let own_node = std::mem::replace(&mut self.node_id, tenant_node);
+        self.refresh_prefix();
+        let result = self.generate_id();
+        self.node_id = own_node;
+        self.refresh_prefix();
+        result.map(|id| (id, tenant_node))
+    }
+
+    /// Generates one ID tagged with `kind`, read back by
+    /// [`id_kind`](crate::id_kind), so IDs of different record types can be
+    /// told apart by the ID alone. The layout must be
+    /// [`kinded`](Layout::kinded), or this fails with
+    /// [`SwitflakeError::InvalidLayout`].
+    ///
+    /// The kind sits above the sequence field, so IDs of one kind increase
+    /// within a generator but IDs of different kinds from the same
+    /// millisecond sort by kind first.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_kind(&mut self, kind: u8) -> Result<u64, SwitflakeError> {
+        if !self.layout.kinded {
+            return Err(SwitflakeError::InvalidLayout("kind needs a kinded layout"));
+        }
+        self.kind = kind;
+        self.refresh_prefix();
+        let result = self.generate_id();
+        self.kind = 0;
+        self.refresh_prefix();
+        result
+    }
+
+    /// How long until the clock runs past the last timestamp the layout can
+    /// hold, after which generating fails with
+    /// [`SwitflakeError::TimestampOverflow`]; zero once it has.
+    pub fn time_until_wraparound(&self) -> Result<Duration, SwitflakeError> {
+        let now = self.time_unit.now(&self.clock)?;
+        let end = self
+            .epoch_ticks()
+            .saturating_add(self.layout.timestamp_mask() + 1);
+        Ok(Duration::from_micros(
+            end.saturating_sub(now)
+                .saturating_mul(self.time_unit.as_micros()),
+        ))
+    }
+
+    /// Like [`generate_id`](Self::generate_id), but a panic inside it, e.g.
+    /// from a misbehaving [`Clock`], comes back as
+    /// [`SwitflakeError::Internal`] instead of unwinding, for FFI and
+    /// plugin hosts that must never unwind across their boundary.
+    ///
+    /// A generator that panicked mid-call may be left partway through a
+    /// tick change; drop it rather than generating from it again.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id_catch(&mut self) -> Result<u64, SwitflakeError> {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.generate_id()))
+            .unwrap_or(Err(SwitflakeError::Internal))
+    }
+
+    /// Like [`generate_id`](Self::generate_id), but never returns 0, which
+    /// callers may keep as a null or sentinel key.
+    ///
+    /// [`generate_id`](Self::generate_id) yields 0 only for the first ID of
+    /// the epoch's first tick from node 0 on thread 0 (or, for obfuscated
+    /// generators, the one ID that obfuscates to 0); this method skips that
+    /// ID and returns the next one instead.
+    #[inline]
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_nonzero(&mut self) -> Result<NonZeroU64, SwitflakeError> {
         loop {
-            let current = self.used_ids.load(Ordering::SeqCst);
-            if !current == 0 {
-                return Err("No available thread IDs (max 8)");
-            }
-            for i in 0..8 {
-                let mask = 1 << i;
-                if current & mask == 0 {
-                    let new_value = current | mask;
-                    if self
-                        .used_ids
-                        .compare_exchange(current, new_value, Ordering::SeqCst, Ordering::SeqCst)
-                        .is_ok()
-                    {
-                        return Ok(i);
+            if let Some(id) = NonZeroU64::new(self.generate_id()?) {
+                return Ok(id);
+            }
+        }
+    }
+
+    #[inline]
+    fn generate_parts(&mut self) -> Result<(u64, DecomposedId, bool), SwitflakeError> {
+        debug_assert_eq!(
+            self.pool.map_or(0, |pool| pool.generation(self.thread_id)),
+            self.generation,
+            "thread-id slot {} was reclaimed while still in use",
+            self.thread_id
+        );
+        debug_assert!(
+            self.thread_id as u64 <= self.layout.thread_mask(),
+            "thread id {} does not fit the {}-bit thread field",
+            self.thread_id,
+            self.layout.thread_bits
+        );
+
+        #[cfg(feature = "paranoid")]
+        let high_water = watermark::snapshot();
+        let now = self.read_clock()?;
+        let mut timestamp = now
+            .checked_sub(self.epoch_ticks())
+            .ok_or(SwitflakeError::ClockMovedBackwards)?;
+        // 범위를 검사하므로 이후 timestamp 마스킹은 필요 없음
+        if timestamp > self.layout.timestamp_mask() {
+            return Err(SwitflakeError::TimestampOverflow);
+        }
+        if timestamp < self.last_timestamp {
+            if self.coordinator.is_none() {
+                return Err(SwitflakeError::ClockMovedBackwards);
+            }
+            // 코디네이터에서 받은 더 늦은 틱에 합류
+            timestamp = self.last_timestamp;
+        }
+        let now_millis = now * self.time_unit.as_micros() / 1_000;
+        if let Some(breaker) = &mut self.rate_breaker {
+            breaker.check(now_millis)?;
+        }
+        let new_tick = timestamp > self.last_timestamp;
+        if new_tick && self.max_future_skew.is_some() {
+            self.check_future_skew(timestamp)?;
+        }
+        if let (true, Some(log)) = (new_tick, &self.state_log) {
+            log.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .append(now * self.time_unit.as_micros())
+                .map_err(|_| SwitflakeError::StateLogUnavailable)?;
+        }
+        #[cfg(feature = "paranoid")]
+        watermark::observe(high_water, now * self.time_unit.as_micros());
+        if new_tick {
+            if !self.borrowed_slots.is_empty() {
+                self.return_borrowed_slots(self.tick_end_micros());
+            }
+            self.last_timestamp = timestamp;
+            self.local_counter = self.first_counter()?;
+            self.borrowed = 0;
+            self.refresh_prefix();
+        } else if self.local_counter as u64 == self.layout.counter_mask() {
+            if self.borrowed < layout::mask(self.adaptive_node_bits) {
+                // 노드 필드의 빈 상위 비트를 카운터 확장으로 사용
+                self.borrowed += 1;
+                telemetry::counter_widened();
+            } else if !self.borrow_slot(now * self.time_unit.as_micros()) {
+                telemetry::sequence_exhausted();
+                return Err(SwitflakeError::SequenceExhausted);
+            }
+            self.local_counter = self.first_counter()?;
+            self.refresh_prefix();
+        }
+        if self.global_order {
+            let token = order::next_token(self.layout.counter_mask());
+            // 토큰이 한 바퀴 돌면 이번 밀리초에서는 더 이상 증가할 수 없음
+            if token < self.local_counter {
+                telemetry::sequence_exhausted();
+                return Err(SwitflakeError::SequenceExhausted);
+            }
+            self.local_counter = token;
+        }
+        if let Some(coordinator) = self.coordinator {
+            return self.generate_coordinated(coordinator, now, timestamp, new_tick);
+        }
+
+        // 카운터 폭이 바뀌어도 조용히 감싸지 않도록 미리 검사
+        let next_counter = self
+            .local_counter
+            .checked_add(1)
+            .ok_or(SwitflakeError::SequenceExhausted)?;
+        let field = self.timestamp_field(timestamp);
+        let node_field = self.node_field();
+        let parts = DecomposedId {
+            timestamp: field,
+            node_id: node_field & layout::mask(self.layout.node_id_bits()),
+            thread_id: self.current_thread_id(),
+            counter: self.local_counter,
+            version: self.version,
+            kind: self.kind,
+            namespace: self.namespace_bit,
+        };
+        let id = self.pack();
+        self.record_issued(id, &parts, now)?;
+        self.local_counter = next_counter;
+        Ok((id, parts, new_tick))
+    }
+
+    /// The rest of `generate_parts` for generators built with a
+    /// [`SequenceCoordinator`]: the whole sequence field comes from a block
+    /// claimed from it, instead of the thread id and counter.
+    fn generate_coordinated(
+        &mut self,
+        coordinator: &SequenceCoordinator,
+        now: u64,
+        timestamp: u64,
+        new_tick: bool,
+    ) -> Result<(u64, DecomposedId, bool), SwitflakeError> {
+        if new_tick {
+            self.coordinated_block = 0..0;
+        }
+        if self.coordinated_block.is_empty() {
+            let (tick, block) = coordinator
+                .claim(self.node_field(), timestamp, self.layout.sequence_mask())
+                .map_err(|e| {
+                    if e == SwitflakeError::SequenceExhausted {
+                        telemetry::sequence_exhausted();
                     }
-                }
+                    e
+                })?;
+            // 다른 생성기가 이미 더 늦은 틱으로 넘겼으면 그 틱으로 발급
+            if tick > self.last_timestamp {
+                self.last_timestamp = tick;
+                self.refresh_prefix();
             }
+            self.coordinated_block = block;
         }
+        let timestamp = self.last_timestamp;
+        let sequence = self.coordinated_block.start;
+        let node_field = self.node_field();
+        let parts = DecomposedId {
+            timestamp: self.timestamp_field(timestamp),
+            node_id: node_field & layout::mask(self.layout.node_id_bits()),
+            thread_id: (sequence >> self.layout.counter_bits) as u8,
+            counter: (sequence & self.layout.counter_mask()) as layout::Counter,
+            version: self.version,
+            kind: self.kind,
+            namespace: self.namespace_bit,
+        };
+        // 접두부의 스레드 필드는 0이므로 시퀀스 전체를 그대로 합침
+        let id = self.pack_sequence(sequence);
+        self.record_issued(id, &parts, now)?;
+        self.coordinated_block.start += 1;
+        Ok((id, parts, new_tick))
     }
 
-    fn release(&self, id: u8) {
-        let mask = !(1 << id);
-        self.used_ids.fetch_and(mask, Ordering::SeqCst);
+    /// Checks and counts `id`, just packed from `parts` at clock reading
+    /// `now`, as issued.
+    fn record_issued(
+        &mut self,
+        id: u64,
+        parts: &DecomposedId,
+        now: u64,
+    ) -> Result<(), SwitflakeError> {
+        // 레이아웃/시프트/마스크 회귀 감시; debug_assert라 릴리스 빌드에서는 비용 없음
+        debug_assert_eq!(
+            decompose(
+                self.obfuscation_key.map_or(id, |key| deobfuscate(id, key)),
+                &self.layout
+            ),
+            *parts,
+            "ID {:#x} does not decompose back into the fields it was packed from",
+            id
+        );
+        #[cfg(any(debug_assertions, feature = "paranoid"))]
+        if let Some(recent) = &mut self.recent_ids {
+            recent.check_and_record(id)?;
+        }
+        self.generated += 1;
+        let now_micros = now * self.time_unit.as_micros();
+        if let Some(breaker) = &mut self.rate_breaker {
+            breaker.record(now_micros / 1_000);
+        }
+        if self.first_generated_micros.is_none() {
+            self.first_generated_micros = Some(now_micros);
+        }
+        telemetry::id_generated();
+        Ok(())
     }
 
-    fn is_full(&self) -> bool {
-        !self.used_ids.load(Ordering::SeqCst) == 0
+    /// Generates an ID without the sequence-limit, clock and range checks of
+    /// [`generate_id`](Self::generate_id), for benchmarks and hot loops whose
+    /// callers can vouch for those conditions themselves.
+    ///
+    /// Obfuscation and reverse order still apply; the global order token,
+    /// random start, recent-ID guard, state log and metrics are skipped. A
+    /// clock read that fails reuses the last tick.
+    ///
+    /// # Safety
+    ///
+    /// Nothing memory-unsafe happens if the preconditions are broken, but
+    /// the IDs silently stop being unique or ordered, which callers must
+    /// treat just as seriously. The caller guarantees that:
+    ///
+    /// - no more IDs are requested per tick than the counter holds (255
+    ///   with the default layout),
+    /// - the clock never goes backwards and stays within the timestamp
+    ///   field's range for this epoch.
+    #[inline]
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub unsafe fn generate_id_unchecked(&mut self) -> u64 {
+        let now = self
+            .read_clock()
+            .unwrap_or(self.epoch_ticks() + self.last_timestamp);
+        let timestamp = now.wrapping_sub(self.epoch_ticks());
+        debug_assert!(timestamp >= self.last_timestamp, "clock moved backwards");
+        if timestamp != self.last_timestamp {
+            self.last_timestamp = timestamp;
+            self.local_counter = 0;
+            self.refresh_prefix();
+        }
+        debug_assert!(
+            (self.local_counter as u64) < self.layout.counter_mask(),
+            "sequence exhausted"
+        );
+        let id = self.pack();
+        self.local_counter = self.local_counter.wrapping_add(1);
+        id
     }
-}
 
-pub struct Switflake {
-    node_id: u64,
-    thread_id: u8,
-    local_counter: u8,
-}
+    /// The clock in ticks of the time unit: the first step of generating.
+    #[inline]
+    fn read_clock(&self) -> Result<u64, SwitflakeError> {
+        self.time_unit.now(&self.clock)
+    }
 
-impl Switflake {
-    pub fn new(node_id: u64) -> Result<Self, &'static str> {
-        let pool = ThreadIdPool::global();
-        if pool.is_full() {
-            return Err("Thread pool full (max 8 simultaneous threads)");
+    /// This tick's prefix with the current counter, obfuscated if set up:
+    /// the last step of generating.
+    #[inline]
+    fn pack(&self) -> u64 {
+        // 카운터는 항상 counter_bits 안에 들어가므로 접두부에 바로 합침
+        self.pack_sequence(self.local_counter as u64)
+    }
+
+    #[inline]
+    fn pack_sequence(&self, sequence: u64) -> u64 {
+        let id = self.prefix | sequence << self.layout.sequence_shift;
+        match self.obfuscation_key {
+            Some(key) => obfuscate(id, key),
+            None => id,
         }
-        let thread_id = pool.acquire()?;
-        Ok(Switflake {
-            node_id: node_id & 0xFFF,
-            thread_id,
-            local_counter: 0,
-        })
     }
 
+    /// Runs only the clock read of [`generate_id`](Self::generate_id), for
+    /// benchmarking it apart from the rest.
+    #[cfg(feature = "bench-internals")]
+    pub fn bench_read_clock(&self) -> Result<u64, SwitflakeError> {
+        self.read_clock()
+    }
+
+    /// Runs only the bit packing of [`generate_id`](Self::generate_id), for
+    /// benchmarking it apart from the rest. Leaves the generator as it was,
+    /// so it returns the same ID every time.
+    #[cfg(feature = "bench-internals")]
+    pub fn bench_pack(&self) -> u64 {
+        self.pack()
+    }
+
+    /// Fails if `timestamp`, a new tick, lies further ahead of the last one
+    /// than the monotonic time elapsed since plus the allowed skew.
+    fn check_future_skew(&mut self, timestamp: u64) -> Result<(), SwitflakeError> {
+        let now = Instant::now();
+        if let (Some(max_skew), Some(reference)) = (self.max_future_skew, self.skew_reference) {
+            let ahead = Duration::from_micros(
+                (timestamp - self.last_timestamp) * self.time_unit.as_micros(),
+            );
+            let delta = ahead.saturating_sub(now.duration_since(reference));
+            if delta > max_skew {
+                return Err(SwitflakeError::ClockJumpedForward { delta });
+            }
+        }
+        self.skew_reference = Some(now);
+        Ok(())
+    }
+
+    #[inline]
+    fn first_counter(&self) -> Result<layout::Counter, SwitflakeError> {
+        #[cfg(feature = "getrandom")]
+        if self.random_start_sequence {
+            return random::below(self.layout.counter_mask()).map(|n| n as layout::Counter);
+        }
+        Ok(0)
+    }
+
+    /// Like [`generate_id`](Self::generate_id), but when this millisecond's
+    /// sequence is exhausted it waits for the clock to move on, as set by
+    /// [`SwitflakeBuilder::block_strategy`], instead of returning
+    /// [`SwitflakeError::SequenceExhausted`].
     #[inline]
-    pub fn generate_id(&mut self) -> Result<u64, &'static str> {
-        if self.local_counter == 0xFF {
-            return Err("Sequence limit reached for this millisecond");
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id_blocking(&mut self) -> Result<u64, SwitflakeError> {
+        loop {
+            match self.generate_id() {
+                Err(SwitflakeError::SequenceExhausted) => self.wait_for_next_tick()?,
+                result => return result,
+            }
+        }
+    }
+
+    /// Like [`generate_id`](Self::generate_id), but on
+    /// [`SwitflakeError::SequenceExhausted`] sleeps `backoff` on the clock
+    /// and tries again, making at most `max_attempts` attempts (at least
+    /// one). Returns the last error if none succeeds; any other error is
+    /// returned straight away.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_retry(
+        &mut self,
+        max_attempts: u32,
+        backoff: Duration,
+    ) -> Result<u64, SwitflakeError> {
+        let mut attempts = 1;
+        loop {
+            match self.generate_id() {
+                Err(SwitflakeError::SequenceExhausted) if attempts < max_attempts => {
+                    self.clock.sleep(backoff);
+                    attempts += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Generates up to `max` IDs without ever waiting: stops early, returning
+    /// fewer, once this millisecond's sequence is exhausted. The length of
+    /// the result tells how much capacity was left.
+    #[must_use = "dropped IDs still use up sequence slots"]
+    pub fn generate_available(&mut self, max: usize) -> Result<Vec<u64>, SwitflakeError> {
+        let mut ids = Vec::with_capacity(max);
+        while ids.len() < max {
+            match self.generate_id() {
+                Err(SwitflakeError::SequenceExhausted) => break,
+                result => ids.push(result?),
+            }
         }
+        Ok(ids)
+    }
+
+    /// How long ago `id` was minted, by this generator's clock. IDs from the
+    /// future report zero.
+    pub fn age(&self, id: u64) -> Result<Duration, SwitflakeError> {
+        let now = self.clock.now_micros()?;
+        Ok(Duration::from_micros(
+            now.saturating_sub(self.unix_micros_of(id)),
+        ))
+    }
+
+    /// Endless iterator of IDs; see [`IdIter`].
+    pub fn iter(&mut self) -> IdIter<'_, C> {
+        IdIter::new(self)
+    }
+}
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|_| "Time went backwards")?
-            .as_millis() as u64
-            & 0x1FFFFFFFFFF;
-        let sequence = (self.thread_id as u64) << 8 | (self.local_counter as u64);
-        let id = (timestamp << 23) | (self.node_id << 11) | (sequence & 0x7FF);
-        self.local_counter += 1;
-        Ok(id)
+impl<C: Clock> Switflake<C> {
+    /// Holds off the first ID until the cooldown of this generator's slot is
+    /// over; see [`ThreadIdPool::set_reuse_cooldown`].
+    pub(crate) fn sit_out_cooldown(&mut self) {
+        let Some(pool) = self.pool else {
+            return;
+        };
+        let until = pool.cooling_until(self.thread_id);
+        if until <= self.clock.now_micros().unwrap_or(0) {
+            return;
+        }
+        let last_tick = (until - 1) / self.time_unit.as_micros();
+        if let Some(timestamp) = last_tick.checked_sub(self.epoch_ticks()) {
+            self.last_timestamp = timestamp;
+            self.local_counter = self.layout.counter_mask() as layout::Counter;
+            self.borrowed = layout::mask(self.adaptive_node_bits);
+            self.refresh_prefix();
+        }
     }
 }
 
-impl Drop for Switflake {
+impl<C> Drop for Switflake<C> {
     fn drop(&mut self) {
-        ThreadIdPool::global().release(self.thread_id);
+        self.run_on_drop();
+        if let Some(pool) = self.pool {
+            let issued_any = self.last_timestamp != 0 || self.local_counter != 0;
+            let end_micros = match issued_any {
+                true => self.tick_end_micros(),
+                false => 0,
+            };
+            self.return_borrowed_slots(end_micros);
+            pool.release_after(self.lease(), end_micros);
+        }
+        #[cfg(feature = "paranoid")]
+        if self.pool.is_none() && self.coordinator.is_none() {
+            live_pairs::release(self.node_id, self.thread_id);
+        }
+        self.registry.deregister();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
     use std::collections::HashSet;
     use std::thread;
 
     #[test]
     fn test_unique_ids_single_thread() {
+        let _guard = serial_guard();
         let mut swit = Switflake::new(1).expect("Failed to create Switflake");
         let mut ids = HashSet::new();
         for _ in 0..100 {
@@ -108,6 +1006,7 @@ mod tests {
     }
     #[test]
     fn test_pool_full_and_reuse() {
+        let _guard = serial_guard();
         let mut handles = Vec::new();
         // 스레드 8개
         for _ in 0..8 {
@@ -130,9 +1029,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_new_when_pool_full() {
+        let _guard = serial_guard();
+        let mut held: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake during setup"))
+            .collect();
+        assert!(ThreadIdPool::global().try_acquire().is_none());
+        assert!(matches!(Switflake::try_new(1), Ok(None)));
+
+        held.pop();
+        let mut swit = Switflake::try_new(1)
+            .expect("try_new failed")
+            .expect("Expected a free slot");
+        assert!(swit.generate_id().is_ok());
+    }
+
+    #[test]
+    fn test_try_new_batch_is_all_or_nothing() {
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+        let one = Switflake::new(1).expect("Failed to create Switflake");
+        assert_eq!(
+            Switflake::try_new_batch(1, 8).err(),
+            Some(SwitflakeError::PoolExhausted)
+        );
+        // 실패한 요청은 슬롯을 하나도 잡고 있지 않음
+        assert_eq!((0..8).filter(|&i| pool.is_held(i)).count(), 1);
+        drop(one);
+
+        let batch = Switflake::try_new_batch(1, 8).expect("Whole pool is free");
+        assert_eq!(batch.len(), 8);
+        assert!(pool.is_full());
+        let threads: HashSet<u8> = batch.iter().map(|s| s.lease().thread_id).collect();
+        assert_eq!(threads.len(), 8);
+        drop(batch);
+        assert!(!pool.is_full());
+        assert!(Switflake::try_new_batch(1, 9).is_err());
+    }
+
     #[test]
     fn test_sequence_limit() {
-        let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(1)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
         for _ in 0..255 {
             let _ = swit.generate_id().expect("Failed to generate ID");
         }
@@ -140,15 +1084,924 @@ mod tests {
             swit.generate_id().is_err(),
             "Should error at sequence limit"
         );
+
+        // 다음 밀리초에는 카운터가 다시 시작됨
+        clock.advance(1);
+        let (_, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert_eq!(parts.counter, 0);
+    }
+
+    #[test]
+    fn test_unmasked_timestamp_matches_masked_output() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(0);
+        let mut swit = Switflake::builder()
+            .node_id(9)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = swit.layout();
+        let horizon = layout.timestamp_mask();
+        let step = horizon / 1_000;
+        for timestamp in (0..=1_000).map(|i| i * step).chain([horizon]) {
+            clock.set(timestamp);
+            let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+            let masked = layout.pack(
+                timestamp & layout.timestamp_mask(),
+                parts.node_id,
+                parts.thread_id,
+                parts.counter,
+            );
+            assert_eq!(id, masked, "timestamp {}", timestamp);
+        }
+
+        // 범위를 넘으면 조용히 감싸지 않고 오류
+        clock.set(horizon + 1);
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::TimestampOverflow));
+    }
+
+    #[test]
+    fn test_namespace_bit_separates_ids() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let layout = Layout {
+            timestamp_bits: 40,
+            ..Layout::default()
+        };
+        let build = |namespace| {
+            Switflake::builder()
+                .node_id(7)
+                .layout(layout)
+                .epoch(1_600_000_000_000)
+                .namespace_bit(namespace)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake")
+        };
+        let (mut events, mut entities) = (build(false), build(true));
+
+        let mut ids = HashSet::new();
+        for _ in 0..200 {
+            let (event, parts) = events.generate_decomposed().expect("Failed to generate ID");
+            assert!(!parts.namespace && event >> 63 == 0);
+            assert_eq!(decompose(event, &layout), parts);
+            let (entity, parts) = entities
+                .generate_decomposed()
+                .expect("Failed to generate ID");
+            assert!(parts.namespace && entity >> 63 == 1);
+            assert_eq!(decompose(entity, &layout), parts);
+            // 비트 63만 다르고 나머지 필드는 같음
+            assert_eq!(entity & !(1 << 63), {
+                let other = decompose(event, &layout);
+                layout.pack(
+                    other.timestamp,
+                    other.node_id,
+                    parts.thread_id,
+                    other.counter,
+                )
+            });
+            assert!(ids.insert(event) && ids.insert(entity));
+        }
+
+        let now = entities.timestamp(ids.iter().copied().max().unwrap());
+        let range = entities
+            .id_range_for_time(now, now + Duration::from_millis(1))
+            .expect("Failed to compute range");
+        assert!(ids
+            .iter()
+            .filter(|&&id| range.contains(&id))
+            .all(|id| id >> 63 == 1));
+
+        // 기본 레이아웃은 타임스탬프가 비트 63까지 차지함
+        assert_eq!(
+            Switflake::builder().namespace_bit(true).build().map(|_| ()),
+            Err(SwitflakeError::InvalidLayout(
+                "namespace bit overlaps a field"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_reverse_order_newer_ids_are_smaller() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .reverse_order(true)
+            .build()
+            .expect("Failed to create Switflake");
+        let mut previous = swit.generate_id().expect("Failed to generate ID");
+        for step in [1, 1, 5, 1_000, 1] {
+            clock.advance(step);
+            let id = swit.generate_id().expect("Failed to generate ID");
+            assert!(id < previous, "{} not below {}", id, previous);
+            assert_eq!(
+                swit.timestamp(id),
+                UNIX_EPOCH + Duration::from_millis(clock.now_millis().unwrap())
+            );
+            previous = id;
+        }
+        assert_eq!(swit.age(previous), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_one_bit_counter() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .layout(Layout {
+                counter_bits: 1,
+                ..Layout::default()
+            })
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        assert!(swit.generate_id().is_ok());
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::SequenceExhausted));
+        clock.advance(1);
+        assert!(swit.generate_id().is_ok());
+    }
+
+    #[test]
+    fn test_generate_id_tracked_flags_new_milliseconds() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = swit.layout();
+        let mut last_timestamp = None;
+        for i in 0..10 {
+            if i % 2 == 0 {
+                clock.advance(1);
+            }
+            let (id, new_tick) = swit.generate_id_tracked().expect("Failed to generate ID");
+            let timestamp = layout.timestamp_of(id);
+            assert_eq!(new_tick, last_timestamp != Some(timestamp), "call {}", i);
+            assert_eq!(new_tick, i % 2 == 0);
+            last_timestamp = Some(timestamp);
+        }
+    }
+
+    #[test]
+    fn test_sequence_position_tracks_generation() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(swit.sequence_remaining(), 255);
+        for issued in 1..=5 {
+            swit.generate_id().expect("Failed to generate ID");
+            assert_eq!(swit.current_sequence(), issued);
+            assert_eq!(swit.sequence_remaining(), 255 - issued);
+        }
+        while swit.generate_id().is_ok() {}
+        assert_eq!(swit.sequence_remaining(), 0);
+
+        // 다음 틱의 첫 ID에서 다시 시작
+        clock.advance(1);
+        swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(swit.current_sequence(), 1);
+    }
+
+    #[test]
+    fn test_eleven_bit_counter_on_default_layout() {
+        let _guard = serial_guard();
+        let layout = Layout {
+            thread_bits: 0,
+            counter_bits: 11,
+            ..Layout::default()
+        };
+        let mut swit = Switflake::builder()
+            .node_id(5)
+            .layout(layout)
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(swit.sequence_remaining(), 2047);
+
+        // 카운터가 8비트에서 감싸지지 않고 시퀀스 필드 전체를 사용
+        let columns = swit
+            .generate_columnar(2047)
+            .expect("Failed to generate IDs");
+        assert_eq!(columns.counters, (0..2047).collect::<Vec<u16>>());
+        assert!(columns
+            .ids
+            .iter()
+            .zip(&columns.counters)
+            .all(|(&id, &counter)| decompose(id, &layout).counter == counter));
+        assert_eq!(swit.current_sequence(), 2047);
+        assert_eq!(swit.sequence_remaining(), 0);
+
+        let top = compose(1, 5, 0, 2047, &layout).expect("Failed to compose ID");
+        assert_eq!(top & 0x7FF, 0x7FF);
+        assert_eq!(decompose(top, &layout).counter, 2047);
+    }
+
+    #[test]
+    fn test_new_preferring_falls_back_when_taken() {
+        let _guard = serial_guard();
+        let swit = Switflake::new_preferring(1, 3).expect("Failed to create Switflake");
+        assert_eq!(swit.lease().thread_id, 3);
+
+        // 3번이 사용 중이면 다른 빈 슬롯을 받음
+        let other = Switflake::new_preferring(1, 3).expect("Failed to create Switflake");
+        assert_ne!(other.lease().thread_id, 3);
+        assert_eq!(other.lease().thread_id, 0);
+        drop(other);
+        drop(swit);
+
+        let beyond = Switflake::new_preferring(1, 200).expect("Failed to create Switflake");
+        assert_eq!(beyond.lease().thread_id, 0);
+    }
+
+    #[test]
+    fn test_poolless_generators_ignore_pool_capacity() {
+        let _guard = serial_guard();
+        let mut generators: Vec<Switflake> = (0..16)
+            .map(|i| Switflake::new_poolless(i, (i % 8) as u8).expect("Failed to create Switflake"))
+            .collect();
+        // 전역 풀은 전혀 사용하지 않음
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+        let mut seen = HashSet::new();
+        for swit in &mut generators {
+            let id = swit.generate_id_blocking().expect("Failed to generate ID");
+            assert_eq!(
+                decompose(id, &swit.layout()).thread_id,
+                swit.lease().thread_id
+            );
+            assert!(seen.insert(id));
+        }
+        drop(generators);
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+
+        assert_eq!(
+            Switflake::new_poolless(1, 8).err(),
+            Some(SwitflakeError::FieldOverflow("thread"))
+        );
+        assert_eq!(
+            Switflake::new_poolless(1 << 12, 0).err(),
+            Some(SwitflakeError::FieldOverflow("node"))
+        );
+    }
+
+    #[test]
+    fn test_generate_for_tenant_overrides_node_once() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(7).expect("Failed to create Switflake");
+        let layout = swit.layout();
+        let (a, tenant_a) = swit
+            .generate_for_tenant(100)
+            .expect("Failed to generate ID");
+        let (b, tenant_b) = swit
+            .generate_for_tenant(200)
+            .expect("Failed to generate ID");
+        assert_eq!((tenant_a, tenant_b), (100, 200));
+        assert_eq!(decompose(a, &layout).node_id, 100);
+        assert_eq!(decompose(b, &layout).node_id, 200);
+
+        // 설정된 노드 ID는 그대로
+        let own = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(decompose(own, &layout).node_id, 7);
+        assert_eq!(
+            swit.generate_for_tenant(1 << 12),
+            Err(SwitflakeError::FieldOverflow("node"))
+        );
+    }
+
+    #[test]
+    fn test_generate_kind_tags_ids() {
+        let _guard = serial_guard();
+        let layout = Layout {
+            kinded: true,
+            ..Layout::default()
+        };
+        let mut swit = Switflake::builder()
+            .node_id(9)
+            .layout(layout)
+            .build()
+            .expect("Failed to create Switflake");
+
+        let mut ids = HashSet::new();
+        let mut last = 0;
+        for _ in 0..200 {
+            let id = swit.generate_kind(42).expect("Failed to generate ID");
+            assert_eq!(id_kind(id, &layout), 42);
+            assert_eq!(decompose(id, &layout).node_id, 9);
+            assert!(id > last, "IDs not monotonic");
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+            last = id;
+        }
+        // 일반 ID는 kind 0
+        let plain = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(id_kind(plain, &layout), 0);
+
+        // 노드 ID는 남은 4비트까지만
+        let wide = Switflake::builder().node_id(16).layout(layout).build();
+        assert_eq!(wide.err(), Some(SwitflakeError::FieldOverflow("node")));
+        let mut unkinded = Switflake::new(9).expect("Failed to create Switflake");
+        assert_eq!(
+            unkinded.generate_kind(42),
+            Err(SwitflakeError::InvalidLayout("kind needs a kinded layout"))
+        );
+    }
+
+    #[test]
+    fn test_generated_ids_round_trip_across_layouts() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let kinded = Layout {
+            kinded: true,
+            ..Layout::default()
+        };
+        let narrow = Layout {
+            timestamp_bits: 40,
+            ..Layout::default()
+        };
+        let versioned = Layout {
+            timestamp_shift: 0,
+            node_shift: 41,
+            sequence_shift: 53,
+            versioned: true,
+            ..Layout::default()
+        };
+        let builders = [
+            Switflake::builder().node_id(4095),
+            Switflake::builder().node_id(7).obfuscation_key(0xA5A5_5A5A),
+            Switflake::builder().node_id(7).reverse_order(true),
+            Switflake::builder()
+                .node_id(7)
+                .layout(narrow)
+                .epoch(1_600_000_000_000)
+                .namespace_bit(true),
+            Switflake::builder().node_id(7).adaptive_counter(4),
+            Switflake::builder().node_id(3).layout(kinded),
+            Switflake::builder().node_id(3).layout(versioned).version(5),
+        ];
+        // generate 안의 debug_assert가 매 ID마다 왕복을 검사
+        for builder in builders {
+            let mut swit = builder
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake");
+            for _ in 0..2_000 {
+                if swit.generate_id() == Err(SwitflakeError::SequenceExhausted) {
+                    clock.advance(1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rate_breaker_trips_and_recovers() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .max_rate_per_second(500)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        for batch in [200, 200, 100] {
+            for _ in 0..batch {
+                swit.generate_id().expect("Failed to generate ID");
+            }
+            clock.advance(1);
+        }
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::RateExceeded));
+        clock.advance(996);
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::RateExceeded));
+
+        // 첫 묶음이 1초 창 밖으로 밀려나면 다시 200개까지 허용
+        clock.advance(1);
+        for _ in 0..200 {
+            swit.generate_id().expect("Failed to generate ID");
+        }
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::RateExceeded));
+    }
+
+    #[test]
+    fn test_ephemeral_processes_get_distinct_nodes() {
+        let _guard = serial_guard();
+        let salt = 0x5EED_1234_ABCD_0042;
+        let mut first = Switflake::ephemeral_for(4_100, salt).expect("Failed to create Switflake");
+        let mut second = Switflake::ephemeral_for(4_101, salt).expect("Failed to create Switflake");
+        assert_ne!(first.node_id, second.node_id);
+        assert!(first.node_id <= DEFAULT_LAYOUT.node_mask());
+
+        let mut ids = HashSet::new();
+        for _ in 0..1_000 {
+            for swit in [&mut first, &mut second] {
+                if let Ok(id) = swit.generate_id() {
+                    assert!(ids.insert(id), "Duplicate ID found: {}", id);
+                }
+            }
+        }
+        drop((first, second));
+
+        // 같은 프로세스 안에서는 노드 ID가 같음
+        let a = Switflake::ephemeral().expect("Failed to create Switflake");
+        let b = Switflake::ephemeral().expect("Failed to create Switflake");
+        assert_eq!(a.node_id, b.node_id);
+        assert_ne!(a.thread_id, b.thread_id);
+    }
+
+    #[test]
+    fn test_generate_id_catch_turns_panic_into_error() {
+        // 설정된 뒤에만 패닉하는 시계
+        #[derive(Clone)]
+        struct FaultyClock(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+        impl Clock for FaultyClock {
+            fn now_millis(&self) -> Result<u64, SwitflakeError> {
+                if self.0.load(std::sync::atomic::Ordering::SeqCst) {
+                    panic!("clock failed");
+                }
+                SystemClock.now_millis()
+            }
+        }
+
+        let _guard = serial_guard();
+        let faulty = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let mut swit = Switflake::builder()
+            .clock(FaultyClock(faulty.clone()))
+            .build()
+            .expect("Failed to create Switflake");
+        assert!(swit.generate_id_catch().is_ok());
+        faulty.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(swit.generate_id_catch(), Err(SwitflakeError::Internal));
+    }
+
+    #[test]
+    fn test_generate_nonzero_skips_zero() {
+        let _guard = serial_guard();
+        let start = 1_700_000_000_000;
+        let clock = MockClock::at(start);
+        // 에포크 첫 틱, 노드 0, 스레드 0이면 첫 ID가 0
+        let mut plain = Switflake::builder()
+            .node_id(0)
+            .epoch(start)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(plain.generate_id(), Ok(0));
+        drop(plain);
+
+        let mut swit = Switflake::builder()
+            .node_id(0)
+            .epoch(start)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let first = swit.generate_nonzero().expect("Failed to generate ID");
+        assert_eq!(decompose(first.get(), &swit.layout()).counter, 1);
+        for i in 0..5_000 {
+            if i % 200 == 0 {
+                clock.advance(1);
+            }
+            swit.generate_nonzero().expect("Failed to generate ID");
+        }
+    }
+
+    #[test]
+    fn test_generate_retry_waits_out_exhaustion() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        while swit.generate_id().is_ok() {}
+
+        // 한 번만 시도하면 소진 오류 그대로 반환
+        assert_eq!(
+            swit.generate_retry(1, Duration::from_millis(1)),
+            Err(SwitflakeError::SequenceExhausted)
+        );
+        assert_eq!(clock.sleeps(), 0);
+
+        // 목 시계는 잠들면 앞으로 가므로 두 번째 시도에서 성공
+        let id = swit
+            .generate_retry(3, Duration::from_millis(1))
+            .expect("Failed to generate ID");
+        assert_eq!(clock.sleeps(), 1);
+        assert_eq!(swit.layout().timestamp_of(id), 1_700_000_000_001);
+
+        clock.set(1_699_999_999_000);
+        assert_eq!(
+            swit.generate_retry(3, Duration::from_millis(1)),
+            Err(SwitflakeError::ClockMovedBackwards)
+        );
+        assert_eq!(clock.sleeps(), 1);
+    }
+
+    #[test]
+    fn test_future_skew_guard() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .max_future_skew(Duration::from_secs(1))
+            .build()
+            .expect("Failed to create Switflake");
+        swit.generate_id().expect("Failed to generate ID");
+
+        // 허용 범위 안의 점프는 그대로 받아들임
+        clock.advance(500);
+        let id = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(swit.layout().timestamp_of(id), 1_700_000_000_500);
+
+        clock.advance(60_000);
+        match swit.generate_id() {
+            Err(SwitflakeError::ClockJumpedForward { delta }) => {
+                assert!(delta > Duration::from_secs(1) && delta <= Duration::from_secs(60));
+            }
+            other => panic!("expected ClockJumpedForward, got {:?}", other),
+        }
+
+        // 시계가 돌아오면 이어서 생성
+        clock.set(1_700_000_000_501);
+        let id = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(swit.layout().timestamp_of(id), 1_700_000_000_501);
+    }
+
+    #[test]
+    fn test_generate_available_stops_at_exhaustion() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::builder()
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to create Switflake");
+        let capacity = swit.layout().counter_mask() as usize;
+        for _ in 0..capacity - 7 {
+            swit.generate_id().expect("Failed to generate ID");
+        }
+
+        // 남은 7개만 받고 기다리지 않음
+        let ids = swit
+            .generate_available(100)
+            .expect("Failed to generate IDs");
+        assert_eq!(ids.len(), 7);
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 7);
+        assert!(swit.generate_available(100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_generated_count_and_first_time() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+        assert_eq!(swit.generated_count(), 0);
+        assert_eq!(swit.first_generated_at(), None);
+        for _ in 0..50 {
+            swit.generate_id_blocking().expect("Failed to generate ID");
+        }
+        assert_eq!(swit.generated_count(), 50);
+        let first = swit
+            .first_generated_at()
+            .expect("first ID time not recorded");
+        let age = SystemTime::now()
+            .duration_since(first)
+            .expect("first ID time is in the future");
+        assert!(age < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_max_throughput_per_second() {
+        let _guard = serial_guard();
+        let swit = Switflake::new(1).expect("Failed to create Switflake");
+        assert_eq!(swit.max_throughput_per_second(), 256_000);
+        drop(swit);
+
+        let micros = Switflake::builder()
+            .time_unit(TimeUnit::Microseconds)
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(micros.max_throughput_per_second(), 256_000_000);
+        drop(micros);
+
+        // 빌려 쓰는 노드 비트만큼 늘어남
+        let adaptive = Switflake::builder()
+            .adaptive_counter(2)
+            .build()
+            .expect("Failed to create Switflake");
+        assert_eq!(adaptive.max_throughput_per_second(), 1_024_000);
+    }
+
+    #[test]
+    fn test_cached_prefix_matches_full_pack() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        for reverse in [false, true] {
+            let mut swit = Switflake::builder()
+                .node_id(0x15)
+                .reverse_order(reverse)
+                .adaptive_counter(2)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake");
+            let layout = swit.layout();
+            let mut rollovers = 0;
+            for i in 0..2_000 {
+                match swit.generate_decomposed() {
+                    // 접두부 캐시 결과가 필드별 조립과 비트 단위로 같아야 함
+                    Ok((id, parts)) => assert_eq!(
+                        id,
+                        layout.pack(
+                            parts.timestamp,
+                            parts.node_id,
+                            parts.thread_id,
+                            parts.counter
+                        )
+                    ),
+                    Err(SwitflakeError::SequenceExhausted) => {
+                        clock.advance(1);
+                        rollovers += 1;
+                    }
+                    Err(e) => panic!("Failed to generate ID: {}", e),
+                }
+                if i % 1_500 == 1_499 {
+                    clock.advance(1);
+                }
+            }
+            assert!(rollovers > 0);
+        }
+    }
+
+    #[test]
+    fn test_unchecked_matches_checked_within_preconditions() {
+        let _guard = serial_guard();
+        let run = |unchecked: bool| {
+            let clock = MockClock::at(1_700_000_000_000);
+            let mut swit = Switflake::builder()
+                .node_id(11)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake");
+            let mut ids = Vec::new();
+            for _ in 0..5 {
+                for _ in 0..200 {
+                    ids.push(if unchecked {
+                        // SAFETY: 200 < 255 IDs per tick and the mock clock only advances
+                        unsafe { swit.generate_id_unchecked() }
+                    } else {
+                        swit.generate_id().expect("Failed to generate ID")
+                    });
+                }
+                clock.advance(1);
+            }
+            ids
+        };
+        // 두 실행 모두 같은 슬롯을 받으므로 ID가 완전히 같아야 함
+        assert_eq!(run(true), run(false));
+    }
+
+    #[test]
+    fn test_adaptive_counter_borrows_spare_node_bits() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(5)
+            .adaptive_counter(2)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+
+        // 시계가 한 밀리초에 멈춰 있어도 기본 용량(255)보다 많이 생성됨
+        let mut ids = Vec::new();
+        while let Ok(id) = swit.generate_id() {
+            ids.push(id);
+        }
+        assert_eq!(ids.len(), 255 * 4);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        for id in &ids {
+            let parts = decompose(*id, &swit.layout());
+            // 빌린 비트 아래의 실제 노드 id는 그대로
+            assert_eq!(parts.node_id & 0x3FF, 5);
+            assert_eq!(parts.timestamp, 1_700_000_000_000);
+        }
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::SequenceExhausted));
+
+        clock.advance(1);
+        let (_, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert_eq!((parts.node_id, parts.counter), (5, 0));
+
+        // 빌릴 비트를 이미 쓰는 노드 id는 거부
+        let result = Switflake::builder()
+            .node_id(0x400)
+            .adaptive_counter(2)
+            .build();
+        assert_eq!(result.err(), Some(SwitflakeError::FieldOverflow("node")));
+    }
+
+    #[test]
+    fn test_resume_from_max_id_continues_after_it() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let max_id = {
+            let mut swit = Switflake::builder()
+                .node_id(3)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake");
+            (0..10)
+                .map(|_| swit.generate_id().expect("Failed to generate ID"))
+                .last()
+                .unwrap()
+        };
+
+        // 같은 밀리초 안에서 재시작해도 max_id 다음부터 이어짐
+        let mut swit = Switflake::resume_from_max_id(3, max_id, clock.clone())
+            .expect("Failed to resume Switflake");
+        let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert!(id > max_id);
+        assert_eq!(parts.counter, decompose(max_id, &swit.layout()).counter + 1);
+        assert_eq!(parts.timestamp, 1_700_000_000_000);
+        drop(swit);
+
+        let full = Layout::default().pack(1_700_000_000_000, 3, 0, 255);
+        assert!(matches!(
+            Switflake::resume_from_max_id(3, full, clock.clone()),
+            Err(SwitflakeError::SequenceExhausted)
+        ));
+
+        // 이전 밀리초의 max_id는 영향 없음
+        clock.advance(1);
+        let mut swit =
+            Switflake::resume_from_max_id(3, max_id, clock).expect("Failed to resume Switflake");
+        let (_, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+        assert_eq!(parts.counter, 0);
+    }
+
+    #[test]
+    fn test_resume_from_max_id_keeps_later_cooldown() {
+        struct CooldownOff;
+        impl Drop for CooldownOff {
+            fn drop(&mut self) {
+                ThreadIdPool::global().set_reuse_cooldown(false);
+            }
+        }
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+        pool.set_reuse_cooldown(true);
+        let _reset = CooldownOff;
+        // 다른 테스트가 남긴 쿨다운보다 뒤의 시각
+        let clock = MockClock::at(2_100_000_000_000);
+        let others: Vec<SlotLease> = (0..7).map(|_| pool.acquire().unwrap()).collect();
+
+        let mut first = Switflake::builder()
+            .node_id(3)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let issued: Vec<u64> = (0..10).map(|_| first.generate_id().unwrap()).collect();
+        let slot = first.thread_id;
+        drop(first);
+        // 저장된 최대 ID는 더 오래됐지만 쿨다운 중인 슬롯을 다시 받음
+        let max_id = Layout::default().pack(2_099_999_999_999, 3, slot, 5);
+        let mut swit = Switflake::resume_from_max_id(3, max_id, clock.clone())
+            .expect("Failed to resume Switflake");
+        assert_eq!(swit.thread_id, slot);
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::SequenceExhausted));
+        clock.advance(1);
+        assert!(swit.generate_id().unwrap() > issued[9]);
+        drop(swit);
+        for lease in others {
+            assert!(pool.force_release(lease));
+        }
+    }
+
+    #[test]
+    fn test_zero_thread_bits_takes_no_pool_slot() {
+        let _guard = serial_guard();
+        let layout = Layout {
+            node_bits: 15,
+            node_shift: 8,
+            thread_bits: 0,
+            counter_bits: 8,
+            ..Layout::default()
+        };
+        let mut swit = Switflake::builder()
+            .node_id(21)
+            .layout(layout)
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to create Switflake");
+        let pool = ThreadIdPool::global();
+        assert!((0..8).all(|i| !pool.is_held(i)));
+        assert_eq!(swit.config().pool_capacity, 1);
+
+        // 스레드 필드 없이 시퀀스 전체가 카운터
+        let mut ids = Vec::new();
+        while let Ok(id) = swit.generate_id() {
+            let parts = decompose(id, &layout);
+            assert_eq!((parts.node_id, parts.thread_id), (21, 0));
+            assert_eq!(id & 0xFF, parts.counter as u64);
+            ids.push(id);
+        }
+        assert_eq!(ids.len(), 255);
+
+        // 풀이 가득 차도 영향 없음
+        let held: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        let other = Switflake::builder().node_id(22).layout(layout).build();
+        assert!(other.is_ok());
+        drop(other);
+        drop(swit);
+        assert!(pool.is_full());
+
+        // 기본 레이아웃에서도 스레드 비트 3개가 카운터로 넘어감
+        let wide = Layout {
+            thread_bits: 0,
+            counter_bits: 11,
+            ..Layout::default()
+        };
+        let mut swit = Switflake::builder()
+            .node_id(23)
+            .layout(wide)
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Thread-less generator should not need a pool slot");
+        let mut max_counter = 0;
+        while let Ok(id) = swit.generate_id() {
+            let parts = decompose(id, &wide);
+            assert_eq!((parts.node_id, parts.thread_id), (23, 0));
+            max_counter = max_counter.max(parts.counter);
+        }
+        assert!(max_counter > 255, "counter stopped at {}", max_counter);
+        assert_eq!(max_counter, 2046);
+        drop(swit);
+        drop(held);
+    }
+
+    #[test]
+    fn test_fixed_time_is_reproducible() {
+        let _guard = serial_guard();
+        let run = || {
+            let mut swit = Switflake::with_fixed_time(9, 1_700_000_000_000)
+                .expect("Failed to create Switflake");
+            let mut ids = Vec::new();
+            loop {
+                match swit.generate_id() {
+                    Ok(id) => ids.push(id),
+                    Err(e) => {
+                        // 시계가 멈춰 있으므로 카운터가 다 차면 계속 실패
+                        assert_eq!(e, SwitflakeError::SequenceExhausted);
+                        assert_eq!(swit.generate_id(), Err(e));
+                        return ids;
+                    }
+                }
+            }
+        };
+        let first = run();
+        assert_eq!(first.len(), 255);
+        assert_eq!(first, run());
+        assert_eq!(first[0] >> 23, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_timestamp_and_age() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let id = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(
+            swit.timestamp(id),
+            UNIX_EPOCH + Duration::from_millis(1_700_000_000_000)
+        );
+        clock.advance(2_100);
+        assert_eq!(swit.age(id), Ok(Duration::from_millis(2_100)));
+    }
+
+    #[test]
+    fn test_clock_moved_backwards() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        swit.generate_id().expect("Failed to generate ID");
+        clock.set(1_699_999_999_999);
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::ClockMovedBackwards));
     }
 
     #[test]
     fn test_multi_thread_unique_ids() {
+        let _guard = serial_guard();
         let mut handles = Vec::new();
         let mut all_ids = HashSet::new();
 
-        for _ in 0..8 {
-            let mut swit = Switflake::new(1).expect("Failed to create Switflake");
+        // 먼저 끝난 스레드의 슬롯이 같은 밀리초에 재사용되지 않도록 모두 먼저 생성
+        let swits: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        for mut swit in swits {
             handles.push(thread::spawn(move || {
                 let mut ids = Vec::new();
                 for _ in 0..64 {
@@ -173,4 +2026,15 @@ mod tests {
             "Not all IDs were unique across threads"
         );
     }
+
+    #[test]
+    fn test_generate_decomposed_matches_decompose() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(42).expect("Failed to create Switflake");
+        for _ in 0..10 {
+            let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+            assert_eq!(parts, decompose(id, &Layout::default()));
+            assert_eq!(parts.node_id, 42);
+        }
+    }
 }
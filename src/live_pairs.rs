@@ -0,0 +1,60 @@
+//! Process-wide check that no two live generators outside a pool share a
+//! node id and thread id, as when two were given the same fixed thread id.
+//! Pooled generators are left out: a pool already keeps its thread ids
+//! apart, and a slot reclaimed from a forgotten generator is meant to be
+//! reused. Only compiled with `paranoid`.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+// Live generators per pair. A count rather than a set, so the generator
+// that trips the check releases only its own claim when it is dropped.
+fn live() -> std::sync::MutexGuard<'static, HashMap<(u64, u8), usize>> {
+    static LIVE: OnceLock<Mutex<HashMap<(u64, u8), usize>>> = OnceLock::new();
+    LIVE.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Records the pair for a generator being built, and panics if a live
+/// generator already holds it, since the two would mint the same IDs.
+pub(crate) fn claim(node_id: u64, thread_id: u8) {
+    let holders = {
+        let mut live = live();
+        let holders = live.entry((node_id, thread_id)).or_default();
+        *holders += 1;
+        *holders
+    };
+    assert!(
+        holders == 1,
+        "node id {} and thread id {} are already used by a live generator",
+        node_id,
+        thread_id
+    );
+}
+
+pub(crate) fn release(node_id: u64, thread_id: u8) {
+    let mut live = live();
+    if let Some(holders) = live.get_mut(&(node_id, thread_id)) {
+        *holders -= 1;
+        if *holders == 0 {
+            live.remove(&(node_id, thread_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[test]
+    #[should_panic(expected = "are already used by a live generator")]
+    fn test_duplicate_fixed_thread_id_trips_check() {
+        let _guard = serial_guard();
+        let first = Switflake::new_poolless(901, 5).expect("Failed to create Switflake");
+        // 같은 (노드, 스레드) 쌍은 같은 ID를 만들 수 있음
+        let _second = Switflake::new_poolless(901, 5);
+        drop(first);
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{decompose, DecomposedId, Layout};
+
+/// A file of little-endian `u64` IDs, memory-mapped rather than read into
+/// memory, so tools can scan billions of IDs lazily.
+///
+/// Nothing is copied up front: each access reads its eight bytes from the
+/// mapping. The file must not be truncated or rewritten while mapped.
+#[derive(Debug)]
+pub struct MappedIds {
+    map: Mmap,
+    layout: Layout,
+}
+
+impl MappedIds {
+    /// Maps the file at `path`, decomposing with the default layout. Fails
+    /// with [`io::ErrorKind::InvalidData`] if the file's length isn't a
+    /// multiple of 8.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only; see the type's note on
+        // concurrent modification.
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() % 8 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file length is not a multiple of 8 bytes",
+            ));
+        }
+        Ok(MappedIds {
+            map,
+            layout: Layout::default(),
+        })
+    }
+
+    /// Sets the layout [`decompose_at`](Self::decompose_at) decodes with.
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len() / 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<u64> {
+        let bytes = self.map.get(index * 8..index * 8 + 8)?;
+        Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// The fields of the ID at `index`, or `None` past the end.
+    pub fn decompose_at(&self, index: usize) -> Option<DecomposedId> {
+        self.get(index).map(|id| decompose(id, &self.layout))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.map
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("switflake-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_mapped_ids_match_originals() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(77).expect("Failed to create Switflake");
+        let ids: Vec<u64> = (0..300)
+            .map(|_| swit.generate_id_blocking().expect("Failed to generate ID"))
+            .collect();
+        let path = temp_path("ids.bin");
+        let bytes: Vec<u8> = ids.iter().flat_map(|id| id.to_le_bytes()).collect();
+        std::fs::write(&path, bytes).unwrap();
+
+        let mapped = MappedIds::open(&path).expect("Failed to map file");
+        assert_eq!(mapped.len(), ids.len());
+        for (index, &id) in ids.iter().enumerate() {
+            assert_eq!(mapped.get(index), Some(id));
+            assert_eq!(
+                mapped.decompose_at(index),
+                Some(decompose(id, &Layout::default()))
+            );
+        }
+        assert_eq!(mapped.decompose_at(ids.len()), None);
+        assert!(mapped.iter().eq(ids.iter().copied()));
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_partial_trailing_id_rejected() {
+        let path = temp_path("partial.bin");
+        std::fs::write(&path, [0u8; 12]).unwrap();
+        let error = MappedIds::open(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
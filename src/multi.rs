@@ -0,0 +1,369 @@
+use crate::layout::Counter;
+use crate::{
+    Clock, SlotLease, Switflake, SwitflakeBuilder, SwitflakeError, SystemClock, ThreadIdPool,
+};
+
+/// One owner holding several pool slots and cycling a single generator's
+/// thread-id field through them, for single-threaded producers that need
+/// more than one generator's per-millisecond capacity.
+///
+/// The generator keeps a counter per slot, so capacity grows with the
+/// number of slots held, while there is only one clock, one set of
+/// settings and one registered generator. IDs are unique but, because
+/// consecutive calls use different thread ids, not increasing from one
+/// call to the next within a millisecond. All slots are released on drop.
+pub struct MultiSlotGenerator<C = SystemClock> {
+    // Generates under `slots[current]`, whose thread id and counter are
+    // swapped in; the other entries hold where each slot's counter stands.
+    swit: Switflake<C>,
+    slots: Vec<SlotState>,
+    current: usize,
+}
+
+/// A held slot and the generator state last used under it.
+#[derive(Debug, Clone, Copy)]
+struct SlotState {
+    lease: SlotLease,
+    last_timestamp: u64,
+    borrowed: u64,
+    local_counter: Counter,
+}
+
+impl MultiSlotGenerator {
+    /// Takes exactly `slots` pool slots, or fails with
+    /// [`SwitflakeError::PoolExhausted`] taking none.
+    #[must_use = "dropping the generator frees its pool slots right away"]
+    pub fn new(node_id: u64, slots: usize) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .build_multi_slot(slots)
+    }
+
+    /// Takes every slot that is free right now, failing only if there is
+    /// none.
+    pub fn with_all_free_slots(node_id: u64) -> Result<Self, SwitflakeError> {
+        let pool = ThreadIdPool::global();
+        let leases: Vec<SlotLease> = std::iter::from_fn(|| pool.try_acquire_lease()).collect();
+        if leases.is_empty() {
+            return Err(SwitflakeError::PoolExhausted);
+        }
+        Ok(SwitflakeBuilder::new()
+            .node_id(node_id)
+            .finish_multi_slot(leases))
+    }
+}
+
+impl<C: Clock> MultiSlotGenerator<C> {
+    /// Generates with `swit` under its own slot and every one of `others`.
+    pub(crate) fn from_slots(mut swit: Switflake<C>, others: Vec<SlotLease>) -> Self {
+        let mut slots = vec![swit.slot_state()];
+        for lease in others {
+            swit.load_slot(SlotState {
+                lease,
+                last_timestamp: 0,
+                borrowed: 0,
+                local_counter: 0,
+            });
+            // 슬롯마다 이전 소유자의 쿨다운과 보호된 범위를 이어받음
+            swit.resume_high_water();
+            swit.sit_out_cooldown();
+            slots.push(swit.slot_state());
+        }
+        swit.load_slot(slots[0]);
+        MultiSlotGenerator {
+            swit,
+            slots,
+            current: 0,
+        }
+    }
+
+    /// Number of pool slots held.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Generates an ID under the next slot in turn, moving on to the
+    /// following slots when one has used up the current millisecond. Fails
+    /// with [`SwitflakeError::SequenceExhausted`] only once every slot has.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id(&mut self) -> Result<u64, SwitflakeError> {
+        for _ in 0..self.slots.len() {
+            let result = self.swit.generate_id();
+            self.advance();
+            match result {
+                Err(SwitflakeError::SequenceExhausted) => continue,
+                result => return result,
+            }
+        }
+        Err(SwitflakeError::SequenceExhausted)
+    }
+
+    /// Saves the current slot's counter and swaps in the next slot's.
+    fn advance(&mut self) {
+        if self.slots.len() == 1 {
+            return;
+        }
+        self.slots[self.current] = self.swit.slot_state();
+        self.current = (self.current + 1) % self.slots.len();
+        self.swit.load_slot(self.slots[self.current]);
+    }
+}
+
+impl<C> Drop for MultiSlotGenerator<C> {
+    fn drop(&mut self) {
+        let Some(pool) = self.swit.pool else {
+            return;
+        };
+        // 현재 슬롯은 생성기 자신이 drop될 때 반환
+        for (i, slot) in self.slots.iter().enumerate() {
+            if i == self.current {
+                continue;
+            }
+            let end_micros = match slot.last_timestamp != 0 || slot.local_counter != 0 {
+                true => {
+                    (self.swit.epoch_ticks() + slot.last_timestamp + 1)
+                        * self.swit.time_unit.as_micros()
+                }
+                false => 0,
+            };
+            pool.release_after(slot.lease, end_micros);
+        }
+    }
+}
+
+impl<C> Switflake<C> {
+    fn slot_state(&self) -> SlotState {
+        SlotState {
+            lease: self.lease(),
+            last_timestamp: self.last_timestamp,
+            borrowed: self.borrowed,
+            local_counter: self.local_counter,
+        }
+    }
+
+    /// Carries on generating under `state`'s slot from where it left off.
+    fn load_slot(&mut self, state: SlotState) {
+        // 빌린 슬롯의 스레드 ID가 우선하므로 먼저 반환
+        if !self.borrowed_slots.is_empty() {
+            self.return_borrowed_slots(self.tick_end_micros());
+        }
+        self.thread_id = state.lease.thread_id;
+        self.generation = state.lease.generation;
+        self.last_timestamp = state.last_timestamp;
+        self.borrowed = state.borrowed;
+        self.local_counter = state.local_counter;
+        self.refresh_prefix();
+    }
+}
+
+/// Several complete generators behind one handle, each call going to the
+/// next in turn, so a single producer gets their combined per-millisecond
+/// capacity from one `next_id` call site.
+///
+/// Unlike [`MultiSlotGenerator`], which cycles one generator through
+/// several slots, the set owns whatever complete generators it is given:
+/// they may use different node ids, pools, layouts or clocks. As there, IDs are unique but not
+/// increasing from one call to the next within a millisecond.
+pub struct GeneratorSet<C = SystemClock> {
+    generators: Vec<Switflake<C>>,
+    next: usize,
+}
+
+impl GeneratorSet {
+    /// Builds `count` generators for `node_id` from the global pool, up to
+    /// its capacity, or fails with [`SwitflakeError::PoolExhausted`]
+    /// building none.
+    #[must_use = "dropping the set frees its pool slots right away"]
+    pub fn new(node_id: u64, count: usize) -> Result<Self, SwitflakeError> {
+        if count == 0 {
+            return Err(SwitflakeError::InvalidLayout(
+                "a generator set needs at least one generator",
+            ));
+        }
+        let generators = SwitflakeBuilder::new()
+            .node_id(node_id)
+            .build_batch(count)?;
+        Ok(GeneratorSet::from_generators(generators))
+    }
+}
+
+impl<C> GeneratorSet<C> {
+    pub fn from_generators(generators: Vec<Switflake<C>>) -> Self {
+        GeneratorSet {
+            generators,
+            next: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.generators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+
+    /// Gives the generators back, e.g. to drop some of them.
+    pub fn into_generators(self) -> Vec<Switflake<C>> {
+        self.generators
+    }
+}
+
+impl<C: Clock> GeneratorSet<C> {
+    /// Generates an ID from the next generator in turn, moving on when one
+    /// has used up the current tick. Fails with
+    /// [`SwitflakeError::SequenceExhausted`] only once all of them have,
+    /// and with [`SwitflakeError::PoolExhausted`] if the set is empty.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn next_id(&mut self) -> Result<u64, SwitflakeError> {
+        if self.generators.is_empty() {
+            return Err(SwitflakeError::PoolExhausted);
+        }
+        round_robin(&mut self.generators, &mut self.next)
+    }
+}
+
+/// Generates from `generators[*next]`, or the first after it with IDs left
+/// in this tick, and advances `next` past the one used.
+fn round_robin<C: Clock>(
+    generators: &mut [Switflake<C>],
+    next: &mut usize,
+) -> Result<u64, SwitflakeError> {
+    let count = generators.len();
+    for _ in 0..count {
+        let swit = &mut generators[*next];
+        *next = (*next + 1) % count;
+        match swit.generate_id() {
+            Err(SwitflakeError::SequenceExhausted) => continue,
+            result => return result,
+        }
+    }
+    Err(SwitflakeError::SequenceExhausted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{decompose, GeneratorRegistry, Layout};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_2000_ids_in_one_millisecond() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut multi = Switflake::builder()
+            .node_id(3)
+            .clock(clock.clone())
+            .build_multi_slot(8)
+            .expect("Failed to create MultiSlotGenerator");
+        assert_eq!(multi.slot_count(), 8);
+        assert!(ThreadIdPool::global().is_full());
+
+        let mut ids = HashSet::new();
+        for _ in 0..2_000 {
+            let id = multi.generate_id().expect("Failed to generate ID");
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+        // 8 슬롯 x 255개 = 2040개까지 가능
+        for _ in 2_000..2_040 {
+            assert!(multi.generate_id().is_ok());
+        }
+        assert_eq!(multi.generate_id(), Err(SwitflakeError::SequenceExhausted));
+
+        drop(multi);
+        assert!(!ThreadIdPool::global().is_full());
+    }
+
+    #[test]
+    fn test_multi_slot_is_one_generator() {
+        static REGISTRY: GeneratorRegistry = GeneratorRegistry::new();
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut multi = Switflake::builder()
+            .node_id(3)
+            .registry(&REGISTRY)
+            .clock(clock.clone())
+            .build_multi_slot(3)
+            .expect("Failed to create MultiSlotGenerator");
+        assert_eq!(REGISTRY.live(), 1);
+        assert_eq!(ThreadIdPool::global().occupied_slots(), vec![0, 1, 2]);
+
+        // 슬롯을 돌아가며 스레드 필드만 바꾸고 카운터는 슬롯마다 따로 셈
+        let parts: Vec<_> = (0..6)
+            .map(|_| decompose(multi.generate_id().unwrap(), &Layout::default()))
+            .map(|parts| (parts.thread_id, parts.counter))
+            .collect();
+        assert_eq!(parts, vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+        clock.advance(1);
+        let parts = decompose(multi.generate_id().unwrap(), &Layout::default());
+        assert_eq!(
+            (parts.timestamp, parts.thread_id, parts.counter),
+            (1_700_000_000_001, 0, 0)
+        );
+
+        drop(multi);
+        assert_eq!(REGISTRY.live(), 0);
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+    }
+
+    #[test]
+    fn test_zero_slots_is_invalid() {
+        let _guard = serial_guard();
+        assert!(matches!(
+            Switflake::builder().build_multi_slot(0),
+            Err(SwitflakeError::InvalidLayout(_))
+        ));
+        assert!(matches!(
+            GeneratorSet::new(1, 0),
+            Err(SwitflakeError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_generator_set_exceeds_single_capacity() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        // 서로 다른 노드의 생성기도 함께 담을 수 있음
+        let generators = (0..4)
+            .map(|node| {
+                Switflake::builder()
+                    .node_id(node)
+                    .clock(clock.clone())
+                    .build()
+                    .expect("Failed to create Switflake")
+            })
+            .collect();
+        let mut set = GeneratorSet::from_generators(generators);
+        assert_eq!(set.len(), 4);
+
+        let mut ids = HashSet::new();
+        for _ in 0..1_000 {
+            let id = set.next_id().expect("Failed to generate ID");
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+        }
+        // 4 x 255개를 넘으면 다음 밀리초까지 소진
+        for _ in 1_000..1_020 {
+            assert!(set.next_id().is_ok());
+        }
+        assert_eq!(set.next_id(), Err(SwitflakeError::SequenceExhausted));
+        clock.advance(1);
+        assert!(set.next_id().is_ok());
+
+        assert_eq!(set.into_generators().len(), 4);
+        assert_eq!(
+            GeneratorSet::<MockClock>::from_generators(Vec::new()).next_id(),
+            Err(SwitflakeError::PoolExhausted)
+        );
+    }
+
+    #[test]
+    fn test_all_free_slots() {
+        let _guard = serial_guard();
+        let _held = Switflake::new(1).expect("Failed to create Switflake");
+        let multi = MultiSlotGenerator::with_all_free_slots(1).expect("Free slots");
+        assert_eq!(multi.slot_count(), 7);
+        assert!(MultiSlotGenerator::with_all_free_slots(1).is_err());
+    }
+}
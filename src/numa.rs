@@ -0,0 +1,213 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::layout::mask;
+use crate::pool::SlotPool;
+use crate::{SlotBitmap, SlotLease, Slots, SwitflakeError, ThreadIdPool};
+
+/// Tells a [`NumaThreadIdPool`] which NUMA node the calling thread runs
+/// on, e.g. through `getcpu(2)` on Linux.
+pub trait NumaDetector: Sync {
+    /// Node of the calling thread, numbered from 0. Values past the pool's
+    /// node count wrap around.
+    fn current_node(&self) -> usize;
+}
+
+/// A [`ThreadIdPool`] whose slots are split into one contiguous partition
+/// per NUMA node, so generators of threads on the same node take
+/// neighbouring thread ids and the per-slot state they touch stays within
+/// that node's cache lines.
+///
+/// Acquiring takes the lowest free slot in the calling thread's partition
+/// and only falls back to other partitions once it is full; blocking and
+/// batch acquisition ignore partitions. The free-slot bitmask itself is
+/// still one word shared by all nodes, but it is only touched when a
+/// generator is created or dropped. Hand it to
+/// [`SwitflakeBuilder::numa_pool`](crate::SwitflakeBuilder::numa_pool):
+///
+/// ```
+/// use switflake::{NumaDetector, NumaThreadIdPool, Switflake};
+///
+/// struct SingleNode;
+///
+/// impl NumaDetector for SingleNode {
+///     fn current_node(&self) -> usize {
+///         0
+///     }
+/// }
+///
+/// static POOL: NumaThreadIdPool<SingleNode> = NumaThreadIdPool::new(2, SingleNode);
+/// let swit = Switflake::builder().numa_pool(&POOL).build().unwrap();
+/// assert!(POOL.partition(0).contains(&swit.lease().thread_id));
+/// ```
+pub struct NumaThreadIdPool<D, const N: usize = 8>
+where
+    Slots<N>: SlotBitmap,
+{
+    slots: ThreadIdPool<N>,
+    nodes: usize,
+    detector: D,
+}
+
+impl<D: NumaDetector, const N: usize> NumaThreadIdPool<D, N>
+where
+    Slots<N>: SlotBitmap,
+{
+    /// # Panics
+    ///
+    /// Panics if `nodes` is 0 or more than the `N` slots.
+    #[cfg(not(loom))]
+    pub const fn new(nodes: usize, detector: D) -> Self {
+        assert!(nodes >= 1 && nodes <= N, "need 1 to N NUMA nodes");
+        NumaThreadIdPool {
+            slots: ThreadIdPool::new(),
+            nodes,
+            detector,
+        }
+    }
+
+    #[cfg(loom)]
+    pub fn new(nodes: usize, detector: D) -> Self {
+        assert!(nodes >= 1 && nodes <= N, "need 1 to N NUMA nodes");
+        NumaThreadIdPool {
+            slots: ThreadIdPool::new(),
+            nodes,
+            detector,
+        }
+    }
+
+    /// Thread ids that generators on `node` take first: `N / nodes` slots,
+    /// give or take one.
+    pub fn partition(&self, node: usize) -> Range<u8> {
+        let node = node % self.nodes;
+        let start = node * N / self.nodes;
+        let end = (node + 1) * N / self.nodes;
+        start as u8..end as u8
+    }
+
+    /// The underlying pool, e.g. for reclaiming slots.
+    pub fn slots(&self) -> &ThreadIdPool<N> {
+        &self.slots
+    }
+
+    fn local_partition(&self) -> u64 {
+        let partition = self.partition(self.detector.current_node());
+        mask(partition.len() as u32) << partition.start
+    }
+}
+
+impl<D: NumaDetector, const N: usize> SlotPool for NumaThreadIdPool<D, N>
+where
+    Slots<N>: SlotBitmap,
+{
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn thread_bits(&self) -> u32 {
+        ThreadIdPool::<N>::THREAD_BITS
+    }
+
+    fn is_full(&self) -> bool {
+        self.slots.is_full()
+    }
+
+    fn acquire_preferring(
+        &self,
+        keep_free: u32,
+        preferred: u64,
+    ) -> Result<SlotLease, SwitflakeError> {
+        // 명시된 선호 안에서 로컬 파티션을 먼저 시도
+        let local = preferred & self.local_partition();
+        let preferred = if local != 0 { local } else { preferred };
+        self.slots.acquire_preferring(keep_free, preferred)
+    }
+
+    fn acquire_blocking(&self, timeout: Option<Duration>) -> Result<SlotLease, SwitflakeError> {
+        match self.slots.acquire_preferring(0, self.local_partition()) {
+            Err(SwitflakeError::PoolExhausted) => self.slots.acquire_blocking(timeout),
+            result => result,
+        }
+    }
+
+    fn try_acquire_lease(&self) -> Option<SlotLease> {
+        self.slots.try_acquire_preferring(self.local_partition())
+    }
+
+    fn acquire_many(&self, count: usize) -> Result<Vec<SlotLease>, SwitflakeError> {
+        self.slots.acquire_many(count)
+    }
+
+    fn generation(&self, id: u8) -> u32 {
+        self.slots.generation(id)
+    }
+
+    fn release_after(&self, lease: SlotLease, end_micros: u64) -> bool {
+        self.slots.release_after(lease, end_micros)
+    }
+
+    fn cooling_until(&self, id: u8) -> u64 {
+        self.slots.cooling_until(id)
+    }
+
+    #[cfg(feature = "async")]
+    fn poll_acquire<'a>(
+        &'a self,
+        cx: &mut std::task::Context<'_>,
+        waiter: &mut Option<crate::pool::Waiter<'a>>,
+    ) -> std::task::Poll<Result<SlotLease, SwitflakeError>> {
+        self.slots.poll_acquire(cx, waiter)
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use crate::Switflake;
+    use std::cell::Cell;
+    use std::thread;
+
+    thread_local! {
+        static NODE: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // 스레드마다 지정한 노드를 보고하는 가짜 감지기
+    struct FakeDetector;
+
+    impl NumaDetector for FakeDetector {
+        fn current_node(&self) -> usize {
+            NODE.with(Cell::get)
+        }
+    }
+
+    static POOL: NumaThreadIdPool<FakeDetector> = NumaThreadIdPool::new(2, FakeDetector);
+
+    fn build_on(node: usize) -> Switflake {
+        NODE.with(|n| n.set(node));
+        Switflake::builder()
+            .numa_pool(&POOL)
+            .build()
+            .expect("Failed to create Switflake")
+    }
+
+    #[test]
+    fn test_threads_take_slots_from_their_node() {
+        assert_eq!((POOL.partition(0), POOL.partition(1)), (0..4, 4..8));
+
+        let on_node1 = thread::spawn(|| build_on(1).lease().thread_id)
+            .join()
+            .expect("Thread join failed");
+        assert!(POOL.partition(1).contains(&on_node1));
+
+        let node0: Vec<Switflake> = (0..4).map(|_| build_on(0)).collect();
+        for swit in &node0 {
+            assert!(POOL.partition(0).contains(&swit.lease().thread_id));
+        }
+        // 자기 파티션이 가득 차면 다른 노드의 슬롯을 씀
+        let overflow = build_on(0);
+        assert!(POOL.partition(1).contains(&overflow.lease().thread_id));
+        let node1 = build_on(1);
+        assert!(POOL.partition(1).contains(&node1.lease().thread_id));
+        assert_eq!(POOL.partition(5), POOL.partition(1));
+    }
+}
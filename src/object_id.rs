@@ -0,0 +1,118 @@
+use crate::{telemetry, Clock, SlotLease, SwitflakeError, SystemClock, ThreadIdPool};
+
+const COUNTER_MASK: u32 = (1 << 24) - 1;
+
+/// 96-bit IDs laid out like MongoDB ObjectIds, so their bytes sort the
+/// same way: a big-endian 4-byte seconds Unix timestamp, a 5-byte machine
+/// field (the 4-byte node id, then the thread id from the shared
+/// [`ThreadIdPool`]) and a 3-byte counter that restarts every second.
+///
+/// Compared byte-wise, IDs from one generator sort in the order they were
+/// made and IDs from any generator sort by the second they were made in.
+/// A generator makes at most 2^24 IDs a second.
+pub struct ObjectIdGenerator<C = SystemClock> {
+    node_id: u32,
+    thread_id: u8,
+    generation: u32,
+    clock: C,
+    last_second: u64,
+    counter: u32,
+}
+
+impl ObjectIdGenerator {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new(node_id: u32) -> Result<Self, SwitflakeError> {
+        ObjectIdGenerator::with_clock(node_id, SystemClock)
+    }
+}
+
+impl<C: Clock> ObjectIdGenerator<C> {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn with_clock(node_id: u32, clock: C) -> Result<Self, SwitflakeError> {
+        let lease = ThreadIdPool::global().acquire()?;
+        Ok(ObjectIdGenerator {
+            node_id,
+            thread_id: lease.thread_id,
+            generation: lease.generation,
+            clock,
+            last_second: 0,
+            counter: 0,
+        })
+    }
+
+    /// Generates the next ID, failing with
+    /// [`SwitflakeError::TimestampOverflow`] after 2106, when seconds no
+    /// longer fit in 4 bytes.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id(&mut self) -> Result<[u8; 12], SwitflakeError> {
+        let second = self.clock.now_millis()? / 1_000;
+        if second > u32::MAX as u64 {
+            return Err(SwitflakeError::TimestampOverflow);
+        }
+        if second < self.last_second {
+            return Err(SwitflakeError::ClockMovedBackwards);
+        }
+        if second > self.last_second {
+            self.last_second = second;
+            self.counter = 0;
+        } else if self.counter > COUNTER_MASK {
+            telemetry::sequence_exhausted();
+            return Err(SwitflakeError::SequenceExhausted);
+        }
+
+        let mut id = [0u8; 12];
+        id[..4].copy_from_slice(&(second as u32).to_be_bytes());
+        id[4..8].copy_from_slice(&self.node_id.to_be_bytes());
+        id[8] = self.thread_id;
+        // 카운터는 하위 3바이트만
+        id[9..].copy_from_slice(&self.counter.to_be_bytes()[1..]);
+        self.counter += 1;
+        telemetry::id_generated();
+        Ok(id)
+    }
+}
+
+impl<C> Drop for ObjectIdGenerator<C> {
+    fn drop(&mut self) {
+        ThreadIdPool::global().force_release(SlotLease {
+            thread_id: self.thread_id,
+            generation: self.generation,
+        });
+    }
+}
+
+/// The 24 lowercase hex digits MongoDB prints an ObjectId as.
+pub fn to_object_id_hex(id: &[u8; 12]) -> String {
+    id.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_bytes_sort_chronologically() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut generator = ObjectIdGenerator::with_clock(0x0102_0304, clock.clone())
+            .expect("Failed to create ObjectIdGenerator");
+        let mut ids = Vec::new();
+        for _ in 0..5 {
+            for _ in 0..300 {
+                ids.push(generator.generate_id().expect("Failed to generate ID"));
+            }
+            // 1초 미만 전진도 섞어서
+            clock.advance(700);
+        }
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ids[0][..4], 1_700_000_000u32.to_be_bytes());
+        assert_eq!(ids[0][4..8], [1, 2, 3, 4]);
+
+        let hex = to_object_id_hex(&ids[0]);
+        assert_eq!(hex.len(), 24);
+        assert!(hex.starts_with("6553f10001020304"), "{}", hex);
+        assert!(hex.ends_with("000000"), "{}", hex);
+    }
+}
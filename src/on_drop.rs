@@ -0,0 +1,85 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::Switflake;
+
+pub(crate) type DropCallback = Box<dyn FnOnce(GeneratorState) + Send + Sync>;
+
+/// Where a generator stood when it was dropped, as given to the callback
+/// set with [`Switflake::on_drop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorState {
+    pub node_id: u64,
+    pub thread_id: u8,
+    /// Timestamp field of the last tick the generator issued IDs in, or 0
+    /// if it issued none.
+    pub last_timestamp: u64,
+    /// Counter the next ID of that tick would have carried.
+    pub next_counter: u16,
+}
+
+impl<C> Switflake<C> {
+    /// Calls `callback` with the generator's final state when it is
+    /// dropped, before its pool slot is released, e.g. to persist the last
+    /// timestamp somewhere other generators can pick it up. Replaces any
+    /// callback set before.
+    ///
+    /// A panic in `callback` is caught and discarded, so it can't unwind
+    /// out of `drop` and abort a thread that is already unwinding.
+    pub fn on_drop(&mut self, callback: impl FnOnce(GeneratorState) + Send + Sync + 'static) {
+        self.on_drop = Some(Box::new(callback));
+    }
+
+    pub(crate) fn run_on_drop(&mut self) {
+        let Some(callback) = self.on_drop.take() else {
+            return;
+        };
+        let state = GeneratorState {
+            node_id: self.node_id,
+            thread_id: self.thread_id,
+            last_timestamp: self.last_timestamp,
+            next_counter: self.local_counter,
+        };
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| callback(state)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::{decompose, Switflake};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_on_drop_reports_final_state() {
+        let _guard = serial_guard();
+        let recorded = Arc::new(Mutex::new(None));
+        let mut swit = Switflake::new(21).expect("Failed to create Switflake");
+        let layout = swit.layout();
+        let sink = Arc::clone(&recorded);
+        swit.on_drop(move |state| *sink.lock().unwrap() = Some(state));
+
+        let mut last = 0;
+        for _ in 0..5 {
+            last = swit.generate_id().expect("Failed to generate ID");
+        }
+        let thread_id = swit.lease().thread_id;
+        drop(swit);
+
+        let parts = decompose(last, &layout);
+        assert_eq!(
+            *recorded.lock().unwrap(),
+            Some(GeneratorState {
+                node_id: 21,
+                thread_id,
+                last_timestamp: parts.timestamp,
+                next_counter: parts.counter + 1,
+            })
+        );
+
+        // 콜백이 패닉해도 drop은 정상 종료
+        let mut swit = Switflake::new(21).expect("Failed to create Switflake");
+        swit.on_drop(|_| panic!("callback failed"));
+        drop(swit);
+    }
+}
@@ -0,0 +1,63 @@
+//! Process-wide ordering token for generators built with
+//! [`SwitflakeBuilder::global_order`](crate::SwitflakeBuilder::global_order).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::layout::Counter;
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+/// Takes the next token, reduced to `0..counter_mask` so the reserved top
+/// counter value is never used, as with ordinary counters.
+#[inline]
+pub(crate) fn next_token(counter_mask: u64) -> Counter {
+    let token = NEXT_TOKEN.fetch_add(1, Ordering::SeqCst);
+    token.checked_rem(counter_mask).unwrap_or(0) as Counter
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{decompose, Switflake, SwitflakeError};
+
+    #[test]
+    fn test_ids_reflect_call_order_across_generators() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let build = |node| {
+            Switflake::builder()
+                .node_id(node)
+                .global_order(true)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake")
+        };
+        let (mut a, mut b) = (build(2), build(1));
+        let layout = a.layout();
+
+        let mut calls = Vec::new();
+        for i in 0..60 {
+            let swit = if i % 3 == 0 { &mut b } else { &mut a };
+            match swit.generate_id() {
+                Ok(id) => calls.push(id),
+                // 토큰이 한 바퀴 돌면 다음 밀리초까지 대기
+                Err(SwitflakeError::SequenceExhausted) => {
+                    clock.advance(1);
+                    calls.push(swit.generate_id().expect("Failed to generate ID"));
+                }
+                Err(e) => panic!("Failed to generate ID: {}", e),
+            }
+            if i % 25 == 24 {
+                clock.advance(1);
+            }
+        }
+
+        let mut sorted = calls.clone();
+        sorted.sort_by_key(|&id| {
+            let parts = decompose(id, &layout);
+            (parts.timestamp, parts.counter)
+        });
+        assert_eq!(sorted, calls);
+    }
+}
@@ -0,0 +1,1496 @@
+// Under `--cfg loom` the slot atomics come from loom, which can then check
+// every interleaving of acquire and release; see the loom tests below.
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, AtomicU8};
+use std::sync::atomic::Ordering;
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, AtomicU8};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+#[cfg(feature = "async")]
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::layout::mask;
+use crate::{telemetry, SwitflakeError};
+use bitmap::Atomic as _;
+
+const POOL_SIZE: usize = 8;
+
+/// Allocator for the thread id embedded in every ID.
+///
+/// The process-wide [`global`](ThreadIdPool::global) pool has 8 slots and
+/// fills the default layout's 3-bit thread field. Other capacities, from 1
+/// to 64 slots, can be declared as statics and handed to
+/// [`SwitflakeBuilder::pool`](crate::SwitflakeBuilder::pool), which sizes
+/// the thread field to match:
+///
+/// ```
+/// use switflake::{Layout, Switflake, ThreadIdPool};
+///
+/// // A single generator needs no thread bits, so an 8-bit sequence field
+/// // can be all counter.
+/// static POOL: ThreadIdPool<1> = ThreadIdPool::new();
+/// let layout = Layout {
+///     node_shift: 8,
+///     node_bits: 15,
+///     counter_bits: 5,
+///     ..Layout::default()
+/// };
+/// let swit = Switflake::builder().layout(layout).pool(&POOL).build().unwrap();
+/// assert_eq!(swit.layout().thread_bits, 0);
+/// assert_eq!(swit.layout().counter_bits, 8);
+/// ```
+///
+/// The capacity is a `usize` rather than a `u32` because it also sizes the
+/// pool's per-slot arrays, which stable Rust can only do with a `usize`
+/// const parameter. The slot bitmap is the narrowest atomic integer with a
+/// bit per slot, picked through [`SlotBitmap`]; capacities outside 1 to 64
+/// don't compile.
+pub struct ThreadIdPool<const N: usize = POOL_SIZE>
+where
+    Slots<N>: SlotBitmap,
+{
+    used_ids: <Slots<N> as SlotBitmap>::Atomic,
+    // Bumped every time a slot is freed, so a holder can tell whether its slot
+    // was reclaimed out from under it.
+    generations: [AtomicU32; N],
+    // Wakes `acquire_blocking` callers whenever a slot is freed. The mutex
+    // guards no data; freeing a slot takes it before notifying so a waiter
+    // that has just seen a full pool cannot miss the wakeup.
+    freed_lock: Mutex<()>,
+    freed: Condvar,
+    // Tasks parked in `acquire_async`, all woken whenever a slot is freed.
+    #[cfg(feature = "async")]
+    wakers: Mutex<Vec<Waker>>,
+    // u32::MAX while no high watermark is set, so acquiring only reads an
+    // atomic unless usage reaches a threshold.
+    watermark_threshold: AtomicU32,
+    watermark_callback: Mutex<Option<WatermarkCallback>>,
+    // Unix micros at which the last tick used through each slot ended, so
+    // with the cooldown on a slot isn't reused before then.
+    released_at: [AtomicU64; N],
+    cooldown: AtomicBool,
+    ordering: PoolOrdering,
+    // Callers parked in `acquire_blocking` or a generator's async build,
+    // and how many may be at once; u32::MAX for no limit.
+    waiters: AtomicU32,
+    max_waiters: AtomicU32,
+}
+
+/// Capacity marker for [`ThreadIdPool`]: `Slots<N>` implements
+/// [`SlotBitmap`] for every supported capacity `N`, 1 to 64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slots<const N: usize>;
+
+/// Picks the atomic integer holding a pool's slot bitmap from its
+/// capacity: `AtomicU8` for up to 8 slots, then `AtomicU16`, `AtomicU32`
+/// and `AtomicU64`. Sealed; only [`Slots`] implements it.
+pub trait SlotBitmap: bitmap::Sealed {
+    #[doc(hidden)]
+    type Atomic: bitmap::Atomic;
+}
+
+mod bitmap {
+    use super::*;
+
+    pub trait Sealed {}
+
+    /// The atomic operations the pool needs on its bitmap, widened to
+    /// `u64` so the pool code doesn't depend on the width. Values passed in
+    /// only have bits for existing slots set, or are masks whose truncated
+    /// high bits don't matter.
+    pub trait Atomic: Sized + Send + Sync {
+        #[cfg(not(loom))]
+        const EMPTY: Self;
+        #[cfg(loom)]
+        fn empty() -> Self;
+        fn load(&self, order: Ordering) -> u64;
+        fn store(&self, value: u64, order: Ordering);
+        fn swap(&self, value: u64, order: Ordering) -> u64;
+        fn fetch_and(&self, value: u64, order: Ordering) -> u64;
+        fn compare_exchange(
+            &self,
+            current: u64,
+            new: u64,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<u64, u64>;
+    }
+
+    macro_rules! atomic_bitmap {
+        ($($atomic:ident: $int:ty),*) => {$(
+            impl Atomic for $atomic {
+                // Only used as an initializer, where copying is the point.
+                #[cfg(not(loom))]
+                #[allow(clippy::declare_interior_mutable_const)]
+                const EMPTY: Self = $atomic::new(0);
+                #[cfg(loom)]
+                fn empty() -> Self {
+                    $atomic::new(0)
+                }
+                #[inline]
+                fn load(&self, order: Ordering) -> u64 {
+                    $atomic::load(self, order) as u64
+                }
+                #[inline]
+                fn store(&self, value: u64, order: Ordering) {
+                    $atomic::store(self, value as $int, order)
+                }
+                #[inline]
+                fn swap(&self, value: u64, order: Ordering) -> u64 {
+                    $atomic::swap(self, value as $int, order) as u64
+                }
+                #[inline]
+                fn fetch_and(&self, value: u64, order: Ordering) -> u64 {
+                    $atomic::fetch_and(self, value as $int, order) as u64
+                }
+                #[inline]
+                fn compare_exchange(
+                    &self,
+                    current: u64,
+                    new: u64,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<u64, u64> {
+                    $atomic::compare_exchange(self, current as $int, new as $int, success, failure)
+                        .map(|value| value as u64)
+                        .map_err(|value| value as u64)
+                }
+            }
+        )*};
+    }
+
+    atomic_bitmap!(AtomicU8: u8, AtomicU16: u16, AtomicU32: u32, AtomicU64: u64);
+
+    macro_rules! slot_bitmap {
+        ($atomic:ident: $($n:literal)*) => {$(
+            impl Sealed for Slots<$n> {}
+            impl SlotBitmap for Slots<$n> {
+                type Atomic = $atomic;
+            }
+        )*};
+    }
+
+    slot_bitmap!(AtomicU8: 1 2 3 4 5 6 7 8);
+    slot_bitmap!(AtomicU16: 9 10 11 12 13 14 15 16);
+    slot_bitmap!(AtomicU32: 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+    slot_bitmap!(
+        AtomicU64: 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58
+            59 60 61 62 63 64
+    );
+}
+
+/// Memory orderings a [`ThreadIdPool`] uses on its slot bitmap and
+/// generation counters, set with [`ThreadIdPool::with_ordering`] so the
+/// cost of `SeqCst` can be measured against the weakest orderings that
+/// are still correct.
+///
+/// Both choices are sound. There is deliberately no weaker one: taking a
+/// slot must acquire and freeing it must release, or a new holder can
+/// miss the previous holder's writes to the generation counter and the
+/// cooldown timestamps, and mint its IDs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PoolOrdering {
+    /// `SeqCst` for every operation.
+    SeqCst,
+    /// `Acquire` loads, `AcqRel` read-modify-writes. The default.
+    #[default]
+    AcqRel,
+}
+
+impl PoolOrdering {
+    const fn load(self) -> Ordering {
+        match self {
+            PoolOrdering::SeqCst => Ordering::SeqCst,
+            PoolOrdering::AcqRel => Ordering::Acquire,
+        }
+    }
+
+    const fn read_modify_write(self) -> Ordering {
+        match self {
+            PoolOrdering::SeqCst => Ordering::SeqCst,
+            PoolOrdering::AcqRel => Ordering::AcqRel,
+        }
+    }
+}
+
+type WatermarkCallback = Arc<dyn Fn(PoolStats) + Send + Sync>;
+
+/// Pool usage as reported to a
+/// [`high watermark`](ThreadIdPool::set_high_watermark) callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Slots held right after the acquisition that triggered the report.
+    pub used: u32,
+    pub capacity: u32,
+}
+
+/// Proof of holding a particular thread-id slot, as handed out by `acquire`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotLease {
+    pub thread_id: u8,
+    pub generation: u32,
+}
+
+/// Confirmation required by [`ThreadIdPool::release_all`].
+///
+/// Constructing one is the caller's statement that no generator still holds
+/// a slot in the pool it is spent on.
+#[derive(Debug)]
+pub struct ReleaseAllToken {
+    _private: (),
+}
+
+impl ReleaseAllToken {
+    /// Calling [`ThreadIdPool::release_all`] while any generator from that
+    /// pool is alive lets two generators share a thread id and mint
+    /// duplicate IDs. Only build this token when every holder is known to be
+    /// gone.
+    pub fn i_understand_live_generators_will_mint_duplicates() -> Self {
+        ReleaseAllToken { _private: () }
+    }
+}
+
+impl ThreadIdPool {
+    pub fn global() -> &'static ThreadIdPool {
+        static POOL: OnceLock<ThreadIdPool> = OnceLock::new();
+        POOL.get_or_init(ThreadIdPool::new)
+    }
+}
+
+impl<const N: usize> Default for ThreadIdPool<N>
+where
+    Slots<N>: SlotBitmap,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ThreadIdPool<N>
+where
+    Slots<N>: SlotBitmap,
+{
+    /// Number of generators that can exist at once.
+    pub const CAPACITY: usize = N;
+
+    /// Width of a thread field that numbers every slot: 0 for a single
+    /// slot, 3 for 8, 6 for 64.
+    pub const THREAD_BITS: u32 = usize::BITS - (N - 1).leading_zeros();
+
+    const FULL: u64 = mask(N as u32);
+    // Only used as an array-repeat initializer, where copying is the point.
+    #[cfg(not(loom))]
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicU32 = AtomicU32::new(0);
+    #[cfg(not(loom))]
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO_MICROS: AtomicU64 = AtomicU64::new(0);
+
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        ThreadIdPool {
+            used_ids: <<Slots<N> as SlotBitmap>::Atomic as bitmap::Atomic>::EMPTY,
+            generations: [Self::ZERO; N],
+            freed_lock: Mutex::new(()),
+            freed: Condvar::new(),
+            #[cfg(feature = "async")]
+            wakers: Mutex::new(Vec::new()),
+            watermark_threshold: AtomicU32::new(u32::MAX),
+            watermark_callback: Mutex::new(None),
+            released_at: [Self::ZERO_MICROS; N],
+            cooldown: AtomicBool::new(false),
+            ordering: PoolOrdering::AcqRel,
+            waiters: AtomicU32::new(0),
+            max_waiters: AtomicU32::new(u32::MAX),
+        }
+    }
+
+    // loom's atomics can't be built in a const context.
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        ThreadIdPool {
+            used_ids: bitmap::Atomic::empty(),
+            generations: std::array::from_fn(|_| AtomicU32::new(0)),
+            freed_lock: Mutex::new(()),
+            freed: Condvar::new(),
+            #[cfg(feature = "async")]
+            wakers: Mutex::new(Vec::new()),
+            watermark_threshold: AtomicU32::new(u32::MAX),
+            watermark_callback: Mutex::new(None),
+            released_at: std::array::from_fn(|_| AtomicU64::new(0)),
+            cooldown: AtomicBool::new(false),
+            ordering: PoolOrdering::AcqRel,
+            waiters: AtomicU32::new(0),
+            max_waiters: AtomicU32::new(u32::MAX),
+        }
+    }
+
+    /// The pool with its slot operations using `ordering`, for
+    /// benchmarking; see [`PoolOrdering`].
+    ///
+    /// ```
+    /// use switflake::{PoolOrdering, ThreadIdPool};
+    ///
+    /// static POOL: ThreadIdPool = ThreadIdPool::new().with_ordering(PoolOrdering::SeqCst);
+    /// ```
+    pub const fn with_ordering(mut self, ordering: PoolOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Keeps a slot freed by a dropped generator from being handed out again
+    /// until the last tick that generator issued IDs in has ended, by the
+    /// clock of the generator built next. Without it a generator built in
+    /// the same millisecond can take the slot and, its clock not having
+    /// advanced, repeat the dropped one's IDs.
+    ///
+    /// Builders then take a slot that isn't cooling down if one is free.
+    /// Otherwise they take a cooling one anyway, and the new generator
+    /// starts issuing IDs only once the cooldown is over, failing until
+    /// then with [`SwitflakeError::SequenceExhausted`] as when a tick runs
+    /// out, or with [`SwitflakeError::ClockMovedBackwards`] if its time unit
+    /// is finer than the dropped generator's.
+    pub fn set_reuse_cooldown(&self, enabled: bool) {
+        self.cooldown.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Caps how many callers may wait for a slot at once in
+    /// [`Switflake::new_blocking`](crate::Switflake::new_blocking),
+    /// `new_async` and the matching builder methods: once `max_waiters` are
+    /// parked, further callers finding the pool full fail right away with
+    /// [`SwitflakeError::PoolExhausted`] instead of joining the queue, so a
+    /// capacity problem surfaces rather than piling up requests. `None`, the
+    /// default, lets any number wait.
+    pub fn set_max_waiters(&self, max_waiters: Option<u32>) {
+        self.max_waiters
+            .store(max_waiters.unwrap_or(u32::MAX), Ordering::SeqCst);
+    }
+
+    /// Counts the caller as waiting until the returned guard is dropped,
+    /// or fails if `max_waiters` already are.
+    fn join_waiters(&self) -> Result<Waiter<'_>, SwitflakeError> {
+        let max_waiters = self.max_waiters.load(Ordering::SeqCst);
+        self.waiters
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |waiters| {
+                (waiters < max_waiters).then_some(waiters + 1)
+            })
+            .map_err(|_| SwitflakeError::PoolExhausted)?;
+        Ok(Waiter {
+            waiters: &self.waiters,
+        })
+    }
+
+    /// Unix micros until which slot `id` is cooling down; 0 if it isn't or
+    /// the cooldown is off.
+    pub(crate) fn cooling_until(&self, id: u8) -> u64 {
+        if !self.cooldown.load(Ordering::Relaxed) || id as usize >= N {
+            return 0;
+        }
+        self.released_at[id as usize].load(Ordering::SeqCst)
+    }
+
+    /// Like `force_release`, but first records when the last tick used
+    /// through the slot ends, for the cooldown. A stale lease records
+    /// nothing, and an earlier end never replaces a later one.
+    pub(crate) fn release_after(&self, lease: SlotLease, end_micros: u64) -> bool {
+        let id = lease.thread_id;
+        if self.is_held(id) && self.generation(id) == lease.generation {
+            self.released_at[id as usize].fetch_max(end_micros, Ordering::SeqCst);
+        }
+        self.force_release(lease)
+    }
+
+    /// Calls `callback` whenever taking a slot leaves `threshold` or more
+    /// slots held, e.g. 6 of 8, so monitoring can react before generators
+    /// start failing with [`SwitflakeError::PoolExhausted`]. Replaces any
+    /// earlier callback.
+    ///
+    /// The callback runs on the acquiring thread, after the slot is taken
+    /// and with no lock of the pool held, so it may itself use the pool.
+    pub fn set_high_watermark(
+        &self,
+        threshold: u32,
+        callback: impl Fn(PoolStats) + Send + Sync + 'static,
+    ) {
+        let mut slot = self
+            .watermark_callback
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *slot = Some(Arc::new(callback));
+        self.watermark_threshold.store(threshold, Ordering::SeqCst);
+    }
+
+    /// Records a successful acquisition that left `used` as the slot mask.
+    fn acquired(&self, used: u64) {
+        telemetry::pool_slots_used(used);
+        let used = used.count_ones();
+        if used < self.watermark_threshold.load(Ordering::Relaxed) {
+            return;
+        }
+        let callback = self
+            .watermark_callback
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(callback) = callback {
+            callback(PoolStats {
+                used,
+                capacity: N as u32,
+            });
+        }
+    }
+
+    pub(crate) fn acquire(&self) -> Result<SlotLease, SwitflakeError> {
+        self.acquire_keeping_free(0)
+    }
+
+    /// Like `acquire`, but fails unless more than `keep_free` slots are free,
+    /// so the last `keep_free` slots stay available to other callers.
+    pub(crate) fn acquire_keeping_free(&self, keep_free: u32) -> Result<SlotLease, SwitflakeError> {
+        self.acquire_preferring(keep_free, Self::FULL)
+    }
+
+    /// Like `acquire_keeping_free`, but takes a free slot among `preferred`
+    /// if there is one.
+    pub(crate) fn acquire_preferring(
+        &self,
+        keep_free: u32,
+        preferred: u64,
+    ) -> Result<SlotLease, SwitflakeError> {
+        let mut backoff = Backoff::new();
+        let mut current = self.used_ids.load(self.ordering.load());
+        loop {
+            if N as u32 - current.count_ones() <= keep_free {
+                return Err(SwitflakeError::PoolExhausted);
+            }
+            let i = pick_free(current, preferred & Self::FULL);
+            let new_value = current | (1 << i);
+            match self.used_ids.compare_exchange(
+                current,
+                new_value,
+                self.ordering.read_modify_write(),
+                self.ordering.load(),
+            ) {
+                Ok(_) => {
+                    self.acquired(new_value);
+                    return Ok(SlotLease {
+                        thread_id: i,
+                        generation: self.generation(i),
+                    });
+                }
+                Err(actual) => {
+                    current = actual;
+                    backoff.snooze();
+                }
+            }
+        }
+    }
+
+    /// Like `acquire`, but when the pool is full parks until a slot is freed
+    /// or `timeout` elapses, whichever comes first. Without a timeout it
+    /// waits indefinitely.
+    pub(crate) fn acquire_blocking(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<SlotLease, SwitflakeError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut waiter = None;
+        loop {
+            match self.acquire() {
+                Err(SwitflakeError::PoolExhausted) => {}
+                result => return result,
+            }
+            if waiter.is_none() {
+                waiter = Some(self.join_waiters()?);
+            }
+            let guard = self.freed_lock.lock().unwrap_or_else(|e| e.into_inner());
+            if !self.is_full() {
+                continue;
+            }
+            match deadline {
+                None => drop(self.freed.wait(guard)),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SwitflakeError::PoolExhausted);
+                    }
+                    drop(self.freed.wait_timeout(guard, deadline - now));
+                }
+            }
+        }
+    }
+
+    /// Takes a free slot with a single compare-and-swap, returning `None` if
+    /// the pool is full or another thread won the race. Unlike the retrying
+    /// acquisition used by [`Switflake::new`](crate::Switflake::new), this
+    /// never spins or yields.
+    ///
+    /// The slot is released again by [`reclaim`](Self::reclaim).
+    pub fn try_acquire(&self) -> Option<u8> {
+        self.try_acquire_lease().map(|lease| lease.thread_id)
+    }
+
+    pub(crate) fn try_acquire_lease(&self) -> Option<SlotLease> {
+        self.try_acquire_preferring(Self::FULL)
+    }
+
+    pub(crate) fn try_acquire_preferring(&self, preferred: u64) -> Option<SlotLease> {
+        let current = self.used_ids.load(self.ordering.load());
+        if current == Self::FULL {
+            return None;
+        }
+        let i = pick_free(current, preferred & Self::FULL);
+        let new_value = current | (1 << i);
+        self.used_ids
+            .compare_exchange(
+                current,
+                new_value,
+                self.ordering.read_modify_write(),
+                self.ordering.load(),
+            )
+            .ok()?;
+        self.acquired(new_value);
+        Some(SlotLease {
+            thread_id: i,
+            generation: self.generation(i),
+        })
+    }
+
+    /// Takes `count` slots in a single compare-and-swap, so either all of
+    /// them are claimed or none are.
+    pub(crate) fn acquire_many(&self, count: usize) -> Result<Vec<SlotLease>, SwitflakeError> {
+        let mut backoff = Backoff::new();
+        let mut current = self.used_ids.load(self.ordering.load());
+        loop {
+            if N - (current.count_ones() as usize) < count {
+                return Err(SwitflakeError::PoolExhausted);
+            }
+            let mut new_value = current;
+            for _ in 0..count {
+                new_value |= 1 << (!new_value).trailing_zeros();
+            }
+            match self.used_ids.compare_exchange(
+                current,
+                new_value,
+                self.ordering.read_modify_write(),
+                self.ordering.load(),
+            ) {
+                Ok(_) => {
+                    self.acquired(new_value);
+                    let claimed = new_value & !current;
+                    return Ok((0..N as u8)
+                        .filter(|i| claimed & (1 << i) != 0)
+                        .map(|i| SlotLease {
+                            thread_id: i,
+                            generation: self.generation(i),
+                        })
+                        .collect());
+                }
+                Err(actual) => {
+                    current = actual;
+                    backoff.snooze();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn release(&self, id: u8) {
+        self.generations[id as usize].fetch_add(1, self.ordering.read_modify_write());
+        let mask = !(1 << id);
+        let previous = self
+            .used_ids
+            .fetch_and(mask, self.ordering.read_modify_write());
+        telemetry::pool_slots_used(previous & mask);
+        self.notify_freed();
+    }
+
+    fn notify_freed(&self) {
+        drop(self.freed_lock.lock().unwrap_or_else(|e| e.into_inner()));
+        self.freed.notify_all();
+        #[cfg(feature = "async")]
+        {
+            let wakers =
+                std::mem::take(&mut *self.wakers.lock().unwrap_or_else(|e| e.into_inner()));
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+
+    /// Takes a free slot, waiting without blocking the thread while the
+    /// pool is full: the returned future completes once a slot is freed
+    /// and this caller wins it. Release the slot again with
+    /// [`force_release`](Self::force_release). Not limited by
+    /// [`set_max_waiters`](Self::set_max_waiters), which only applies to
+    /// building generators.
+    #[cfg(feature = "async")]
+    pub fn acquire_async(&self) -> impl std::future::Future<Output = SlotLease> + '_ {
+        std::future::poll_fn(move |cx| self.poll_slot(cx))
+    }
+
+    /// Like `poll_slot`, but joins the waiters, kept in `waiter` until the
+    /// future holding it is done, before first parking.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_acquire<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+        waiter: &mut Option<Waiter<'a>>,
+    ) -> Poll<Result<SlotLease, SwitflakeError>> {
+        if waiter.is_none() {
+            if let Ok(lease) = self.acquire() {
+                return Poll::Ready(Ok(lease));
+            }
+            *waiter = Some(self.join_waiters()?);
+        }
+        self.poll_slot(cx).map(Ok)
+    }
+
+    #[cfg(feature = "async")]
+    fn poll_slot(&self, cx: &mut Context<'_>) -> Poll<SlotLease> {
+        if let Ok(lease) = self.acquire() {
+            return Poll::Ready(lease);
+        }
+        {
+            let mut wakers = self.wakers.lock().unwrap_or_else(|e| e.into_inner());
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        // 등록 직전에 해제된 슬롯을 놓치지 않도록 한 번 더 시도
+        match self.acquire() {
+            Ok(lease) => Poll::Ready(lease),
+            Err(_) => Poll::Pending,
+        }
+    }
+
+    /// Whether every slot is currently held, so creating a generator would
+    /// fail. Only a snapshot: another thread may free or take a slot right
+    /// after.
+    pub fn is_full(&self) -> bool {
+        self.used_ids.load(self.ordering.load()) == Self::FULL
+    }
+
+    /// Thread ids of every slot held right now, in increasing order, read
+    /// from a single snapshot of the pool, e.g. to find out who holds a
+    /// pool that looks full.
+    pub fn occupied_slots(&self) -> Vec<u8> {
+        let used = self.used_ids.load(self.ordering.load());
+        (0..N as u8).filter(|i| used & (1 << i) != 0).collect()
+    }
+
+    /// Whether slot `id` is handed out; always false for an `id` past the
+    /// pool's capacity.
+    pub fn is_held(&self, id: u8) -> bool {
+        (id as usize) < N
+            && 1u64
+                .checked_shl(id as u32)
+                .is_some_and(|bit| self.used_ids.load(self.ordering.load()) & bit != 0)
+    }
+
+    pub(crate) fn generation(&self, id: u8) -> u32 {
+        self.generations[id as usize].load(self.ordering.load())
+    }
+
+    /// Unconditionally frees slot `id`, returning whether it was held.
+    ///
+    /// Only call this for a slot whose holder is known to be gone without
+    /// having run its `Drop` (e.g. it was `mem::forget`-ed). Reclaiming a slot
+    /// that a live generator still uses lets two generators share a thread id
+    /// and mint duplicate IDs; in debug builds the original holder panics on
+    /// its next `generate_id`.
+    pub fn reclaim(&self, id: u8) -> bool {
+        assert!((id as usize) < N, "thread id {} out of range", id);
+        let held = self.is_held(id);
+        self.release(id);
+        held
+    }
+
+    /// Frees the slot described by `lease` only if it has not been freed and
+    /// handed out again since the lease was taken.
+    ///
+    /// Supervisors should record a generator's `lease()` when creating it and
+    /// use this rather than `reclaim`, so a stale lease can never free a slot
+    /// that now belongs to somebody else. Returns whether the slot was freed.
+    pub fn force_release(&self, lease: SlotLease) -> bool {
+        let id = lease.thread_id;
+        if (id as usize) >= N || !self.is_held(id) {
+            return false;
+        }
+        let freed = self.generations[id as usize]
+            .compare_exchange(
+                lease.generation,
+                lease.generation.wrapping_add(1),
+                self.ordering.read_modify_write(),
+                self.ordering.load(),
+            )
+            .is_ok();
+        if freed {
+            let mask = !(1 << id);
+            let previous = self
+                .used_ids
+                .fetch_and(mask, self.ordering.read_modify_write());
+            telemetry::pool_slots_used(previous & mask);
+            self.notify_freed();
+        }
+        freed
+    }
+
+    /// Frees every slot at once, for test harnesses and recovery after
+    /// generators were leaked wholesale.
+    ///
+    /// **This causes duplicate IDs if any generator from this pool is still
+    /// alive.** Its slot goes back into circulation and the next generator
+    /// to take it mints IDs with the same thread id. In debug builds such a
+    /// survivor panics on its next `generate_id`, but release builds do not
+    /// notice. Prefer [`force_release`](Self::force_release) with recorded
+    /// leases whenever the holders are known.
+    pub fn release_all(&self, confirm: ReleaseAllToken) {
+        let ReleaseAllToken { _private: () } = confirm;
+        let held = self.used_ids.swap(0, self.ordering.read_modify_write());
+        for id in 0..N {
+            if held & (1 << id) != 0 {
+                self.generations[id].fetch_add(1, self.ordering.read_modify_write());
+            }
+        }
+        telemetry::pool_slots_used(0);
+        self.notify_freed();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn release_all_for_test(&self) {
+        self.release_all(ReleaseAllToken::i_understand_live_generators_will_mint_duplicates());
+    }
+
+    /// Disowns every slot inherited across a `fork`. Call it in the child
+    /// before creating any generator, typically from a `pthread_atfork` child
+    /// handler:
+    ///
+    /// ```ignore
+    /// extern "C" fn reinit_child() {
+    ///     switflake::ThreadIdPool::global().reinit_after_fork();
+    /// }
+    /// unsafe { libc::pthread_atfork(None, None, Some(reinit_child)) };
+    /// ```
+    ///
+    /// Generators copied into the child stop owning their slots: dropping
+    /// them no longer frees anything and, in debug builds, using them panics.
+    /// The slots stay marked as held because the parent still uses them, so
+    /// generators created in the child get thread ids the parent did not hold
+    /// at fork time. The two processes don't share a pool afterwards, so
+    /// slots the parent acquires later are invisible to the child; unless the
+    /// parent stops creating generators after forking, give the child its own
+    /// node id.
+    pub fn reinit_after_fork(&self) {
+        let held = self.used_ids.load(self.ordering.load());
+        for id in 0..N {
+            if held & (1 << id) != 0 {
+                self.generations[id].fetch_add(1, self.ordering.read_modify_write());
+            }
+        }
+    }
+
+    // What a forked child starts with: a byte-for-byte copy of the parent.
+    #[cfg(test)]
+    fn fork_copy(&self) -> ThreadIdPool<N> {
+        let copy = ThreadIdPool::<N>::new();
+        copy.used_ids
+            .store(self.used_ids.load(Ordering::SeqCst), Ordering::SeqCst);
+        for (dst, src) in copy.generations.iter().zip(&self.generations) {
+            dst.store(src.load(Ordering::SeqCst), Ordering::SeqCst);
+        }
+        copy
+    }
+}
+
+/// Lowest free slot among `preferred`, or else the lowest free slot. The
+/// caller checks that one is free.
+#[inline]
+fn pick_free(used: u64, preferred: u64) -> u8 {
+    let free = !used;
+    match free & preferred {
+        0 => free.trailing_zeros() as u8,
+        candidates => candidates.trailing_zeros() as u8,
+    }
+}
+
+/// Object-safe view of a [`ThreadIdPool`] of any capacity, so builders and
+/// generators can hold on to the pool their slot came from.
+pub(crate) trait SlotPool: Sync {
+    fn capacity(&self) -> usize;
+    fn thread_bits(&self) -> u32;
+    fn is_full(&self) -> bool;
+    fn acquire_preferring(
+        &self,
+        keep_free: u32,
+        preferred: u64,
+    ) -> Result<SlotLease, SwitflakeError>;
+    fn acquire_blocking(&self, timeout: Option<Duration>) -> Result<SlotLease, SwitflakeError>;
+    fn try_acquire_lease(&self) -> Option<SlotLease>;
+    fn acquire_many(&self, count: usize) -> Result<Vec<SlotLease>, SwitflakeError>;
+    fn generation(&self, id: u8) -> u32;
+    fn release_after(&self, lease: SlotLease, end_micros: u64) -> bool;
+    fn cooling_until(&self, id: u8) -> u64;
+    #[cfg(feature = "async")]
+    fn poll_acquire<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+        waiter: &mut Option<Waiter<'a>>,
+    ) -> Poll<Result<SlotLease, SwitflakeError>>;
+}
+
+impl<const N: usize> SlotPool for ThreadIdPool<N>
+where
+    Slots<N>: SlotBitmap,
+{
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn thread_bits(&self) -> u32 {
+        Self::THREAD_BITS
+    }
+
+    fn is_full(&self) -> bool {
+        Self::is_full(self)
+    }
+
+    fn acquire_preferring(
+        &self,
+        keep_free: u32,
+        preferred: u64,
+    ) -> Result<SlotLease, SwitflakeError> {
+        Self::acquire_preferring(self, keep_free, preferred)
+    }
+
+    fn acquire_blocking(&self, timeout: Option<Duration>) -> Result<SlotLease, SwitflakeError> {
+        Self::acquire_blocking(self, timeout)
+    }
+
+    fn try_acquire_lease(&self) -> Option<SlotLease> {
+        Self::try_acquire_lease(self)
+    }
+
+    fn acquire_many(&self, count: usize) -> Result<Vec<SlotLease>, SwitflakeError> {
+        Self::acquire_many(self, count)
+    }
+
+    #[inline]
+    fn generation(&self, id: u8) -> u32 {
+        Self::generation(self, id)
+    }
+
+    fn release_after(&self, lease: SlotLease, end_micros: u64) -> bool {
+        Self::release_after(self, lease, end_micros)
+    }
+
+    fn cooling_until(&self, id: u8) -> u64 {
+        Self::cooling_until(self, id)
+    }
+
+    #[cfg(feature = "async")]
+    fn poll_acquire<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+        waiter: &mut Option<Waiter<'a>>,
+    ) -> Poll<Result<SlotLease, SwitflakeError>> {
+        ThreadIdPool::poll_acquire(self, cx, waiter)
+    }
+}
+
+/// A caller counted among a pool's waiters until dropped.
+pub(crate) struct Waiter<'a> {
+    waiters: &'a AtomicU32,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl std::fmt::Debug for dyn SlotPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThreadIdPool")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Exponential backoff between failed CAS attempts: spin for 1, 2, 4, ...
+/// iterations, then start yielding the thread once spinning stops paying off.
+struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    const SPIN_LIMIT: u32 = 6;
+
+    fn new() -> Self {
+        Backoff { step: 0 }
+    }
+
+    #[inline]
+    fn snooze(&mut self) {
+        if self.step <= Self::SPIN_LIMIT {
+            for _ in 0..(1u32 << self.step) {
+                std::hint::spin_loop();
+            }
+            self.step += 1;
+        } else {
+            std::thread::yield_now();
+        }
+    }
+}
+
+/// Serializes tests that depend on the state of the global pool. Also
+/// forgets the process-wide timestamp high-water mark, since each test runs
+/// its own clock.
+#[cfg(test)]
+pub(crate) fn serial_guard() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    #[cfg(feature = "paranoid")]
+    crate::watermark::reset();
+    guard
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use crate::Switflake;
+    use std::mem;
+    use std::sync::atomic::AtomicU8;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn test_reclaim_forgotten_slot() {
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+
+        let mut swits: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        let leaked = swits.pop().unwrap();
+        let leaked_id = leaked.lease().thread_id;
+        mem::forget(leaked);
+        drop(swits);
+
+        // 잊힌 슬롯은 풀이 비어도 계속 점유됨
+        assert!(pool.is_held(leaked_id));
+        let refill: Vec<Switflake> = (0..7)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        assert!(Switflake::new(1).is_err(), "Leaked slot should be stuck");
+
+        assert!(pool.reclaim(leaked_id));
+        let swit = Switflake::new(1).expect("Should succeed after reclaim");
+        assert_eq!(swit.lease().thread_id, leaked_id);
+        drop(refill);
+    }
+
+    #[test]
+    fn test_force_release_rejects_stale_lease() {
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+
+        let leaked = Switflake::new(1).expect("Failed to create Switflake");
+        let stale = leaked.lease();
+        mem::forget(leaked);
+        assert!(pool.force_release(stale));
+        assert!(!pool.is_held(stale.thread_id));
+
+        let mut holders = Vec::new();
+        while holders
+            .last()
+            .map_or(true, |s: &Switflake| s.lease().thread_id != stale.thread_id)
+        {
+            holders.push(Switflake::new(1).expect("Failed to create Switflake"));
+        }
+        // 같은 슬롯을 다시 받은 살아있는 생성기는 오래된 lease로 해제되면 안 됨
+        assert!(!pool.force_release(stale));
+        assert!(pool.is_held(stale.thread_id));
+    }
+
+    #[test]
+    fn test_new_blocking_waits_for_freed_slot() {
+        let _guard = serial_guard();
+        let mut held: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        assert_eq!(
+            Switflake::new_blocking(1, Some(std::time::Duration::from_millis(20))).err(),
+            Some(SwitflakeError::PoolExhausted)
+        );
+
+        let waiter = thread::spawn(|| Switflake::new_blocking(1, None).map(|s| s.lease()));
+        thread::sleep(std::time::Duration::from_millis(20));
+        // 슬롯 하나가 반환되면 대기 중인 생성이 깨어나야 함
+        let freed = held.pop().unwrap().lease().thread_id;
+        let lease = waiter
+            .join()
+            .expect("Thread join failed")
+            .expect("Blocking new should succeed");
+        assert_eq!(lease.thread_id, freed);
+    }
+
+    #[test]
+    fn test_max_waiters_fails_fast_when_queue_full() {
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+        let mut held: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        pool.set_max_waiters(Some(1));
+        // 단언이 실패해도 전역 풀의 제한은 되돌림
+        struct ResetMaxWaiters(&'static ThreadIdPool);
+        impl Drop for ResetMaxWaiters {
+            fn drop(&mut self) {
+                self.0.set_max_waiters(None);
+            }
+        }
+        let _reset = ResetMaxWaiters(pool);
+
+        let waiter = thread::spawn(|| Switflake::new_blocking(1, None).map(|s| s.lease()));
+        while pool.waiters.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        }
+        // 대기열이 찼으므로 타임아웃까지 기다리지 않고 바로 실패
+        let start = Instant::now();
+        assert_eq!(
+            Switflake::new_blocking(1, Some(Duration::from_secs(5))).err(),
+            Some(SwitflakeError::PoolExhausted)
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let freed = held.pop().unwrap().lease().thread_id;
+        let lease = waiter
+            .join()
+            .expect("Thread join failed")
+            .expect("Blocking new should succeed");
+        assert_eq!(lease.thread_id, freed);
+        assert_eq!(pool.waiters.load(Ordering::SeqCst), 0);
+    }
+
+    // The guard only serializes tests; this one runs on a single thread.
+    #[allow(clippy::await_holding_lock)]
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_new_async_wakes_when_slot_freed() {
+        let _guard = serial_guard();
+        let mut held: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Failed to create Switflake"))
+            .collect();
+        let waiter = tokio::spawn(Switflake::new_async(1));
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        // 다른 태스크가 슬롯을 반환하면 기다리던 태스크가 깨어남
+        let releaser = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            let freed = held.pop().unwrap().lease().thread_id;
+            (freed, held)
+        });
+        let swit = waiter
+            .await
+            .expect("Task panicked")
+            .expect("Async new should succeed");
+        let (freed, _held) = releaser.await.expect("Task panicked");
+        assert_eq!(swit.lease().thread_id, freed);
+
+        drop(swit);
+        let pool = ThreadIdPool::global();
+        let lease = pool.acquire_async().await;
+        assert_eq!(lease.thread_id, freed);
+        assert!(pool.force_release(lease));
+    }
+
+    #[test]
+    fn test_single_slot_pool_gives_counter_the_thread_bits() {
+        use crate::clock::MockClock;
+        use crate::Layout;
+
+        static POOL: ThreadIdPool<1> = ThreadIdPool::new();
+        let narrow = Layout {
+            node_bits: 15,
+            node_shift: 8,
+            thread_bits: 3,
+            counter_bits: 5,
+            ..Layout::default()
+        };
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .layout(narrow)
+            .pool(&POOL)
+            .clock(clock)
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = swit.layout();
+        assert_eq!((layout.thread_bits, layout.counter_bits), (0, 8));
+        assert_eq!(swit.config().pool_capacity, 1);
+        assert_eq!(
+            Switflake::builder()
+                .pool(&POOL)
+                .try_build()
+                .map(|s| s.is_none()),
+            Ok(true)
+        );
+
+        // 스레드 비트 3개가 카운터로 넘어가 밀리초당 31개 대신 255개
+        let mut ids = Vec::new();
+        while let Ok(id) = swit.generate_id() {
+            ids.push(id);
+        }
+        assert_eq!(ids.len(), 255);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_single_slot_pool_on_default_layout_uses_whole_sequence() {
+        use crate::clock::MockClock;
+        use crate::{compose, decompose};
+
+        static POOL: ThreadIdPool<1> = ThreadIdPool::new();
+        let mut swit = Switflake::builder()
+            .pool(&POOL)
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = swit.layout();
+        assert_eq!((layout.thread_bits, layout.counter_bits), (0, 11));
+        assert_eq!(layout.counter_mask(), 2047);
+
+        // 카운터가 8비트로 잘리지 않고 11비트 시퀀스 전체에 도달
+        let mut last = 0;
+        let mut count = 0;
+        while let Ok(id) = swit.generate_id() {
+            last = decompose(id, &layout).counter;
+            count += 1;
+        }
+        assert_eq!(count, 2047);
+        assert_eq!(last, 2046);
+        assert_eq!(swit.current_sequence(), 2047);
+        let top = compose(1, 0, 0, 2047, &layout).expect("Failed to compose ID");
+        assert_eq!(decompose(top, &layout).counter, 2047);
+    }
+
+    #[test]
+    fn test_slot_bitmap_is_narrowest_fitting_atomic() {
+        fn width<const N: usize>() -> usize
+        where
+            Slots<N>: SlotBitmap,
+        {
+            mem::size_of::<<Slots<N> as SlotBitmap>::Atomic>()
+        }
+        assert_eq!((width::<1>(), width::<8>()), (1, 1));
+        assert_eq!((width::<9>(), width::<16>()), (2, 2));
+        assert_eq!((width::<17>(), width::<32>()), (4, 4));
+        assert_eq!((width::<33>(), width::<64>()), (8, 8));
+
+        // 좁은 비트맵에서도 가득 참과 해제가 정상 동작
+        static POOL: ThreadIdPool<9> = ThreadIdPool::new();
+        let leases: Vec<SlotLease> = (0..9)
+            .map(|_| POOL.acquire().expect("Failed to acquire slot"))
+            .collect();
+        assert!(POOL.is_full());
+        assert_eq!(POOL.occupied_slots(), (0..9).collect::<Vec<u8>>());
+        assert!(POOL.force_release(leases[8]));
+        assert!(!POOL.is_full());
+        assert_eq!(POOL.try_acquire(), Some(8));
+    }
+
+    #[test]
+    fn test_is_held_past_capacity() {
+        static POOL: ThreadIdPool<64> = ThreadIdPool::new();
+        let _lease = POOL.acquire().expect("Failed to acquire slot");
+        assert!(POOL.is_held(0));
+        // 범위 밖 id는 시프트 오버플로 없이 false
+        assert!(!POOL.is_held(64));
+        assert!(!POOL.is_held(u8::MAX));
+        assert!(!ThreadIdPool::global().is_held(8));
+    }
+
+    #[test]
+    fn test_64_slot_pool() {
+        static POOL: ThreadIdPool<64> = ThreadIdPool::new();
+        assert_eq!(ThreadIdPool::<64>::THREAD_BITS, 6);
+        assert_eq!(<ThreadIdPool>::THREAD_BITS, 3);
+
+        let mut swits: Vec<Switflake> = (0..64)
+            .map(|_| {
+                Switflake::builder()
+                    .node_id(2)
+                    .pool(&POOL)
+                    .build()
+                    .expect("Failed to create Switflake")
+            })
+            .collect();
+        assert!(POOL.is_full());
+        assert!(Switflake::builder().pool(&POOL).build().is_err());
+        let layout = swits[0].layout();
+        assert_eq!((layout.thread_bits, layout.counter_bits), (6, 5));
+
+        let mut thread_ids: Vec<u8> = swits.iter().map(|s| s.lease().thread_id).collect();
+        thread_ids.sort_unstable();
+        assert_eq!(thread_ids, (0..64).collect::<Vec<u8>>());
+        let mut ids: Vec<u64> = swits
+            .iter_mut()
+            .map(|s| s.generate_id_blocking().expect("Failed to generate ID"))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 64);
+
+        drop(swits);
+        assert!(!(0..64).any(|id| POOL.is_held(id)));
+    }
+
+    #[test]
+    fn test_contended_acquire_hands_out_distinct_slots() {
+        assert_eq!(<ThreadIdPool>::new().ordering, PoolOrdering::AcqRel);
+        assert_eq!(PoolOrdering::default(), PoolOrdering::AcqRel);
+        for ordering in [PoolOrdering::SeqCst, PoolOrdering::AcqRel] {
+            contended_acquire_hands_out_distinct_slots(ordering);
+        }
+    }
+
+    fn contended_acquire_hands_out_distinct_slots(ordering: PoolOrdering) {
+        let pool = Arc::new(<ThreadIdPool>::new().with_ordering(ordering));
+        let owned = Arc::new(AtomicU8::new(0));
+        let barrier = Arc::new(Barrier::new(32));
+
+        let handles: Vec<_> = (0..32)
+            .map(|_| {
+                let (pool, owned, barrier) = (pool.clone(), owned.clone(), barrier.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    for _ in 0..200 {
+                        if let Ok(lease) = pool.acquire() {
+                            // 다른 스레드가 같은 슬롯을 동시에 가지면 안 됨
+                            let bit = 1 << lease.thread_id;
+                            let before = owned.fetch_or(bit, Ordering::SeqCst);
+                            assert_eq!(
+                                before & bit,
+                                0,
+                                "slot {} handed out twice",
+                                lease.thread_id
+                            );
+                            owned.fetch_and(!bit, Ordering::SeqCst);
+                            assert!(pool.force_release(lease));
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().expect("Thread join failed");
+        }
+        assert_eq!(pool.used_ids.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_reinit_after_fork() {
+        let parent = <ThreadIdPool>::new();
+        let held: Vec<SlotLease> = (0..3).map(|_| parent.acquire().unwrap()).collect();
+
+        let child = parent.fork_copy();
+        child.reinit_after_fork();
+
+        // 자식이 물려받은 생성기는 더 이상 슬롯을 해제할 수 없음
+        for lease in &held {
+            assert!(!child.force_release(*lease));
+            assert!(child.is_held(lease.thread_id));
+        }
+        let fresh: Vec<SlotLease> = (0..5).map(|_| child.acquire().unwrap()).collect();
+        for lease in &fresh {
+            assert!(held.iter().all(|h| h.thread_id != lease.thread_id));
+        }
+        assert!(child.acquire().is_err());
+
+        // 부모 쪽 상태는 영향을 받지 않음
+        for lease in held {
+            assert!(parent.force_release(lease));
+        }
+    }
+
+    #[test]
+    fn test_release_all_frees_every_slot() {
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+        for _ in 0..8 {
+            mem::forget(Switflake::new(1).expect("Failed to create Switflake"));
+        }
+        assert!(pool.is_full());
+
+        pool.release_all(ReleaseAllToken::i_understand_live_generators_will_mint_duplicates());
+        assert!((0..8).all(|id| !pool.is_held(id)));
+        let refill: Vec<Switflake> = (0..8)
+            .map(|_| Switflake::new(1).expect("Should succeed after release_all"))
+            .collect();
+
+        // 살아 있는 생성기의 Drop은 이미 비워진 슬롯을 건드리지 않음
+        pool.release_all_for_test();
+        let held_by_other = pool.acquire().unwrap();
+        drop(refill);
+        assert!(pool.is_held(held_by_other.thread_id));
+        assert!(pool.force_release(held_by_other));
+    }
+
+    #[test]
+    fn test_cooldown_keeps_dropped_slot_out_of_same_tick() {
+        static TWO: ThreadIdPool<2> = ThreadIdPool::new();
+        static ONE: ThreadIdPool<1> = ThreadIdPool::new();
+        TWO.set_reuse_cooldown(true);
+        ONE.set_reuse_cooldown(true);
+        let _guard = serial_guard();
+        let clock = crate::clock::MockClock::at(1_700_000_000_000);
+
+        let mut first = Switflake::builder()
+            .pool(&TWO)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let issued = first.generate_id().unwrap();
+        let slot = first.lease().thread_id;
+        drop(first);
+        // 같은 밀리초에는 방금 반환된 슬롯 대신 다른 슬롯을 받음
+        let mut second = Switflake::builder()
+            .pool(&TWO)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        assert_ne!(second.lease().thread_id, slot);
+        assert_ne!(second.generate_id().unwrap(), issued);
+        drop(second);
+
+        // 남은 슬롯이 쿨다운 중이면 받되 다음 밀리초까지 발급하지 않음
+        let mut first = Switflake::builder()
+            .pool(&ONE)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        let issued: Vec<u64> = (0..3).map(|_| first.generate_id().unwrap()).collect();
+        drop(first);
+        let mut second = Switflake::builder()
+            .pool(&ONE)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        assert_eq!(second.generate_id(), Err(SwitflakeError::SequenceExhausted));
+        clock.advance(1);
+        let id = second.generate_id().unwrap();
+        assert!(id > issued[2]);
+        drop(second);
+
+        // 쿨다운이 끝난 슬롯은 바로 다시 사용
+        clock.advance(1);
+        let mut third = Switflake::builder()
+            .pool(&ONE)
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        assert!(third.generate_id().is_ok());
+    }
+
+    #[test]
+    fn test_release_after_ignores_stale_and_earlier_ends() {
+        static POOL: ThreadIdPool<1> = ThreadIdPool::new();
+        POOL.set_reuse_cooldown(true);
+
+        let first = POOL.acquire().unwrap();
+        assert!(POOL.release_after(first, 2_000));
+        assert_eq!(POOL.cooling_until(0), 2_000);
+        // 이미 반환된 임대로는 쿨다운을 바꾸지 못함
+        let second = POOL.acquire().unwrap();
+        assert!(!POOL.release_after(first, 9_000));
+        assert_eq!(POOL.cooling_until(0), 2_000);
+        // 더 이른 종료 시각이 더 늦은 시각을 덮어쓰지 않음
+        assert!(POOL.release_after(second, 1_000));
+        assert_eq!(POOL.cooling_until(0), 2_000);
+    }
+
+    #[test]
+    fn test_high_watermark_fires_at_threshold() {
+        static POOL: ThreadIdPool<4> = ThreadIdPool::new();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        POOL.set_high_watermark(2, move |stats| sink.lock().unwrap().push(stats));
+
+        let first = POOL.acquire().unwrap();
+        assert!(reports.lock().unwrap().is_empty());
+        let second = POOL.acquire().unwrap();
+        let third = POOL.try_acquire_lease().unwrap();
+        assert_eq!(
+            *reports.lock().unwrap(),
+            [
+                PoolStats {
+                    used: 2,
+                    capacity: 4
+                },
+                PoolStats {
+                    used: 3,
+                    capacity: 4
+                },
+            ]
+        );
+
+        // 해제 후 임계값 아래에서는 다시 호출되지 않음
+        for lease in [first, second, third] {
+            assert!(POOL.force_release(lease));
+        }
+        let again = POOL.acquire().unwrap();
+        assert_eq!(reports.lock().unwrap().len(), 2);
+        assert!(POOL.force_release(again));
+    }
+
+    #[test]
+    fn test_occupied_slots_lists_held_ids() {
+        static POOL: ThreadIdPool<4> = ThreadIdPool::new();
+        assert!(POOL.occupied_slots().is_empty());
+        let leases: Vec<SlotLease> = (0..3).map(|_| POOL.acquire().unwrap()).collect();
+        assert!(POOL.force_release(leases[1]));
+        assert_eq!(POOL.occupied_slots(), [0, 2]);
+        for lease in [leases[0], leases[2]] {
+            assert!(POOL.force_release(lease));
+        }
+    }
+}
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test --lib --release loom_`; the
+// other tests use the pool outside a loom model and fail under that cfg.
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn loom_concurrent_acquires_get_distinct_slots() {
+        loom::model(|| {
+            let pool = Arc::new(ThreadIdPool::<2>::new());
+            let other = Arc::clone(&pool);
+            let handle = thread::spawn(move || other.acquire().unwrap());
+            let mine = pool.acquire().unwrap();
+            let theirs = handle.join().unwrap();
+            assert_ne!(mine.thread_id, theirs.thread_id);
+            assert!(pool.is_full());
+        });
+    }
+
+    #[test]
+    fn loom_release_racing_acquire_loses_no_slot() {
+        loom::model(|| {
+            let pool = Arc::new(ThreadIdPool::<2>::new());
+            let held = pool.acquire().unwrap();
+            let other = Arc::clone(&pool);
+            let handle = thread::spawn(move || other.force_release(held));
+            let taken = pool.acquire().unwrap();
+            assert!(handle.join().unwrap());
+            // 해제된 슬롯은 다시 비어 있고 새로 얻은 슬롯만 점유됨
+            assert!(pool.is_held(taken.thread_id));
+            let free = 1 - taken.thread_id;
+            assert!(!pool.is_held(free));
+            assert!(pool.force_release(taken));
+            assert!(!pool.is_full() && !pool.is_held(taken.thread_id));
+        });
+    }
+}
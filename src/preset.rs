@@ -0,0 +1,216 @@
+use crate::{Layout, TimeUnit};
+
+/// Well-known 64-bit ID schemes, described in terms of [`Layout`]. For
+/// 96-bit IDs that sort like MongoDB ObjectIds, see
+/// [`ObjectIdGenerator`](crate::ObjectIdGenerator).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutPreset {
+    /// This crate's default: milliseconds since the Unix epoch.
+    Switflake,
+    /// Twitter snowflake: 41-bit milliseconds since 2010-11-04, a 10-bit
+    /// machine id and a 12-bit sequence.
+    Twitter,
+    /// Sonyflake: 39-bit 10 ms ticks since 2014-09-01, an 8-bit sequence
+    /// and a 16-bit machine id in the low bits.
+    Sonyflake,
+    /// A 48-bit millisecond timestamp from the Unix epoch, good for about
+    /// 8,900 years instead of the default's 69, paid for with an 8-bit node
+    /// id (256 nodes) and a 5-bit counter: 31 IDs per millisecond per
+    /// generator, 248 per node with the global pool full.
+    LongLived,
+}
+
+impl LayoutPreset {
+    pub const ALL: [LayoutPreset; 4] = [
+        LayoutPreset::Switflake,
+        LayoutPreset::Twitter,
+        LayoutPreset::Sonyflake,
+        LayoutPreset::LongLived,
+    ];
+
+    /// The preset's fields. Sequence fields wider than a thread id plus an
+    /// 8-bit counter are split with the surplus in `thread_bits`.
+    pub const fn layout(self) -> Layout {
+        match self {
+            LayoutPreset::Switflake => Layout {
+                timestamp_bits: 41,
+                timestamp_shift: 23,
+                node_bits: 12,
+                node_shift: 11,
+                thread_bits: 3,
+                counter_bits: 8,
+                sequence_shift: 0,
+                versioned: false,
+                kinded: false,
+            },
+            LayoutPreset::Twitter => Layout {
+                timestamp_bits: 41,
+                timestamp_shift: 22,
+                node_bits: 10,
+                node_shift: 12,
+                thread_bits: 4,
+                counter_bits: 8,
+                sequence_shift: 0,
+                versioned: false,
+                kinded: false,
+            },
+            LayoutPreset::Sonyflake => Layout {
+                timestamp_bits: 39,
+                timestamp_shift: 24,
+                node_bits: 16,
+                node_shift: 0,
+                thread_bits: 0,
+                counter_bits: 8,
+                sequence_shift: 16,
+                versioned: false,
+                kinded: false,
+            },
+            LayoutPreset::LongLived => Layout {
+                timestamp_bits: 48,
+                timestamp_shift: 16,
+                node_bits: 8,
+                node_shift: 8,
+                thread_bits: 3,
+                counter_bits: 5,
+                sequence_shift: 0,
+                versioned: false,
+                kinded: false,
+            },
+        }
+    }
+
+    /// Unix time, in milliseconds, that timestamp 0 stands for.
+    pub const fn epoch(self) -> u64 {
+        match self {
+            LayoutPreset::Switflake | LayoutPreset::LongLived => 0,
+            LayoutPreset::Twitter => 1_288_834_974_657,
+            LayoutPreset::Sonyflake => 1_409_529_600_000,
+        }
+    }
+
+    pub const fn time_unit(self) -> TimeUnit {
+        match self {
+            LayoutPreset::Sonyflake => TimeUnit::TenMilliseconds,
+            _ => TimeUnit::Milliseconds,
+        }
+    }
+
+    /// Unix milliseconds of `id`'s timestamp if it was minted under this
+    /// preset.
+    fn unix_millis_of(self, id: u64) -> u64 {
+        let ticks = self.layout().timestamp_of(id);
+        self.epoch() + ticks * self.time_unit().as_micros() / 1_000
+    }
+}
+
+// 2010-01-01, 2015-01-01, 2030-01-01 and 2040-01-01 in Unix milliseconds.
+const IMPLAUSIBLE_BEFORE: u64 = 1_262_304_000_000;
+const PLAUSIBLE_FROM: u64 = 1_420_070_400_000;
+const PLAUSIBLE_UNTIL: u64 = 1_893_456_000_000;
+const IMPLAUSIBLE_AFTER: u64 = 2_208_988_800_000;
+
+/// Guesses which preset minted `id`, returning every preset with a
+/// confidence in `0.0..=1.0`, most likely first. The confidences sum to 1
+/// unless no preset fits at all, in which case they are all 0.
+///
+/// This is a heuristic, not a decoder: it only checks that the timestamp
+/// each preset would read lands between 2015 and 2030, fading out towards
+/// 2010 and 2040, and that no bit outside the preset's fields is set. IDs
+/// that happen to decode to plausible times under several presets get
+/// split confidences, and very young or very old IDs are easily misread.
+pub fn detect_layout(id: u64) -> Vec<(LayoutPreset, f64)> {
+    let mut scores: Vec<(LayoutPreset, f64)> = LayoutPreset::ALL
+        .iter()
+        .map(|&preset| (preset, plausibility(preset, id)))
+        .collect();
+    let total: f64 = scores.iter().map(|(_, score)| score).sum();
+    if total > 0.0 {
+        for (_, score) in &mut scores {
+            *score /= total;
+        }
+    }
+    // 안정 정렬이므로 동점이면 ALL 순서 유지
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores
+}
+
+fn plausibility(preset: LayoutPreset, id: u64) -> f64 {
+    if id & preset.layout().reserved_mask() != 0 {
+        return 0.0;
+    }
+    let millis = preset.unix_millis_of(id);
+    // 구간 양 끝에서 선형으로 0까지 감소
+    let fraction = |part: u64, whole: u64| part as f64 / whole as f64;
+    match millis {
+        m if m <= IMPLAUSIBLE_BEFORE || m >= IMPLAUSIBLE_AFTER => 0.0,
+        m if m < PLAUSIBLE_FROM => {
+            fraction(m - IMPLAUSIBLE_BEFORE, PLAUSIBLE_FROM - IMPLAUSIBLE_BEFORE)
+        }
+        m if m > PLAUSIBLE_UNTIL => {
+            fraction(IMPLAUSIBLE_AFTER - m, IMPLAUSIBLE_AFTER - PLAUSIBLE_UNTIL)
+        }
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[test]
+    fn test_twitter_snowflake_detected() {
+        // 2020-01-01 00:00:14 UTC에 작성된 트윗 ID
+        let ranked = detect_layout(1_212_161_572_561_461_248);
+        assert_eq!(ranked[0].0, LayoutPreset::Twitter);
+        assert!(ranked[0].1 > 0.5);
+        assert!((ranked.iter().map(|(_, c)| c).sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_own_ids_detected() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::builder()
+            .clock(MockClock::at(1_700_000_000_000))
+            .build()
+            .expect("Failed to create Switflake");
+        let id = swit.generate_id().expect("Failed to generate ID");
+        assert_eq!(detect_layout(id)[0].0, LayoutPreset::Switflake);
+
+        // 2023-11-14의 Sonyflake ID (10ms 단위, 시퀀스 3, 머신 77)
+        let ticks = (1_700_000_000_000 - LayoutPreset::Sonyflake.epoch()) / 10;
+        let sonyflake = (ticks << 24) | (3 << 16) | 77;
+        assert_eq!(detect_layout(sonyflake)[0].0, LayoutPreset::Sonyflake);
+    }
+
+    #[test]
+    fn test_long_lived_lasts_millennia() {
+        let _guard = serial_guard();
+        let preset = LayoutPreset::LongLived;
+        let now = 1_700_000_000_000;
+        let mut swit = Switflake::builder()
+            .node_id(200)
+            .layout(preset.layout())
+            .epoch(preset.epoch())
+            .clock(MockClock::at(now))
+            .build()
+            .expect("Failed to create Switflake");
+
+        // 48비트 밀리초는 약 8900년, 1970년부터 이미 53년 경과
+        let years = swit.time_until_wraparound().unwrap().as_secs() / (365 * 24 * 3600);
+        assert!((8_800..8_900).contains(&years), "{} years", years);
+
+        let ids: Vec<u64> = (0..31).map(|_| swit.generate_id().unwrap()).collect();
+        assert_eq!(
+            swit.generate_id(),
+            Err(crate::SwitflakeError::SequenceExhausted)
+        );
+        for id in ids {
+            let parts = crate::decompose(id, &preset.layout());
+            assert_eq!((parts.timestamp, parts.node_id), (now, 200));
+            assert_eq!(preset.unix_millis_of(id), now);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Clock, Switflake, SwitflakeBuilder, SwitflakeError, ThreadIdPool};
+
+/// How urgently a caller needs a generator; see [`PriorityPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    High,
+}
+
+/// Admission policy over the global [`ThreadIdPool`] that keeps some slots
+/// back for high-priority callers.
+///
+/// Low-priority construction fails with [`SwitflakeError::PoolExhausted`]
+/// once only the reserved number of slots is left, while high-priority
+/// construction may take any free slot. The reservation is a count of free
+/// slots rather than a fixed set, so whichever slot a generator gives back
+/// counts towards it again.
+///
+/// Only generators created through a `PriorityPool` respect the reservation;
+/// [`Switflake::new`] and [`SwitflakeBuilder::build`] may still take the
+/// reserved slots.
+#[derive(Debug, Clone, Copy)]
+pub struct PriorityPool {
+    reserved_high: u32,
+}
+
+impl PriorityPool {
+    /// Keeps `reserved_high` slots for [`Priority::High`] callers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reserved_high` exceeds [`ThreadIdPool::CAPACITY`].
+    pub fn new(reserved_high: usize) -> Self {
+        assert!(
+            reserved_high <= <ThreadIdPool>::CAPACITY,
+            "cannot reserve {} of {} slots",
+            reserved_high,
+            <ThreadIdPool>::CAPACITY
+        );
+        PriorityPool {
+            reserved_high: reserved_high as u32,
+        }
+    }
+
+    pub fn new_priority(
+        &self,
+        node_id: u64,
+        priority: Priority,
+    ) -> Result<Switflake, SwitflakeError> {
+        self.build(SwitflakeBuilder::new().node_id(node_id), priority)
+    }
+
+    /// Builds `builder` under this pool's reservation.
+    pub fn build<C: Clock>(
+        &self,
+        builder: SwitflakeBuilder<C>,
+        priority: Priority,
+    ) -> Result<Switflake<C>, SwitflakeError> {
+        let keep_free = match priority {
+            Priority::Low => self.reserved_high,
+            Priority::High => 0,
+        };
+        builder.build_keeping_free(keep_free)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_high_priority_gets_reserved_slots() {
+        let _guard = serial_guard();
+        let pool = PriorityPool::new(2);
+        let mut low: Vec<_> = (0..6)
+            .map(|_| {
+                pool.new_priority(1, Priority::Low)
+                    .expect("Low slot available")
+            })
+            .collect();
+        assert_eq!(
+            pool.new_priority(1, Priority::Low).err(),
+            Some(SwitflakeError::PoolExhausted)
+        );
+
+        let high = pool.new_priority(1, Priority::High).expect("Reserved slot");
+        let high2 = pool.new_priority(1, Priority::High).expect("Reserved slot");
+        assert!(ThreadIdPool::global().is_full());
+
+        // 고우선순위 슬롯이 반환되면 다시 예약분으로 돌아감
+        drop(high);
+        assert!(pool.new_priority(1, Priority::Low).is_err());
+        let _high3 = pool.new_priority(1, Priority::High).expect("Reserved slot");
+
+        // 예약분보다 많은 슬롯이 비어야 저우선순위가 다시 쓸 수 있음
+        low.pop();
+        drop(_high3);
+        assert!(pool.new_priority(1, Priority::Low).is_err());
+        drop(high2);
+        assert!(pool.new_priority(1, Priority::Low).is_ok());
+    }
+}
@@ -0,0 +1,53 @@
+//! Conversions for carrying IDs in protobuf messages.
+//!
+//! `uint64` and `fixed64` hold every ID exactly. `sfixed64` and `int64` also
+//! keep all 64 bits once reinterpreted as a signed value, but IDs with the top
+//! bit set come out negative. With the default layout and the Unix epoch that
+//! is every ID minted after 2004, so signed fields neither sort nor display
+//! like the IDs; prefer `uint64`/`fixed64` in new schemas and keep signed
+//! fields for compatibility with existing ones. A later epoch (see
+//! [`SwitflakeBuilder::epoch`](crate::SwitflakeBuilder::epoch)) keeps the top
+//! bit clear for decades, making signed fields orderable too.
+
+/// Value for a `uint64` or `fixed64` field. This is the ID itself, spelled
+/// out so call sites say which wire type they target.
+#[inline]
+pub const fn to_proto_uint64(id: u64) -> u64 {
+    id
+}
+
+/// Inverse of [`to_proto_uint64`].
+#[inline]
+pub const fn from_proto_uint64(value: u64) -> u64 {
+    value
+}
+
+/// Value for an `sfixed64` or `int64` field: the ID's bits reinterpreted as
+/// two's complement.
+#[inline]
+pub const fn to_proto_sfixed64(id: u64) -> i64 {
+    id as i64
+}
+
+/// Inverse of [`to_proto_sfixed64`].
+#[inline]
+pub const fn from_proto_sfixed64(value: i64) -> u64 {
+    value as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_both_wire_types() {
+        let default_id = (1_700_000_000_000u64 << 23) | (42 << 11) | 7;
+        for id in [0, 1, default_id, i64::MAX as u64, 1 << 63, u64::MAX] {
+            assert_eq!(from_proto_uint64(to_proto_uint64(id)), id);
+            assert_eq!(from_proto_sfixed64(to_proto_sfixed64(id)), id);
+        }
+        // 현재 시각의 기본 레이아웃 ID는 부호 비트가 켜져 있음
+        assert!(to_proto_sfixed64(default_id) < 0);
+        assert_eq!(to_proto_sfixed64(42), 42);
+    }
+}
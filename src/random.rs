@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+
+use crate::{Clock, Layout, SwitflakeError, SystemClock};
+
+// Draws per ID before giving up on the current millisecond.
+const MAX_DRAWS: u32 = 32;
+
+/// Uniform-enough random value in `0..bound` from the OS generator. The
+/// modulo bias is negligible for the small bounds used here.
+pub(crate) fn below(bound: u64) -> Result<u64, SwitflakeError> {
+    if bound <= 1 {
+        return Ok(0);
+    }
+    getrandom::u64()
+        .map(|n| n % bound)
+        .map_err(|_| SwitflakeError::EntropyUnavailable)
+}
+
+/// Time-sortable IDs whose bits below the timestamp, node and sequence
+/// fields alike, are random rather than a counter, so IDs don't give away
+/// how many were generated or where.
+///
+/// IDs from one generator are unique: every ID of the current millisecond
+/// is remembered and a colliding draw is redrawn. Random values collide
+/// long before the field is full, so a millisecond is given up as
+/// exhausted once half its values are taken, and gets noticeably slower
+/// well before that; with [`DEFAULT_LAYOUT`](crate::DEFAULT_LAYOUT) that is
+/// 2^22 IDs, but expect collisions to need redraws after a couple of
+/// thousand. IDs from separate generators are only unlikely, not
+/// guaranteed, to differ, and come in no particular order within a
+/// millisecond.
+#[derive(Debug)]
+pub struct RandomIdGenerator<C = SystemClock> {
+    layout: Layout,
+    epoch: u64,
+    clock: C,
+    last_timestamp: u64,
+    // IDs already issued in `last_timestamp`.
+    seen: HashSet<u64>,
+}
+
+impl RandomIdGenerator {
+    /// A generator with the default layout, timestamps from the Unix epoch
+    /// and the system clock.
+    pub fn new() -> Self {
+        RandomIdGenerator::from_timestamp_and_random(Layout::default(), 0, SystemClock)
+            .expect("default layout is valid")
+    }
+}
+
+impl Default for RandomIdGenerator {
+    fn default() -> Self {
+        RandomIdGenerator::new()
+    }
+}
+
+impl<C: Clock> RandomIdGenerator<C> {
+    /// Builds a generator that puts millisecond timestamps from `epoch`
+    /// (Unix milliseconds) into `layout`'s timestamp field and fills every
+    /// other field at random. Fails with
+    /// [`SwitflakeError::InvalidLayout`] for a layout no
+    /// [`Switflake`](crate::Switflake) could use either.
+    pub fn from_timestamp_and_random(
+        layout: Layout,
+        epoch: u64,
+        clock: C,
+    ) -> Result<Self, SwitflakeError> {
+        layout.check_fields()?;
+        Ok(RandomIdGenerator {
+            layout,
+            epoch,
+            clock,
+            last_timestamp: 0,
+            seen: HashSet::new(),
+        })
+    }
+
+    /// Generates the next ID, failing with
+    /// [`SwitflakeError::SequenceExhausted`] when this millisecond is
+    /// crowded enough that redraws keep colliding, and with
+    /// [`SwitflakeError::EntropyUnavailable`] if the OS generator fails.
+    #[must_use = "a dropped ID still counts against this millisecond"]
+    pub fn next_id(&mut self) -> Result<u64, SwitflakeError> {
+        let timestamp = self
+            .clock
+            .now_millis()?
+            .checked_sub(self.epoch)
+            .ok_or(SwitflakeError::ClockMovedBackwards)?;
+        if timestamp > self.layout.timestamp_mask() {
+            return Err(SwitflakeError::TimestampOverflow);
+        }
+        if timestamp < self.last_timestamp {
+            return Err(SwitflakeError::ClockMovedBackwards);
+        }
+        if timestamp > self.last_timestamp {
+            self.last_timestamp = timestamp;
+            self.seen.clear();
+        }
+
+        let random_mask = !self.layout.reserved_mask()
+            & !(self.layout.timestamp_mask() << self.layout.timestamp_shift);
+        let capacity = 1u64
+            .checked_shl(random_mask.count_ones())
+            .unwrap_or(u64::MAX);
+        if self.seen.len() as u64 >= capacity / 2 {
+            return Err(SwitflakeError::SequenceExhausted);
+        }
+        let prefix = timestamp << self.layout.timestamp_shift;
+        for _ in 0..MAX_DRAWS {
+            let random = getrandom::u64().map_err(|_| SwitflakeError::EntropyUnavailable)?;
+            let id = prefix | (random & random_mask);
+            if self.seen.insert(id) {
+                return Ok(id);
+            }
+        }
+        Err(SwitflakeError::SequenceExhausted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomIdGenerator;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{Layout, Switflake, SwitflakeError};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_random_start_differs_and_stays_unique() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let build = || {
+            Switflake::builder()
+                .clock(clock.clone())
+                .random_start_sequence(true)
+                .build()
+                .expect("Failed to create Switflake")
+        };
+        let (mut a, mut b) = (build(), build());
+
+        let mut ids = HashSet::new();
+        let mut starts_differ = false;
+        for _ in 0..16 {
+            let (first_a, parts_a) = a.generate_decomposed().expect("Failed to generate ID");
+            let (first_b, parts_b) = b.generate_decomposed().expect("Failed to generate ID");
+            starts_differ |= parts_a.counter != parts_b.counter;
+            assert!(ids.insert(first_a) && ids.insert(first_b));
+
+            let mut last = first_a;
+            while let Ok(id) = a.generate_id() {
+                assert!(id > last, "IDs not monotonic");
+                assert!(ids.insert(id), "Duplicate ID found: {}", id);
+                last = id;
+            }
+            while let Ok(id) = b.generate_id() {
+                assert!(ids.insert(id), "Duplicate ID found: {}", id);
+            }
+            clock.advance(1);
+        }
+        // 16번의 밀리초 전환에서 모두 같은 시작값일 확률은 무시할 수준
+        assert!(starts_differ, "Random start counters never differed");
+    }
+
+    #[test]
+    fn test_random_ids_unique_within_one_millisecond() {
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut generator =
+            RandomIdGenerator::from_timestamp_and_random(Layout::default(), 0, clock.clone())
+                .expect("Failed to create generator");
+
+        let mut ids = HashSet::new();
+        let mut sequential = 0;
+        let mut last = 0;
+        for _ in 0..10_000 {
+            let id = generator.next_id().expect("Failed to generate ID");
+            assert_eq!(Layout::default().timestamp_of(id), 1_700_000_000_000);
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+            sequential += (id == last + 1) as u32;
+            last = id;
+        }
+        // 카운터처럼 1씩 증가하는 경우는 거의 없어야 함
+        assert!(sequential < 10, "IDs look sequential");
+
+        clock.advance(1);
+        let id = generator.next_id().expect("Failed to generate ID");
+        assert!(ids.iter().all(|earlier| *earlier < id));
+        clock.set(1_699_999_999_999);
+        assert_eq!(
+            generator.next_id(),
+            Err(SwitflakeError::ClockMovedBackwards)
+        );
+    }
+
+    #[test]
+    fn test_random_ids_exhaust_narrow_field() {
+        let layout = Layout {
+            timestamp_bits: 41,
+            timestamp_shift: 4,
+            node_bits: 1,
+            node_shift: 3,
+            thread_bits: 1,
+            counter_bits: 2,
+            sequence_shift: 0,
+            versioned: false,
+            kinded: false,
+        };
+        let mut generator =
+            RandomIdGenerator::from_timestamp_and_random(layout, 0, MockClock::at(5))
+                .expect("Failed to create generator");
+        // 16개 중 절반까지만 발급
+        let ids: HashSet<u64> = (0..8).map(|_| generator.next_id().unwrap()).collect();
+        assert_eq!(ids.len(), 8);
+        assert_eq!(generator.next_id(), Err(SwitflakeError::SequenceExhausted));
+    }
+}
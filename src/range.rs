@@ -0,0 +1,202 @@
+use std::ops::RangeInclusive;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::layout::NAMESPACE_BIT;
+use crate::{Layout, Switflake, SwitflakeError};
+
+impl<C> Switflake<C> {
+    /// The smallest and largest IDs this generator's configuration can mint
+    /// during `[start, end)`, for turning a time-window query into a range
+    /// scan over stored IDs.
+    ///
+    /// Every tick that overlaps the window is included, so the range may
+    /// take in IDs from up to one tick before `start` and after `end`. IDs
+    /// from any node, thread and counter fall inside it, but only those of
+    /// this generator's namespace bit. Parts of the
+    /// window before the epoch or past the end of the timestamp field are
+    /// clipped, and a window with nothing left, such as `end <= start`,
+    /// gives an empty range.
+    ///
+    /// Fails with [`SwitflakeError::InvalidLayout`] when IDs don't sort by
+    /// time: the timestamp isn't the most significant field, or IDs are
+    /// obfuscated. Times before the Unix epoch fail with
+    /// [`SwitflakeError::ClockMovedBackwards`].
+    pub fn id_range_for_time(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Result<RangeInclusive<u64>, SwitflakeError> {
+        let layout = &self.layout;
+        // 네임스페이스 비트가 켜져 있으면 타임스탬프는 그 바로 아래까지
+        check_time_ordered(layout, if self.namespace_bit { 63 } else { 64 })?;
+        if self.obfuscation_key.is_some() {
+            return Err(SwitflakeError::InvalidLayout(
+                "obfuscated IDs are not ordered by time",
+            ));
+        }
+
+        let unit = self.time_unit.as_micros();
+        // 끝은 배타적이므로 end 직전 마이크로초가 속한 틱까지
+        let first = (micros(start)? / unit).saturating_sub(self.epoch_ticks());
+        let last = match micros(end)?.checked_sub(1) {
+            Some(end) => (end / unit).checked_sub(self.epoch_ticks()),
+            None => None,
+        };
+        let last = match last {
+            Some(last) if last >= first && first <= layout.timestamp_mask() => {
+                last.min(layout.timestamp_mask())
+            }
+            #[allow(clippy::reversed_empty_ranges)]
+            _ => return Ok(1..=0),
+        };
+
+        let (low, high) = if self.reverse_order {
+            (self.timestamp_field(last), self.timestamp_field(first))
+        } else {
+            (first, last)
+        };
+        let below_timestamp = (1u64 << layout.timestamp_shift) - 1;
+        let namespace = if self.namespace_bit { NAMESPACE_BIT } else { 0 };
+        Ok((namespace | low << layout.timestamp_shift)
+            ..=(namespace | high << layout.timestamp_shift | below_timestamp))
+    }
+}
+
+/// The smallest ID any generator with `layout` and `epoch` (Unix
+/// milliseconds) can mint in the millisecond containing `time`, without
+/// needing such a generator; see [`Switflake::id_range_for_time`] for the
+/// range over a whole window. Assumes millisecond ticks, no namespace bit
+/// and no reverse order.
+///
+/// Fails with [`SwitflakeError::InvalidLayout`] when the timestamp isn't
+/// the most significant field, with
+/// [`SwitflakeError::ClockMovedBackwards`] for a time before the epoch and
+/// with [`SwitflakeError::TimestampOverflow`] for one past the end of the
+/// timestamp field.
+pub fn min_id_at(time: SystemTime, epoch: u64, layout: &Layout) -> Result<u64, SwitflakeError> {
+    Ok(timestamp_at(time, epoch, layout)? << layout.timestamp_shift)
+}
+
+/// The largest ID counterpart of [`min_id_at`], failing the same way.
+pub fn max_id_at(time: SystemTime, epoch: u64, layout: &Layout) -> Result<u64, SwitflakeError> {
+    let below_timestamp = (1u64 << layout.timestamp_shift) - 1;
+    Ok(min_id_at(time, epoch, layout)? | below_timestamp)
+}
+
+fn timestamp_at(time: SystemTime, epoch: u64, layout: &Layout) -> Result<u64, SwitflakeError> {
+    check_time_ordered(layout, 64)?;
+    let timestamp = (micros(time)? / 1_000)
+        .checked_sub(epoch)
+        .ok_or(SwitflakeError::ClockMovedBackwards)?;
+    if timestamp > layout.timestamp_mask() {
+        return Err(SwitflakeError::TimestampOverflow);
+    }
+    Ok(timestamp)
+}
+
+/// Fails unless the timestamp field ends at bit `top` with every other
+/// field below it, so IDs sort by time.
+fn check_time_ordered(layout: &Layout, top: u32) -> Result<(), SwitflakeError> {
+    if layout.timestamp_shift + layout.timestamp_bits != top
+        || layout.node_shift > layout.timestamp_shift
+        || layout.sequence_shift > layout.timestamp_shift
+    {
+        return Err(SwitflakeError::InvalidLayout(
+            "timestamp is not the most significant field",
+        ));
+    }
+    Ok(())
+}
+
+fn micros(time: SystemTime) -> Result<u64, SwitflakeError> {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .map_err(|_| SwitflakeError::ClockMovedBackwards)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{max_id_at, min_id_at};
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{Layout, Switflake, SwitflakeError};
+
+    #[test]
+    fn test_free_bounds_match_generator_range() {
+        let _guard = serial_guard();
+        let epoch = 1_600_000_000_000;
+        let swit = Switflake::builder()
+            .epoch(epoch)
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = Layout::default();
+        for offset in [0, 1, 999, 86_400_000] {
+            let time = std::time::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000 + offset);
+            let range = swit
+                .id_range_for_time(time, time + Duration::from_millis(1))
+                .expect("Failed to compute range");
+            assert_eq!(min_id_at(time, epoch, &layout), Ok(*range.start()));
+            assert_eq!(max_id_at(time, epoch, &layout), Ok(*range.end()));
+        }
+        // 에포크 이전은 오류
+        let before = std::time::UNIX_EPOCH + Duration::from_millis(epoch - 1);
+        assert_eq!(
+            min_id_at(before, epoch, &layout),
+            Err(SwitflakeError::ClockMovedBackwards)
+        );
+    }
+
+    #[test]
+    fn test_generated_id_falls_in_window() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        for reverse in [false, true] {
+            let mut swit = Switflake::builder()
+                .node_id(4095)
+                .epoch(1_600_000_000_000)
+                .reverse_order(reverse)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake");
+            let id = swit.generate_id().expect("Failed to generate ID");
+            let created = swit.timestamp(id);
+
+            let range = swit
+                .id_range_for_time(created, created + Duration::from_millis(1))
+                .expect("Failed to compute range");
+            assert!(range.contains(&id));
+            let wide = swit
+                .id_range_for_time(
+                    created - Duration::from_secs(60),
+                    created + Duration::from_secs(60),
+                )
+                .expect("Failed to compute range");
+            assert!(wide.contains(&id));
+
+            // 창 밖의 ID는 범위에 들어가지 않음
+            clock.advance(1);
+            let later = swit.generate_id().expect("Failed to generate ID");
+            assert!(!range.contains(&later));
+            assert!(wide.contains(&later));
+            assert!(swit.id_range_for_time(created, created).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_unordered_ids_rejected() {
+        let _guard = serial_guard();
+        let swit = Switflake::builder()
+            .field_offsets(0, 41, 53)
+            .build()
+            .expect("Failed to create Switflake");
+        let now = std::time::SystemTime::now();
+        assert_eq!(
+            swit.id_range_for_time(now, now + Duration::from_secs(1)),
+            Err(SwitflakeError::InvalidLayout(
+                "timestamp is not the most significant field"
+            ))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::VecDeque;
+
+use crate::SwitflakeError;
+
+const WINDOW_MILLIS: u64 = 1_000;
+
+/// Counts the IDs issued over the last second, in per-millisecond buckets.
+#[derive(Debug)]
+pub(crate) struct RateBreaker {
+    limit: u64,
+    // (millisecond, IDs issued in it), oldest first.
+    buckets: VecDeque<(u64, u64)>,
+    total: u64,
+}
+
+impl RateBreaker {
+    pub(crate) fn new(limit: u64) -> Self {
+        RateBreaker {
+            limit,
+            buckets: VecDeque::new(),
+            total: 0,
+        }
+    }
+
+    /// Fails with [`SwitflakeError::RateExceeded`] if another ID at
+    /// `now_millis` would put more than the limit in the window ending then.
+    pub(crate) fn check(&mut self, now_millis: u64) -> Result<(), SwitflakeError> {
+        while let Some(&(millis, count)) = self.buckets.front() {
+            if millis + WINDOW_MILLIS > now_millis {
+                break;
+            }
+            self.total -= count;
+            self.buckets.pop_front();
+        }
+        if self.total >= self.limit {
+            return Err(SwitflakeError::RateExceeded);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn record(&mut self, now_millis: u64) {
+        match self.buckets.back_mut() {
+            Some((millis, count)) if *millis == now_millis => *count += 1,
+            _ => self.buckets.push_back((now_millis, 1)),
+        }
+        self.total += 1;
+    }
+}
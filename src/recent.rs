@@ -0,0 +1,86 @@
+use crate::SwitflakeError;
+
+/// Fixed-size ring of the most recently issued IDs.
+#[derive(Debug)]
+pub(crate) struct RecentIds {
+    ids: Box<[u64]>,
+    len: usize,
+    next: usize,
+}
+
+impl RecentIds {
+    pub(crate) fn new(capacity: usize) -> Self {
+        RecentIds {
+            ids: vec![0; capacity].into_boxed_slice(),
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Records `id`, or reports it if it is already in the ring: as an error
+    /// with the `paranoid` feature, as a debug assertion otherwise.
+    pub(crate) fn check_and_record(&mut self, id: u64) -> Result<(), SwitflakeError> {
+        if self.ids.is_empty() {
+            return Ok(());
+        }
+        let seen = self.ids[..self.len].contains(&id);
+        #[cfg(feature = "paranoid")]
+        if seen {
+            return Err(SwitflakeError::DuplicateId(id));
+        }
+        debug_assert!(!seen, "switflake: about to issue duplicate ID {}", id);
+
+        self.ids[self.next] = id;
+        self.next = (self.next + 1) % self.ids.len();
+        self.len = (self.len + 1).min(self.ids.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    // 같은 타임스탬프를 되풀이하는 시계 + 강제 카운터 리셋으로 중복을 만듦
+    fn buggy_generator() -> (Switflake<MockClock>, u64) {
+        let mut swit = Switflake::builder()
+            .clock(MockClock::at(1_700_000_000_000))
+            .recent_ids_guard(16)
+            .build()
+            .expect("Failed to create Switflake");
+        let first = swit.generate_id().expect("Failed to generate ID");
+        swit.generate_id().expect("Failed to generate ID");
+        swit.local_counter = 0;
+        (swit, first)
+    }
+
+    #[test]
+    #[cfg(not(feature = "paranoid"))]
+    #[should_panic(expected = "duplicate ID")]
+    fn test_duplicate_panics_in_debug() {
+        let _guard = serial_guard();
+        let (mut swit, _) = buggy_generator();
+        let _ = swit.generate_id();
+    }
+
+    #[test]
+    #[cfg(feature = "paranoid")]
+    fn test_duplicate_errors_when_paranoid() {
+        let _guard = serial_guard();
+        let (mut swit, first) = buggy_generator();
+        assert_eq!(swit.generate_id(), Err(SwitflakeError::DuplicateId(first)));
+    }
+
+    #[test]
+    fn test_ring_forgets_oldest() {
+        let mut recent = RecentIds::new(2);
+        assert!(recent.check_and_record(1).is_ok());
+        assert!(recent.check_and_record(2).is_ok());
+        assert!(recent.check_and_record(3).is_ok());
+        // 1은 밀려났으므로 다시 허용됨
+        assert!(recent.check_and_record(1).is_ok());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Clock, Layout, Switflake, SwitflakeError};
+
+impl<C: Clock> Switflake<C> {
+    /// Switches a live generator to `new_layout` and `new_epoch` while
+    /// keeping its pool slot.
+    ///
+    /// IDs from one generator always increase, and this must stay true
+    /// across the switch: the change is refused with
+    /// [`SwitflakeError::InvalidLayout`] if the next ID under the new
+    /// configuration would not sort after the last one already issued. In
+    /// practice that means the epoch may only move earlier and the timestamp
+    /// field may only move up or widen. The node id must fit the new node
+    /// field, and on error nothing is changed.
+    ///
+    /// Ordering is checked on plain IDs; obfuscated IDs never sorted within a
+    /// millisecond to begin with.
+    pub fn reconfigure(
+        &mut self,
+        new_layout: Layout,
+        new_epoch: u64,
+    ) -> Result<(), SwitflakeError> {
+        new_layout.check_fields()?;
+        if self.adaptive_node_bits > 0 && new_layout.node_bits != self.layout.node_bits {
+            return Err(SwitflakeError::InvalidLayout(
+                "adaptive counter needs the node field width unchanged",
+            ));
+        }
+        if self.adaptive_node_bits > 0 && new_layout.versioned {
+            return Err(SwitflakeError::InvalidLayout(
+                "adaptive counter cannot borrow from a versioned node field",
+            ));
+        }
+        if self.adaptive_node_bits > 0 && new_layout.kinded {
+            return Err(SwitflakeError::InvalidLayout(
+                "adaptive counter cannot borrow from a kinded node field",
+            ));
+        }
+        if self.version != 0 && !new_layout.versioned {
+            return Err(SwitflakeError::InvalidLayout(
+                "version needs a versioned layout",
+            ));
+        }
+        if self.node_id & !crate::layout::mask(new_layout.node_id_bits()) != 0 {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        if self.thread_id as u64 & !new_layout.thread_mask() != 0 {
+            return Err(SwitflakeError::FieldOverflow("thread"));
+        }
+        let new_epoch_ticks = self.time_unit.ticks_in_millis(new_epoch);
+        let timestamp = self
+            .time_unit
+            .now(&self.clock)?
+            .checked_sub(new_epoch_ticks)
+            .ok_or(SwitflakeError::ClockMovedBackwards)?;
+        if timestamp > new_layout.timestamp_mask() {
+            return Err(SwitflakeError::TimestampOverflow);
+        }
+
+        let issued_any = self.last_timestamp != 0 || self.local_counter != 0;
+        if issued_any {
+            let last_id = self.layout.pack(
+                self.last_timestamp,
+                self.node_field(),
+                self.thread_id,
+                self.local_counter.saturating_sub(1),
+            );
+            let node_field = self.node_id | new_layout.version_field(self.version);
+            let next_id = new_layout.pack(timestamp, node_field, self.thread_id, 0);
+            if next_id <= last_id {
+                return Err(SwitflakeError::InvalidLayout(
+                    "reconfiguration would break ID ordering",
+                ));
+            }
+        }
+
+        // 같은 밀리초가 이어질 수 있으므로 벽시계 기준으로 상태를 옮김
+        let last_ticks = self.epoch_ticks() + self.last_timestamp;
+        self.last_timestamp = last_ticks.saturating_sub(new_epoch_ticks);
+        self.local_counter = self
+            .local_counter
+            .min(new_layout.counter_mask() as crate::layout::Counter);
+        self.layout = new_layout;
+        self.epoch = new_epoch;
+        self.refresh_prefix();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_safe_reconfigure_keeps_order() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(7)
+            .epoch(1_600_000_000_000)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let lease = swit.lease();
+        let before = swit.generate_id().expect("Failed to generate ID");
+
+        // epoch를 앞당기면 타임스탬프가 커지므로 안전
+        swit.reconfigure(Layout::default(), 1_500_000_000_000)
+            .expect("Safe reconfigure rejected");
+        assert_eq!(swit.lease(), lease);
+        assert_eq!(swit.epoch(), 1_500_000_000_000);
+        let after = swit.generate_id().expect("Failed to generate ID");
+        assert!(after > before);
+    }
+
+    #[test]
+    fn test_ordering_breaking_reconfigure_rejected() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        swit.generate_id().expect("Failed to generate ID");
+
+        let lower_timestamp = Layout {
+            timestamp_bits: 42,
+            timestamp_shift: 22,
+            node_shift: 10,
+            counter_bits: 7,
+            ..Layout::default()
+        };
+        let rejected = Err(SwitflakeError::InvalidLayout(
+            "reconfiguration would break ID ordering",
+        ));
+        assert_eq!(swit.reconfigure(lower_timestamp, 0), rejected);
+        // epoch를 늦추는 것도 순서를 깨뜨림
+        assert_eq!(swit.reconfigure(Layout::default(), 1_000), rejected);
+        assert_eq!(swit.layout(), Layout::default());
+        assert_eq!(swit.epoch(), 0);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{decompose, DecomposedId, Layout, Switflake, SwitflakeError};
+
+/// Width of the region code at the top of the default layout's node field,
+/// leaving 9 bits, 512 nodes, per region.
+pub const REGION_BITS: u32 = 3;
+
+const NODE_BITS: u32 = 12 - REGION_BITS;
+
+/// Coarse region of the node that minted an ID, for routing reads to the
+/// region that owns the data straight from the ID; see
+/// [`Switflake::new_regional`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    Us,
+    Eu,
+    Ap,
+    /// Any other region code, 3 to 7.
+    Other(u8),
+}
+
+impl Region {
+    /// The code stored in IDs: 0 for `Us`, 1 for `Eu`, 2 for `Ap`.
+    pub const fn code(self) -> u8 {
+        match self {
+            Region::Us => 0,
+            Region::Eu => 1,
+            Region::Ap => 2,
+            Region::Other(code) => code,
+        }
+    }
+
+    pub const fn from_code(code: u8) -> Region {
+        match code {
+            0 => Region::Us,
+            1 => Region::Eu,
+            2 => Region::Ap,
+            code => Region::Other(code),
+        }
+    }
+}
+
+impl Switflake {
+    /// Creates a generator whose node field carries `region` in its top
+    /// [`REGION_BITS`] bits and `node` in the remaining 9, under the default
+    /// layout. Node ids only need to be unique within a region.
+    ///
+    /// Fails with [`SwitflakeError::FieldOverflow`] if `region` is 8 or more
+    /// or `node` 512 or more.
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new_regional(region: u8, node: u64) -> Result<Self, SwitflakeError> {
+        if region as u64 >> REGION_BITS != 0 {
+            return Err(SwitflakeError::FieldOverflow("region"));
+        }
+        if node >> NODE_BITS != 0 {
+            return Err(SwitflakeError::FieldOverflow("node"));
+        }
+        Switflake::new((region as u64) << NODE_BITS | node)
+    }
+}
+
+/// Region of an ID from [`Switflake::new_regional`].
+#[inline]
+pub fn id_to_region(id: u64) -> Region {
+    decompose_regional(id).0
+}
+
+/// Splits an ID from [`Switflake::new_regional`] into its region and its
+/// fields, with the region bits taken out of `node_id`.
+pub fn decompose_regional(id: u64) -> (Region, DecomposedId) {
+    let mut parts = decompose(id, &Layout::default());
+    let region = Region::from_code((parts.node_id >> NODE_BITS) as u8);
+    parts.node_id &= crate::layout::mask(NODE_BITS);
+    (region, parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_region_round_trip_and_routing() {
+        let _guard = serial_guard();
+        let mut us = Switflake::new_regional(Region::Us.code(), 511).expect("Failed to create");
+        let mut eu = Switflake::new_regional(Region::Eu.code(), 7).expect("Failed to create");
+        let mut other = Switflake::new_regional(6, 7).expect("Failed to create");
+
+        let (region, parts) = decompose_regional(eu.generate_id().unwrap());
+        assert_eq!((region, parts.node_id), (Region::Eu, 7));
+        let (region, parts) = decompose_regional(us.generate_id().unwrap());
+        assert_eq!((region, parts.node_id), (Region::Us, 511));
+
+        // 지역별로 라우팅
+        let ids = [
+            us.generate_id().unwrap(),
+            eu.generate_id().unwrap(),
+            other.generate_id().unwrap(),
+        ];
+        let routes: Vec<Region> = ids.iter().map(|&id| id_to_region(id)).collect();
+        assert_eq!(routes, vec![Region::Us, Region::Eu, Region::Other(6)]);
+        assert_eq!(Region::from_code(Region::Ap.code()), Region::Ap);
+
+        assert_eq!(
+            Switflake::new_regional(8, 0).map(|_| ()),
+            Err(SwitflakeError::FieldOverflow("region"))
+        );
+        assert_eq!(
+            Switflake::new_regional(0, 512).map(|_| ()),
+            Err(SwitflakeError::FieldOverflow("node"))
+        );
+    }
+}
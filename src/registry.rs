@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Counts live generators so a supervisor can wait for all of them to be
+/// dropped, e.g. after they have persisted their state, before shutting
+/// down.
+///
+/// Every [`Switflake`](crate::Switflake) registers with the
+/// [`global`](Self::global) registry when built, or with the one given to
+/// [`SwitflakeBuilder::registry`](crate::SwitflakeBuilder::registry), and
+/// deregisters when dropped. Both are a single atomic operation; the lock
+/// is only taken by waiters and by the drop that brings the count to zero.
+#[derive(Debug, Default)]
+pub struct GeneratorRegistry {
+    live: AtomicUsize,
+    lock: Mutex<()>,
+    idle: Condvar,
+}
+
+impl GeneratorRegistry {
+    pub const fn new() -> Self {
+        GeneratorRegistry {
+            live: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            idle: Condvar::new(),
+        }
+    }
+
+    /// The registry every generator joins unless told otherwise.
+    pub fn global() -> &'static GeneratorRegistry {
+        static GLOBAL: GeneratorRegistry = GeneratorRegistry::new();
+        &GLOBAL
+    }
+
+    /// Generators currently registered.
+    pub fn live(&self) -> usize {
+        self.live.load(Ordering::Acquire)
+    }
+
+    /// Blocks until no generator is registered, waiting up to `timeout`
+    /// (forever if `None`). Returns whether that point was reached; new
+    /// generators may register right after.
+    pub fn wait_until_idle(&self, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        while self.live() > 0 {
+            guard = match deadline {
+                None => self.idle.wait(guard).unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return false;
+                    }
+                    self.idle
+                        .wait_timeout(guard, left)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+        true
+    }
+
+    pub(crate) fn register(&self) {
+        self.live.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn deregister(&self) {
+        if self.live.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // 대기자가 검사와 대기 사이에 있으면 잠금을 잡을 수 없으므로
+            // 알림을 놓치지 않음
+            drop(self.lock.lock().unwrap_or_else(|e| e.into_inner()));
+            self.idle.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+    use std::thread;
+
+    #[test]
+    fn test_wait_until_idle_returns_after_drops() {
+        static REGISTRY: GeneratorRegistry = GeneratorRegistry::new();
+        let _guard = serial_guard();
+        let generators: Vec<_> = (0..4)
+            .map(|_| {
+                Switflake::builder()
+                    .registry(&REGISTRY)
+                    .build()
+                    .expect("Failed to create Switflake")
+            })
+            .collect();
+        assert_eq!(REGISTRY.live(), 4);
+        assert!(!REGISTRY.wait_until_idle(Some(Duration::from_millis(10))));
+
+        let workers: Vec<_> = generators
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut swit)| {
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(5 * i as u64));
+                    swit.generate_id_blocking().expect("Failed to generate ID");
+                })
+            })
+            .collect();
+        assert!(REGISTRY.wait_until_idle(Some(Duration::from_secs(5))));
+        assert_eq!(REGISTRY.live(), 0);
+        for worker in workers {
+            worker.join().expect("Thread join failed");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::{Clock, Layout, Switflake, SwitflakeBuilder, SwitflakeError, SystemClock};
+
+// Offsets of 2^35..2^36 ms put the hidden origin one to two years before
+// the process started.
+const MIN_OFFSET_MILLIS: u64 = 1 << 35;
+
+/// Milliseconds since a random, undisclosed origin shared by the whole
+/// process, read from the monotonic clock.
+///
+/// IDs stamped by this clock sort and stay unique like any others, but the
+/// timestamp they carry only says how long after the hidden origin they
+/// were minted, not when: the origin lies a random one to two years before
+/// the process started. Each process picks its own origin, so IDs from
+/// different processes no longer sort by creation time. The origin is
+/// drawn from the standard library's hash seeding, which hides it from
+/// casual observers but is not meant to withstand a determined attacker.
+///
+/// With the `paranoid` feature, don't mix generators on this clock with
+/// wall-clock ones in one process: the process-wide timestamp high-water
+/// mark would see time jump back decades.
+#[derive(Debug, Clone, Copy)]
+pub struct RelativeClock {
+    origin: &'static Origin,
+}
+
+#[derive(Debug)]
+struct Origin {
+    started: Instant,
+    offset_millis: u64,
+    unix_millis: u64,
+}
+
+impl RelativeClock {
+    /// The process-wide clock; its origin is drawn on first use.
+    pub fn process() -> Result<Self, SwitflakeError> {
+        static ORIGIN: OnceLock<Origin> = OnceLock::new();
+        let now = SystemClock.now_millis()?;
+        let origin = ORIGIN.get_or_init(|| {
+            let started = Instant::now();
+            let random = RandomState::new().build_hasher().finish();
+            let offset_millis = MIN_OFFSET_MILLIS + random % MIN_OFFSET_MILLIS;
+            Origin {
+                started,
+                offset_millis,
+                unix_millis: now.saturating_sub(offset_millis),
+            }
+        });
+        Ok(RelativeClock { origin })
+    }
+
+    /// Unix time, in milliseconds, of the hidden origin. Whoever holds it
+    /// can turn [`id_to_relative_millis`] back into wall-clock time; keep
+    /// it wherever the IDs must not be dated.
+    pub fn origin_unix_millis(&self) -> u64 {
+        self.origin.unix_millis
+    }
+}
+
+impl Clock for RelativeClock {
+    #[inline]
+    fn now_millis(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.origin.offset_millis + self.origin.started.elapsed().as_millis() as u64)
+    }
+
+    #[inline]
+    fn now_micros(&self) -> Result<u64, SwitflakeError> {
+        Ok(self.origin.offset_millis * 1_000 + self.origin.started.elapsed().as_micros() as u64)
+    }
+}
+
+impl Switflake<RelativeClock> {
+    /// Creates a generator whose IDs don't reveal when they were minted;
+    /// see [`RelativeClock`].
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new_private(node_id: u64) -> Result<Self, SwitflakeError> {
+        SwitflakeBuilder::new()
+            .node_id(node_id)
+            .clock(RelativeClock::process()?)
+            .build()
+    }
+}
+
+/// Milliseconds between the hidden origin of [`RelativeClock`] and the
+/// minting of `id`, for IDs from [`Switflake::new_private`] with the
+/// default layout. Add [`RelativeClock::origin_unix_millis`] to get Unix
+/// time back.
+#[inline]
+pub fn id_to_relative_millis(id: u64) -> u64 {
+    Layout::default().timestamp_of(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+
+    #[test]
+    fn test_private_ids_hide_creation_time() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new_private(1).expect("Failed to create Switflake");
+        let first = swit.generate_id().expect("Failed to generate ID");
+        let second = swit.generate_id().expect("Failed to generate ID");
+        assert!(second > first);
+
+        // 원점 없이 해석한 시각은 1970년대를 가리킴
+        let unix_now = SystemClock.now_millis().unwrap();
+        let relative = id_to_relative_millis(second);
+        assert!(relative < 2 * MIN_OFFSET_MILLIS + 60_000);
+        assert!(relative < unix_now / 10);
+
+        let origin = RelativeClock::process().unwrap().origin_unix_millis();
+        let recovered = origin + relative;
+        assert!(recovered.abs_diff(unix_now) < 60_000);
+    }
+}
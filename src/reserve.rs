@@ -0,0 +1,207 @@
+use std::sync::Mutex;
+
+use crate::layout::Counter;
+use crate::{telemetry, Clock, Switflake, SwitflakeError, ThreadIdPool};
+
+// Furthest (Unix micros of the tick, borrowed node bits, next counter)
+// reached on each global pool slot by a range dropped with IDs unused. The
+// next generator to take the slot resumes from here within that tick.
+static HIGH_WATER: Mutex<[(u64, u64, Counter); <ThreadIdPool>::CAPACITY]> =
+    Mutex::new([(0, 0, 0); <ThreadIdPool>::CAPACITY]);
+
+/// What a [`ReservedRange`] does when dropped before all its IDs were
+/// handed out; see
+/// [`SwitflakeBuilder::reserved_drop_policy`](crate::SwitflakeBuilder::reserved_drop_policy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReservedDropPolicy {
+    /// Count the unused IDs in the `switflake_reserved_ids_lost_total`
+    /// metric and nothing more. The default without the `paranoid` feature.
+    #[cfg_attr(not(feature = "paranoid"), default)]
+    Forget,
+    /// Also record how far the range got in its tick, so a generator that
+    /// takes the same slot of the global pool within that tick starts after
+    /// it. The default with the `paranoid` feature.
+    #[cfg_attr(feature = "paranoid", default)]
+    Guard,
+}
+
+/// IDs minted up front by [`Switflake::reserve_range`] and handed out one
+/// at a time, e.g. to a batch job that outlives the generator.
+///
+/// The generator can be dropped, and its pool slot taken again, while the
+/// range is still held. IDs never handed out are lost; what else happens
+/// when the range is dropped with some left is set by
+/// [`ReservedDropPolicy`].
+#[derive(Debug)]
+pub struct ReservedRange {
+    ids: std::vec::IntoIter<u64>,
+    policy: ReservedDropPolicy,
+    // Global pool slot and the generator state its last ID left behind.
+    slot: Option<u8>,
+    high_water: (u64, u64, Counter),
+}
+
+impl ReservedRange {
+    /// The IDs not handed out yet.
+    pub fn as_slice(&self) -> &[u64] {
+        self.ids.as_slice()
+    }
+}
+
+impl Iterator for ReservedRange {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.ids.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ReservedRange {}
+
+impl Drop for ReservedRange {
+    fn drop(&mut self) {
+        let unused = self.ids.len();
+        if unused == 0 {
+            return;
+        }
+        telemetry::reserved_ids_lost(unused as u64);
+        if let (ReservedDropPolicy::Guard, Some(slot)) = (self.policy, self.slot) {
+            let mut high_water = lock_high_water();
+            let entry = &mut high_water[slot as usize];
+            if self.high_water > *entry {
+                *entry = self.high_water;
+            }
+        }
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Mints the next `count` IDs of this generator at once and returns
+    /// them as a [`ReservedRange`].
+    ///
+    /// Fails like [`generate_id`](Self::generate_id), e.g. with
+    /// [`SwitflakeError::SequenceExhausted`] if this tick runs out first;
+    /// the IDs minted before the failure are dropped as an unused range.
+    #[must_use = "an unused range still uses up sequence slots"]
+    pub fn reserve_range(&mut self, count: usize) -> Result<ReservedRange, SwitflakeError> {
+        let mut ids = Vec::with_capacity(count);
+        let mut range = ReservedRange {
+            ids: Vec::new().into_iter(),
+            policy: self.reserved_drop_policy,
+            slot: self.holds_global_slot().then_some(self.thread_id),
+            high_water: (0, 0, 0),
+        };
+        while ids.len() < count {
+            match self.generate_id() {
+                Ok(id) => ids.push(id),
+                Err(e) => {
+                    range.ids = ids.into_iter();
+                    range.high_water = self.high_water();
+                    return Err(e);
+                }
+            }
+        }
+        range.ids = ids.into_iter();
+        range.high_water = self.high_water();
+        Ok(range)
+    }
+}
+
+impl<C> Switflake<C> {
+    fn holds_global_slot(&self) -> bool {
+        self.pool.is_some_and(|pool| {
+            std::ptr::eq(
+                pool as *const dyn crate::pool::SlotPool as *const u8,
+                ThreadIdPool::global() as *const ThreadIdPool as *const u8,
+            )
+        })
+    }
+
+    fn high_water(&self) -> (u64, u64, Counter) {
+        let micros = (self.epoch_ticks() + self.last_timestamp) * self.time_unit.as_micros();
+        (micros, self.borrowed, self.local_counter)
+    }
+
+    /// Picks up where a guarded range dropped on this generator's slot left
+    /// off; called once, when the generator is built.
+    pub(crate) fn resume_high_water(&mut self) {
+        if !self.holds_global_slot() {
+            return;
+        }
+        let (micros, borrowed, counter) =
+            std::mem::take(&mut lock_high_water()[self.thread_id as usize]);
+        let ticks = micros / self.time_unit.as_micros();
+        if let Some(timestamp) = ticks.checked_sub(self.epoch_ticks()) {
+            if micros != 0 && timestamp <= self.layout.timestamp_mask() {
+                self.last_timestamp = timestamp;
+                self.borrowed = borrowed;
+                self.local_counter = counter;
+                self.refresh_prefix();
+            }
+        }
+    }
+}
+
+/// Takes the Unix micros of the tick a guarded range left global pool slot
+/// `slot` in; 0 if none did.
+pub(crate) fn take_high_water(slot: u8) -> u64 {
+    std::mem::take(&mut lock_high_water()[slot as usize]).0
+}
+
+fn lock_high_water(
+) -> std::sync::MutexGuard<'static, [(u64, u64, Counter); <ThreadIdPool>::CAPACITY]> {
+    HIGH_WATER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_guarded_range_not_reissued_on_same_slot() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let build = |policy| {
+            Switflake::builder()
+                .node_id(9)
+                .reserved_drop_policy(policy)
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake")
+        };
+
+        let mut swit = build(ReservedDropPolicy::Guard);
+        let mut range = swit.reserve_range(10).expect("Failed to reserve IDs");
+        assert_eq!(range.len(), 10);
+        let handed_out = range.next().expect("range is empty");
+        let unused: HashSet<u64> = range.as_slice().iter().copied().collect();
+        drop(swit);
+        drop(range);
+
+        // 같은 밀리초에 같은 슬롯을 다시 받음
+        let mut next = build(ReservedDropPolicy::Guard);
+        let ids = next.generate_available(300).unwrap();
+        assert!(!ids.is_empty());
+        assert!(ids
+            .iter()
+            .all(|id| *id != handed_out && !unused.contains(id)));
+        drop(next);
+
+        // Forget는 기록하지 않으므로 재발급됨
+        clock.advance(1);
+        let mut swit = build(ReservedDropPolicy::Forget);
+        let range = swit.reserve_range(5).expect("Failed to reserve IDs");
+        let unused: Vec<u64> = range.as_slice().to_vec();
+        drop(swit);
+        drop(range);
+        let mut next = build(ReservedDropPolicy::Forget);
+        assert_eq!(next.generate_id(), Ok(unused[0]));
+    }
+}
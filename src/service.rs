@@ -0,0 +1,131 @@
+//! A generator on its own thread, shared by handing it requests.
+
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use crate::{Clock, Switflake, SwitflakeError};
+
+// Requests that may queue up before producers wait for the thread.
+const QUEUE_LEN: usize = 1024;
+
+type Reply = SyncSender<Result<u64, SwitflakeError>>;
+
+/// One [`Switflake`] owned by a dedicated thread that serves IDs to any
+/// number of producer threads over a channel, so they share a single pool
+/// slot and never contend on the generator itself.
+///
+/// Each [`next_id`](Self::next_id) is a round trip to that thread, which
+/// costs more than generating in place but keeps the generator out of
+/// reach of every other thread. Requests are served in the order they
+/// arrive; when a millisecond runs out the thread waits for the next one,
+/// as [`Switflake::generate_id_blocking`] does.
+pub struct GeneratorService {
+    requests: Option<SyncSender<Reply>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl GeneratorService {
+    /// Spawns a service around a new generator for `node_id`.
+    #[must_use = "dropping the service frees its pool slot right away"]
+    pub fn new(node_id: u64) -> Result<Self, SwitflakeError> {
+        Ok(GeneratorService::spawn(Switflake::new(node_id)?))
+    }
+
+    /// Spawns the service thread and moves `swit` onto it.
+    pub fn spawn<C: Clock + Send + 'static>(mut swit: Switflake<C>) -> Self {
+        let (requests, queue) = mpsc::sync_channel::<Reply>(QUEUE_LEN);
+        let worker = thread::spawn(move || {
+            // 모든 송신자가 사라질 때까지 남은 요청을 처리
+            for reply in queue {
+                let _ = reply.send(swit.generate_id_blocking());
+            }
+        });
+        GeneratorService {
+            requests: Some(requests),
+            worker: Some(worker),
+        }
+    }
+
+    /// Gets an ID from the service thread, waiting for it if others are
+    /// queued first. Fails with [`SwitflakeError::Internal`] if the thread
+    /// is gone, which only happens if it panicked.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn next_id(&self) -> Result<u64, SwitflakeError> {
+        let (reply, answer) = mpsc::sync_channel(1);
+        self.requests
+            .as_ref()
+            .and_then(|requests| requests.send(reply).ok())
+            .ok_or(SwitflakeError::Internal)?;
+        answer.recv().unwrap_or(Err(SwitflakeError::Internal))
+    }
+
+    /// Stops taking requests, answers every one already queued, then waits
+    /// for the thread to drop the generator and free its pool slot.
+    /// Dropping the service does the same.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        drop(self.requests.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for GeneratorService {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::ThreadIdPool;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_32_producers_share_one_service() {
+        let _guard = serial_guard();
+        let service = Arc::new(GeneratorService::new(5).expect("Failed to create service"));
+        let handles: Vec<_> = (0..32)
+            .map(|_| {
+                let service = Arc::clone(&service);
+                thread::spawn(move || {
+                    (0..100)
+                        .map(|_| service.next_id().expect("Failed to generate ID"))
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+
+        let mut ids = HashSet::new();
+        for handle in handles {
+            for id in handle.join().expect("Thread join failed") {
+                assert!(ids.insert(id), "Duplicate ID found: {}", id);
+            }
+        }
+        assert_eq!(ids.len(), 3_200);
+        // 슬롯은 서비스 하나만 사용
+        assert_eq!(ThreadIdPool::global().occupied_slots().len(), 1);
+    }
+
+    #[test]
+    fn test_shutdown_frees_slot() {
+        let _guard = serial_guard();
+        let service = GeneratorService::new(5).expect("Failed to create service");
+        let first = service.next_id().expect("Failed to generate ID");
+        assert!(service.next_id().expect("Failed to generate ID") > first);
+        service.shutdown();
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+
+        // drop도 같은 방식으로 정리
+        let service = GeneratorService::new(5).expect("Failed to create service");
+        drop(service);
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+    }
+}
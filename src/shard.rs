@@ -0,0 +1,379 @@
+use crate::obfuscate::mix;
+use crate::{decompose, Layout};
+
+/// Maps `id` to a shard in `0..shard_count`, evenly and deterministically.
+///
+/// A plain `id % shard_count` is a poor router for these IDs: the low bits
+/// hold the node, thread and a counter that restarts every millisecond, so a
+/// single busy generator keeps hitting the same few shards, and when
+/// `shard_count` is a power of two the timestamp never takes part at all.
+/// Hashing the whole ID first spreads every field over every shard.
+///
+/// # Panics
+///
+/// Panics if `shard_count` is 0.
+#[inline]
+pub fn id_to_shard(id: u64, shard_count: u32) -> u32 {
+    assert!(shard_count > 0, "shard_count must be non-zero");
+    // 상위 32비트를 [0, shard_count) 범위로 축소 (modulo 없이)
+    (((mix(id) >> 32) * shard_count as u64) >> 32) as u32
+}
+
+/// Folds `id` into a well-spread 32-bit hash, e.g. for a secondary
+/// bloom-filter or cache key.
+///
+/// Unlike `id as u32`, which keeps only the node, sequence and lowest
+/// timestamp bits, both halves of the ID contribute to every output bit.
+/// The function is fixed: the same ID hashes to the same value in every
+/// version of this crate. It is not cryptographic and anyone can compute
+/// it, so don't use it where an attacker must not pick colliding IDs.
+#[inline]
+pub fn id_hash32(id: u64) -> u32 {
+    let mut h = ((id >> 32) as u32 ^ id as u32).wrapping_mul(0x9E37_79B1);
+    // murmur3 finalizer: a lone multiply leaves the high bits biased for
+    // sequential inputs
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xC2B2_AE35);
+    h ^ (h >> 16)
+}
+
+/// Reverses the bits of `id`, so that IDs minted one after another, which
+/// differ only in their low counter and timestamp bits, land far apart in
+/// key order: stored under the scrambled form they no longer pile up in
+/// the newest partition of a range-partitioned store.
+///
+/// Unlike [`id_to_shard`] this loses nothing: [`unscramble`] gives the
+/// original back. The price is that scrambled keys don't sort by time, so
+/// range scans by creation time need the original IDs. Anyone can
+/// unscramble; use [`obfuscate`](crate::obfuscate) to hide the fields.
+#[inline]
+pub const fn scramble(id: u64) -> u64 {
+    id.reverse_bits()
+}
+
+/// Inverse of [`scramble`].
+#[inline]
+pub const fn unscramble(scrambled: u64) -> u64 {
+    scrambled.reverse_bits()
+}
+
+/// Splits `id` into a `(shard_key, sort_key)` pair for wide-column stores
+/// such as Cassandra or DynamoDB: the node field as the partition key and
+/// the timestamp followed by the sequence as the clustering key.
+///
+/// Records minted by one node then land in one partition, where they sort
+/// by time just as the IDs themselves do. Partitions are only as even as
+/// the traffic of the nodes; hash with [`id_to_shard`] instead if one node
+/// may dominate.
+#[inline]
+pub fn to_composite_key(id: u64, layout: &Layout) -> (u64, u64) {
+    let parts = decompose(id, layout);
+    let sequence = (parts.thread_id as u64) << layout.counter_bits | parts.counter as u64;
+    (
+        parts.node_id,
+        parts.timestamp << layout.sequence_bits() | sequence,
+    )
+}
+
+/// Interleaves the bits of the timestamp and node fields of `id` (Morton,
+/// or Z-order), for stores whose queries filter on node and time together:
+/// under the transformed keys records of one node from nearby times sit
+/// close together in a B-tree instead of being spread across every other
+/// node's records of those times.
+///
+/// The interleaved bits take the place of the two fields, lowest timestamp
+/// bit first, and the rest of the wider field fills the top. Every other
+/// bit is left alone, and [`from_zorder`] gives the original back. The
+/// price is time order: keys still sort by the timestamp bits above the
+/// node's width, but within such a span they sort by a mix of time and
+/// node, so IDs minted later no longer always get larger keys and an
+/// exact time-range scan needs several key ranges or an extra filter.
+pub fn to_zorder(id: u64, layout: &Layout) -> u64 {
+    let fields = zorder_fields(layout);
+    let timestamp = layout.timestamp_of(id);
+    let node = (id >> layout.node_shift) & layout.node_mask();
+    let mut interleaved = 0;
+    let (mut t, mut n) = (0, 0);
+    for bit in 0..layout.timestamp_bits + layout.node_bits {
+        // 노드 비트가 남아 있는 동안 타임스탬프와 번갈아 배치
+        let value = if t < layout.timestamp_bits && (n >= layout.node_bits || t <= n) {
+            t += 1;
+            timestamp >> (t - 1)
+        } else {
+            n += 1;
+            node >> (n - 1)
+        };
+        interleaved |= (value & 1) << bit;
+    }
+    (id & !fields) | deposit(interleaved, fields)
+}
+
+/// Inverse of [`to_zorder`].
+pub fn from_zorder(zorder: u64, layout: &Layout) -> u64 {
+    let fields = zorder_fields(layout);
+    let interleaved = extract(zorder, fields);
+    let (mut timestamp, mut node) = (0, 0);
+    let (mut t, mut n) = (0, 0);
+    for bit in 0..layout.timestamp_bits + layout.node_bits {
+        let value = (interleaved >> bit) & 1;
+        if t < layout.timestamp_bits && (n >= layout.node_bits || t <= n) {
+            timestamp |= value << t;
+            t += 1;
+        } else {
+            node |= value << n;
+            n += 1;
+        }
+    }
+    (zorder & !fields)
+        | timestamp.checked_shl(layout.timestamp_shift).unwrap_or(0)
+        | node.checked_shl(layout.node_shift).unwrap_or(0)
+}
+
+// Bits of an ID that belong to the timestamp or node field.
+fn zorder_fields(layout: &Layout) -> u64 {
+    layout
+        .timestamp_mask()
+        .checked_shl(layout.timestamp_shift)
+        .unwrap_or(0)
+        | layout
+            .node_mask()
+            .checked_shl(layout.node_shift)
+            .unwrap_or(0)
+}
+
+// Spreads the low bits of `value` over the set bits of `positions`, lowest
+// first.
+fn deposit(value: u64, mut positions: u64) -> u64 {
+    let mut out = 0;
+    let mut bit = 0;
+    while positions != 0 {
+        let lowest = positions & positions.wrapping_neg();
+        if (value >> bit) & 1 != 0 {
+            out |= lowest;
+        }
+        positions &= !lowest;
+        bit += 1;
+    }
+    out
+}
+
+// Gathers the bits of `value` at the set bits of `positions` into its low
+// bits; the inverse of `deposit`.
+fn extract(value: u64, mut positions: u64) -> u64 {
+    let mut out = 0;
+    let mut bit = 0;
+    while positions != 0 {
+        let lowest = positions & positions.wrapping_neg();
+        if value & lowest != 0 {
+            out |= 1 << bit;
+        }
+        positions &= !lowest;
+        bit += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{Switflake, SwitflakeError};
+
+    #[test]
+    fn test_zorder_round_trips() {
+        let layouts = [
+            Layout::default(),
+            Layout {
+                timestamp_shift: 0,
+                node_shift: 41,
+                sequence_shift: 53,
+                ..Layout::default()
+            },
+        ];
+        for layout in layouts {
+            for id in [0, 1, 42, 7_159_857_416_126_464_001, u64::MAX - 1, u64::MAX] {
+                assert_eq!(from_zorder(to_zorder(id, &layout), &layout), id);
+            }
+        }
+        // 시퀀스 필드는 그대로
+        let id = 7_159_857_416_126_464_001;
+        let sequence_mask = Layout::default().sequence_mask();
+        assert_eq!(
+            to_zorder(id, &Layout::default()) & sequence_mask,
+            id & sequence_mask
+        );
+    }
+
+    #[test]
+    fn test_zorder_keeps_one_node_close_in_time() {
+        let layout = Layout::default();
+        let id = |timestamp: u64, node: u64| timestamp << 23 | node << 11 | 7;
+        let start = 1_700_000_000_000 & !0xF;
+        let span = |keys: &[u64]| keys.iter().max().unwrap() - keys.iter().min().unwrap();
+
+        // 16ms 동안 같은 노드: 타임스탬프 하위 4비트는 z-순서 하위 8비트에 들어감
+        let keys: Vec<u64> = (0..16)
+            .map(|ms| to_zorder(id(start + ms, 5), &layout))
+            .collect();
+        assert!(span(&keys) < 1 << (11 + 8));
+        let raw: Vec<u64> = (0..16).map(|ms| id(start + ms, 5)).collect();
+        assert!(span(&raw) >= 15 << 23);
+
+        // 같은 밀리초라도 노드 번호가 크게 다르면 멀리 떨어짐
+        let far = to_zorder(id(start, 5 | 1 << 11), &layout);
+        assert!(far - keys[0] >= 1 << (11 + 23));
+    }
+
+    #[test]
+    fn test_scramble_round_trips() {
+        for id in [0, 1, 42, 7_159_857_416_126_464_001, u64::MAX - 1, u64::MAX] {
+            assert_eq!(unscramble(scramble(id)), id);
+        }
+    }
+
+    #[test]
+    fn test_scrambled_consecutive_ids_spread_out() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(5)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let mut buckets = [0usize; 16];
+        let mut previous = None;
+        for i in 0..1_600 {
+            if i % 200 == 0 {
+                clock.advance(1);
+            }
+            let id = swit.generate_id().expect("Failed to generate ID");
+            let scrambled = scramble(id);
+            buckets[(scrambled >> 60) as usize] += 1;
+            if let Some(previous) = previous {
+                // 이웃한 ID의 뒤섞인 값은 상위 비트부터 달라짐
+                assert!(scramble(previous) ^ scrambled >= 1 << 56);
+            }
+            previous = Some(id);
+        }
+        assert!(buckets.iter().all(|&count| count > 50), "{:?}", buckets);
+    }
+
+    #[test]
+    fn test_composite_key_groups_by_node_and_sorts_by_time() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(42)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let layout = swit.layout();
+        let mut previous: Option<(u64, u64)> = None;
+        for i in 0..1_000 {
+            if i % 100 == 0 {
+                clock.advance(1);
+            }
+            let id = swit.generate_id().expect("Failed to generate ID");
+            let (shard_key, sort_key) = to_composite_key(id, &layout);
+            assert_eq!(shard_key, decompose(id, &layout).node_id);
+            if let Some((previous_id, previous_sort)) = previous {
+                assert_eq!(previous_id.cmp(&id), previous_sort.cmp(&sort_key));
+            }
+            previous = Some((id, sort_key));
+        }
+    }
+
+    #[test]
+    fn test_even_distribution_over_16_shards() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(3)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+
+        const TOTAL: usize = 100_000;
+        const SHARDS: u32 = 16;
+        let mut counts = [0usize; SHARDS as usize];
+        let mut generated = 0;
+        while generated < TOTAL {
+            match swit.generate_id() {
+                Ok(id) => {
+                    let shard = id_to_shard(id, SHARDS);
+                    assert_eq!(shard, id_to_shard(id, SHARDS));
+                    counts[shard as usize] += 1;
+                    generated += 1;
+                }
+                Err(SwitflakeError::SequenceExhausted) => clock.advance(1),
+                Err(e) => panic!("Failed to generate ID: {}", e),
+            }
+        }
+
+        let mean = TOTAL / SHARDS as usize;
+        for (shard, &count) in counts.iter().enumerate() {
+            assert!(
+                count.abs_diff(mean) < mean / 10,
+                "shard {} got {} IDs, mean {}",
+                shard,
+                count,
+                mean
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash32_spreads_generated_ids() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .node_id(3)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+
+        const TOTAL: usize = 100_000;
+        let mut hashes = Vec::with_capacity(TOTAL);
+        let mut bit_counts = [0usize; 32];
+        while hashes.len() < TOTAL {
+            match swit.generate_id() {
+                Ok(id) => {
+                    let hash = id_hash32(id);
+                    for (bit, count) in bit_counts.iter_mut().enumerate() {
+                        *count += (hash >> bit) as usize & 1;
+                    }
+                    hashes.push(hash);
+                }
+                Err(SwitflakeError::SequenceExhausted) => clock.advance(1),
+                Err(e) => panic!("Failed to generate ID: {}", e),
+            }
+        }
+
+        // 무작위 32비트 해시라면 10만 개 중 충돌은 1~2개 정도
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert!(
+            TOTAL - hashes.len() <= 10,
+            "{} collisions",
+            TOTAL - hashes.len()
+        );
+        for (bit, &count) in bit_counts.iter().enumerate() {
+            assert!(
+                count.abs_diff(TOTAL / 2) < TOTAL / 50,
+                "bit {} set in {} of {} hashes",
+                bit,
+                count,
+                TOTAL
+            );
+        }
+    }
+
+    #[test]
+    fn test_single_shard() {
+        assert_eq!(id_to_shard(0, 1), 0);
+        assert_eq!(id_to_shard(u64::MAX, 1), 0);
+    }
+}
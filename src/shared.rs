@@ -0,0 +1,467 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::builder::cooling_slots;
+use crate::layout::{Counter, DEFAULT_LAYOUT};
+use crate::reserve;
+use crate::{telemetry, Clock, Layout, SlotLease, SwitflakeError, SystemClock, ThreadIdPool};
+
+// The generator's state packs the tick last issued from above the counter
+// its next ID takes, in the default layout's timestamp position.
+const TICK_SHIFT: u32 = DEFAULT_LAYOUT.timestamp_shift;
+const LANE_MASK: u64 = (1 << TICK_SHIFT) - 1;
+const COUNTER_MASK: u64 = DEFAULT_LAYOUT.counter_mask();
+
+// Numbers every generator ever made, so a thread's reservation can't be
+// mistaken for one from a dropped generator at the same address.
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // The calling thread's unused reserved counters, from whichever
+    // generator it last reserved from.
+    static RESERVED: Cell<Reservation> = const { Cell::new(Reservation::NONE) };
+}
+
+/// Counters `next..end` of `tick`, reserved by one thread from generator
+/// `instance`.
+#[derive(Debug, Clone, Copy)]
+struct Reservation {
+    instance: u64,
+    tick: u64,
+    next: u64,
+    end: u64,
+}
+
+impl Reservation {
+    const NONE: Reservation = Reservation {
+        instance: 0,
+        tick: 0,
+        next: 0,
+        end: 0,
+    };
+}
+
+/// A generator for the default layout that threads share by reference
+/// instead of each holding their own: [`generate_id`](Self::generate_id)
+/// takes `&self`, so one `SharedSwitflake` can sit in an `Arc` or a static
+/// without a `Mutex`.
+///
+/// It holds a single slot of the global [`ThreadIdPool`], and every thread
+/// takes counters from the same per-millisecond sequence with a
+/// compare-and-swap, so all threads together get as many IDs per
+/// millisecond as one [`Switflake`](crate::Switflake). IDs from one thread
+/// increase; across threads they are unique and sort by millisecond. A
+/// clock reading behind the latest tick another thread issued from joins
+/// that tick rather than failing.
+///
+/// It takes and gives back its slot like a `Switflake` does, so the pool's
+/// [reuse cooldown](ThreadIdPool::set_reuse_cooldown) and ranges guarded by
+/// [`ReservedDropPolicy::Guard`](crate::ReservedDropPolicy::Guard) hold
+/// for it too: built on a slot whose last millisecond isn't over, it issues
+/// nothing until the next one.
+///
+/// Under heavy contention the compare-and-swap retries a lot; with
+/// [`with_reservations`](Self::with_reservations) each thread instead
+/// claims a block of counters with a single `fetch_add` and hands them out
+/// without touching the shared state until the block runs out or the
+/// millisecond ends.
+pub struct SharedSwitflake<C = SystemClock> {
+    node_id: u64,
+    thread_id: u8,
+    generation: u32,
+    clock: C,
+    state: AtomicU64,
+    instance: u64,
+    // Counters a thread reserves at once; 0 to take them one at a time.
+    block: u64,
+}
+
+impl SharedSwitflake {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new(node_id: u64) -> Result<Self, SwitflakeError> {
+        SharedSwitflake::with_clock(node_id, SystemClock)
+    }
+}
+
+impl<C: Clock> SharedSwitflake<C> {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn with_clock(node_id: u64, clock: C) -> Result<Self, SwitflakeError> {
+        let pool = ThreadIdPool::global();
+        // 쿨다운 중인 슬롯은 다른 빈 슬롯이 없을 때만 사용
+        let lease = pool.acquire_preferring(0, !cooling_slots(pool, &clock))?;
+        let state = match start_tick(pool, lease.thread_id, &clock) {
+            0 => 0,
+            tick => pack(tick, COUNTER_MASK),
+        };
+        Ok(SharedSwitflake {
+            node_id: node_id & DEFAULT_LAYOUT.node_mask(),
+            thread_id: lease.thread_id,
+            generation: lease.generation,
+            clock,
+            state: AtomicU64::new(state),
+            instance: NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed),
+            block: 0,
+        })
+    }
+
+    /// Has each thread reserve `block` counters at a time, clamped to the
+    /// 255 a millisecond holds. IDs stay unique: reserved blocks never
+    /// overlap, within a millisecond or across one. They still increase per
+    /// thread, but threads' IDs interleave less finely within a millisecond,
+    /// and counters a thread reserved but didn't use by the end of the
+    /// millisecond are lost, so a millisecond may run out before all of
+    /// them are issued. A `block` of 0 turns reservations back off.
+    pub fn with_reservations(mut self, block: u16) -> Self {
+        self.block = (block as u64).min(COUNTER_MASK);
+        self
+    }
+
+    pub fn layout(&self) -> Layout {
+        DEFAULT_LAYOUT
+    }
+
+    /// Generates the next ID, failing with
+    /// [`SwitflakeError::SequenceExhausted`] once this millisecond's
+    /// counter is used up across all threads.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id(&self) -> Result<u64, SwitflakeError> {
+        let now = self.now()?;
+        if self.block > 0 {
+            return self.generate_reserved(now);
+        }
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            let (tick, counter) = unpack(state);
+            let (tick, counter) = if now > tick {
+                (now, 0)
+            } else if counter < COUNTER_MASK {
+                (tick, counter)
+            } else {
+                telemetry::sequence_exhausted();
+                return Err(SwitflakeError::SequenceExhausted);
+            };
+            match self.state.compare_exchange_weak(
+                state,
+                pack(tick, counter + 1),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(self.issue(tick, counter)),
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    /// Like [`generate_id`](Self::generate_id), but spins until the next
+    /// millisecond instead of failing when this one runs out.
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id_blocking(&self) -> Result<u64, SwitflakeError> {
+        loop {
+            match self.generate_id() {
+                Err(SwitflakeError::SequenceExhausted) => std::hint::spin_loop(),
+                result => return result,
+            }
+        }
+    }
+
+    fn generate_reserved(&self, now: u64) -> Result<u64, SwitflakeError> {
+        RESERVED.with(|cell| {
+            let mut reservation = cell.get();
+            if reservation.instance != self.instance
+                || reservation.tick < now
+                || reservation.next == reservation.end
+            {
+                reservation = self.reserve(now)?;
+            }
+            let counter = reservation.next;
+            reservation.next += 1;
+            cell.set(reservation);
+            Ok(self.issue(reservation.tick, counter))
+        })
+    }
+
+    /// Claims the next block of counters, starting a new tick if `now` is
+    /// past the latest one.
+    ///
+    /// Within a tick the counter only moves through `fetch_add`, so every
+    /// claim gets its own stretch of it, and a new tick is only ever
+    /// started by a compare-and-swap from the state it replaces, so no
+    /// claim can straddle two ticks. A thread only adds after seeing
+    /// counters left, which bounds how far past the last one the counter
+    /// can run to a block per concurrent thread, well within the 23 bits
+    /// it has before it would carry into the tick.
+    fn reserve(&self, now: u64) -> Result<Reservation, SwitflakeError> {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            let (tick, counter) = unpack(state);
+            if now > tick {
+                let end = self.block;
+                match self.state.compare_exchange_weak(
+                    state,
+                    pack(now, end),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => {
+                        return Ok(Reservation {
+                            instance: self.instance,
+                            tick: now,
+                            next: 0,
+                            end,
+                        })
+                    }
+                    Err(actual) => {
+                        state = actual;
+                        continue;
+                    }
+                }
+            }
+            if counter >= COUNTER_MASK {
+                telemetry::sequence_exhausted();
+                return Err(SwitflakeError::SequenceExhausted);
+            }
+            let (tick, start) = unpack(self.state.fetch_add(self.block, Ordering::AcqRel));
+            if start < COUNTER_MASK {
+                return Ok(Reservation {
+                    instance: self.instance,
+                    tick,
+                    next: start,
+                    end: (start + self.block).min(COUNTER_MASK),
+                });
+            }
+            // 다른 스레드가 먼저 소진시킴
+            state = self.state.load(Ordering::Acquire);
+        }
+    }
+
+    fn now(&self) -> Result<u64, SwitflakeError> {
+        let now = self.clock.now_millis()?;
+        if now > DEFAULT_LAYOUT.timestamp_mask() {
+            return Err(SwitflakeError::TimestampOverflow);
+        }
+        Ok(now)
+    }
+
+    fn issue(&self, tick: u64, counter: u64) -> u64 {
+        telemetry::id_generated();
+        DEFAULT_LAYOUT.pack(tick, self.node_id, self.thread_id, counter as Counter)
+    }
+}
+
+impl<C> Drop for SharedSwitflake<C> {
+    fn drop(&mut self) {
+        let end_micros = match unpack(*self.state.get_mut()) {
+            (0, _) => 0,
+            (tick, _) => (tick + 1) * 1_000,
+        };
+        let lease = SlotLease {
+            thread_id: self.thread_id,
+            generation: self.generation,
+        };
+        ThreadIdPool::global().release_after(lease, end_micros);
+    }
+}
+
+/// The last millisecond IDs on slot `thread_id` may already have been
+/// issued in, by a dropped generator still cooling down or by a guarded
+/// range; 0 if there is none.
+fn start_tick<C: Clock>(pool: &ThreadIdPool, thread_id: u8, clock: &C) -> u64 {
+    let cooling = match pool.cooling_until(thread_id) {
+        until if until > clock.now_micros().unwrap_or(0) => (until - 1) / 1_000,
+        _ => 0,
+    };
+    cooling.max(reserve::take_high_water(thread_id) / 1_000)
+}
+
+#[inline]
+const fn pack(tick: u64, counter: u64) -> u64 {
+    tick << TICK_SHIFT | counter
+}
+
+#[inline]
+const fn unpack(state: u64) -> (u64, u64) {
+    (state >> TICK_SHIFT, state & LANE_MASK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::decompose;
+    use crate::pool::serial_guard;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn test_threads_share_one_sequence() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let swit = Arc::new(
+            SharedSwitflake::with_clock(9, clock.clone())
+                .expect("Failed to create SharedSwitflake"),
+        );
+        assert_eq!(ThreadIdPool::global().occupied_slots().len(), 1);
+
+        // 네 스레드가 한 밀리초의 카운터 255개를 나눠 가짐
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let swit = Arc::clone(&swit);
+                thread::spawn(move || {
+                    let mut ids = Vec::new();
+                    while let Ok(id) = swit.generate_id() {
+                        ids.push(id);
+                    }
+                    ids
+                })
+            })
+            .collect();
+        let mut counters = Vec::new();
+        for handle in handles {
+            let ids = handle.join().expect("Thread join failed");
+            assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+            counters.extend(ids.iter().map(|&id| decompose(id, &swit.layout()).counter));
+        }
+        counters.sort_unstable();
+        assert_eq!(counters, (0..255).collect::<Vec<_>>());
+
+        clock.advance(1);
+        let parts = decompose(swit.generate_id().unwrap(), &swit.layout());
+        assert_eq!(
+            (parts.timestamp, parts.node_id, parts.counter),
+            (1_700_000_000_001, 9, 0)
+        );
+        drop(swit);
+        assert!(ThreadIdPool::global().occupied_slots().is_empty());
+    }
+
+    #[test]
+    fn test_reservation_takes_a_block_per_thread() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let swit = SharedSwitflake::with_clock(3, clock.clone())
+            .expect("Failed to create SharedSwitflake")
+            .with_reservations(16);
+        let counter = |id| decompose(id, &swit.layout()).counter;
+        assert_eq!(counter(swit.generate_id().unwrap()), 0);
+        assert_eq!(counter(swit.generate_id().unwrap()), 1);
+
+        // 다른 스레드는 다음 블록에서 시작
+        let other: Vec<_> = thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    (0..20)
+                        .map(|_| counter(swit.generate_id().unwrap()))
+                        .collect()
+                })
+                .join()
+                .expect("Thread join failed")
+        });
+        assert_eq!(other, (16..36).collect::<Vec<_>>());
+        assert_eq!(counter(swit.generate_id().unwrap()), 2);
+
+        // 남은 예약은 다음 밀리초로 넘어가지 않음
+        clock.advance(1);
+        let parts = decompose(swit.generate_id().unwrap(), &swit.layout());
+        assert_eq!((parts.timestamp, parts.counter), (1_700_000_000_001, 0));
+    }
+
+    #[test]
+    fn test_reserved_ids_unique_across_32_threads() {
+        const THREADS: usize = 32;
+        const PER_THREAD: usize = 1_000;
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let swit = SharedSwitflake::with_clock(7, clock.clone())
+            .expect("Failed to create SharedSwitflake")
+            .with_reservations(8);
+        let start = Barrier::new(THREADS + 1);
+        let done = AtomicBool::new(false);
+
+        let per_thread: Vec<Vec<u64>> = thread::scope(|scope| {
+            // 생성 도중 밀리초가 계속 넘어가도록 시계를 진행
+            scope.spawn(|| {
+                start.wait();
+                while !done.load(Ordering::Relaxed) {
+                    clock.advance(1);
+                    thread::yield_now();
+                }
+            });
+            let handles: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    scope.spawn(|| {
+                        start.wait();
+                        (0..PER_THREAD)
+                            .map(|_| swit.generate_id_blocking().expect("Failed to generate ID"))
+                            .collect::<Vec<u64>>()
+                    })
+                })
+                .collect();
+            let ids = handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Thread join failed"))
+                .collect();
+            done.store(true, Ordering::Relaxed);
+            ids
+        });
+
+        let mut seen = HashSet::new();
+        for ids in &per_thread {
+            assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+            for &id in ids {
+                assert!(seen.insert(id), "Duplicate ID found: {}", id);
+            }
+        }
+        assert_eq!(seen.len(), THREADS * PER_THREAD);
+        let ticks: HashSet<u64> = seen
+            .iter()
+            .map(|&id| decompose(id, &swit.layout()).timestamp)
+            .collect();
+        assert!(ticks.len() > 1, "Expected IDs across several milliseconds");
+    }
+
+    #[test]
+    fn test_recreated_generator_sits_out_cooldown() {
+        struct CooldownOff;
+        impl Drop for CooldownOff {
+            fn drop(&mut self) {
+                ThreadIdPool::global().set_reuse_cooldown(false);
+            }
+        }
+        let _guard = serial_guard();
+        let pool = ThreadIdPool::global();
+        pool.set_reuse_cooldown(true);
+        let _reset = CooldownOff;
+        // 다른 테스트가 남긴 쿨다운보다 뒤의 시각
+        let clock = MockClock::at(2_000_000_000_000);
+        // 빈 슬롯을 두 개만 남김
+        let others: Vec<SlotLease> = (0..6).map(|_| pool.acquire().unwrap()).collect();
+
+        let first = SharedSwitflake::with_clock(5, clock.clone()).unwrap();
+        let mut issued: HashSet<u64> = (0..3).map(|_| first.generate_id().unwrap()).collect();
+        let slot = first.thread_id;
+        drop(first);
+        // 같은 밀리초에는 방금 반환된 슬롯 대신 다른 슬롯을 받음
+        let second = SharedSwitflake::with_clock(5, clock.clone()).unwrap();
+        assert_ne!(second.thread_id, slot);
+        assert!(issued.insert(second.generate_id().unwrap()));
+        drop(second);
+
+        // 두 슬롯 모두 쿨다운 중이면 받되 다음 밀리초까지 발급하지 않음
+        let third = SharedSwitflake::with_clock(5, clock.clone())
+            .unwrap()
+            .with_reservations(4);
+        assert_eq!(third.generate_id(), Err(SwitflakeError::SequenceExhausted));
+        clock.advance(1);
+        for _ in 0..3 {
+            let id = third.generate_id().unwrap();
+            assert!(id > *issued.iter().max().unwrap());
+            assert!(issued.insert(id));
+        }
+        drop(third);
+        for lease in others {
+            assert!(pool.force_release(lease));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{Clock, Switflake, SwitflakeBuilder, SwitflakeError};
+
+// Records kept before the log is rewritten down to its latest one.
+const DEFAULT_MAX_RECORDS: u64 = 1 << 16;
+
+/// Append-only log of the ticks a generator has started, so a generator
+/// resumed from it after a crash never issues IDs from a tick it may
+/// already have used; see [`SwitflakeBuilder::state_log`] and
+/// [`Switflake::resume_from_log`].
+///
+/// Each record is the start of a tick as 8 little-endian bytes of Unix
+/// microseconds, appended when the generator rolls over to a new tick and
+/// before any ID from that tick is returned, so the log grows with the
+/// number of ticks used, not of IDs. Once it holds
+/// [`max_records`](Self::max_records) records it is rewritten, through a
+/// temporary file and a rename, down to its latest record. With `sync` on
+/// every record is flushed to disk before the ID is handed out; without
+/// it a crash of the whole machine, rather than the process, can lose the
+/// latest records.
+#[derive(Debug)]
+pub struct StateLog {
+    file: File,
+    path: PathBuf,
+    sync: bool,
+    records: u64,
+    max_records: u64,
+    latest_micros: Option<u64>,
+}
+
+impl StateLog {
+    /// Opens the log at `path`, creating it if needed. A record torn by a
+    /// crash halfway through writing it is dropped.
+    pub fn open(path: impl AsRef<Path>, sync: bool) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let whole = bytes.len() - bytes.len() % 8;
+        if whole != bytes.len() {
+            file.set_len(whole as u64)?;
+        }
+        // 여러 생성기가 한 로그를 쓰면 순서가 섞일 수 있어 최댓값을 사용
+        let latest_micros = bytes[..whole]
+            .chunks_exact(8)
+            .map(|record| u64::from_le_bytes(record.try_into().unwrap()))
+            .max();
+        Ok(StateLog {
+            file,
+            path,
+            sync,
+            records: (whole / 8) as u64,
+            max_records: DEFAULT_MAX_RECORDS,
+            latest_micros,
+        })
+    }
+
+    /// Rewrites the log down to its latest record once it holds `records`
+    /// records; 65 536 by default.
+    pub fn max_records(mut self, records: u64) -> Self {
+        self.max_records = records.max(1);
+        self
+    }
+
+    /// Unix microseconds of the latest tick recorded.
+    pub fn latest_micros(&self) -> Option<u64> {
+        self.latest_micros
+    }
+
+    pub(crate) fn append(&mut self, micros: u64) -> io::Result<()> {
+        let latest = self
+            .latest_micros
+            .map_or(micros, |latest| latest.max(micros));
+        if self.records >= self.max_records {
+            self.compact(latest)?;
+        } else {
+            self.file.write_all(&micros.to_le_bytes())?;
+            if self.sync {
+                self.file.sync_data()?;
+            }
+            self.records += 1;
+        }
+        self.latest_micros = Some(latest);
+        Ok(())
+    }
+
+    fn compact(&mut self, latest: u64) -> io::Result<()> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(&latest.to_le_bytes())?;
+        tmp.sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.records = 1;
+        Ok(())
+    }
+}
+
+impl<C: Clock> Switflake<C> {
+    /// Creates a generator for `node_id` that keeps appending to `log` and
+    /// skips every tick up to and including the latest one recorded, so
+    /// after a crash it never repeats an ID the previous generator may
+    /// have issued. Until `clock` is past that tick generation fails, with
+    /// [`SwitflakeError::ClockMovedBackwards`] before it and
+    /// [`SwitflakeError::SequenceExhausted`] during it: the rest of the
+    /// recorded tick is lost even if it was barely used.
+    pub fn resume_from_log(node_id: u64, log: StateLog, clock: C) -> Result<Self, SwitflakeError> {
+        let latest_micros = log.latest_micros();
+        let mut swit = SwitflakeBuilder::new()
+            .node_id(node_id)
+            .clock(clock)
+            .state_log(log)
+            .build()?;
+        if let Some(micros) = latest_micros {
+            let ticks = micros / swit.time_unit.as_micros();
+            if let Some(timestamp) = ticks.checked_sub(swit.epoch_ticks()) {
+                swit.last_timestamp = timestamp;
+                swit.local_counter = swit.layout.counter_mask() as crate::layout::Counter;
+                swit.borrowed = crate::layout::mask(swit.adaptive_node_bits);
+                swit.refresh_prefix();
+            }
+        }
+        Ok(swit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("switflake-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_resumed_generator_skips_logged_ticks() {
+        let _guard = serial_guard();
+        let path = temp_path("state.log");
+        let _ = std::fs::remove_file(&path);
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .state_log(StateLog::open(&path, true).unwrap())
+            .build()
+            .expect("Failed to create Switflake");
+        let mut last = 0;
+        for _ in 0..5 {
+            for _ in 0..10 {
+                last = swit.generate_id().expect("Failed to generate ID");
+            }
+            clock.advance(1);
+        }
+        let last_tick = swit.layout().timestamp_of(last);
+        // 틱마다 한 번만 기록
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 5 * 8);
+        // 충돌을 흉내 내어 정리 없이 버림
+        std::mem::forget(swit);
+        crate::ThreadIdPool::global().release_all_for_test();
+
+        clock.set(1_700_000_000_004);
+        let log = StateLog::open(&path, true).unwrap();
+        assert_eq!(log.latest_micros(), Some(last_tick * 1_000));
+        let mut resumed =
+            Switflake::resume_from_log(1, log, clock.clone()).expect("Failed to resume Switflake");
+        assert_eq!(
+            resumed.generate_id(),
+            Err(SwitflakeError::SequenceExhausted)
+        );
+        clock.advance(1);
+        let id = resumed.generate_id().expect("Failed to generate ID");
+        assert!(resumed.layout().timestamp_of(id) > last_tick);
+        assert!(id > last);
+        drop(resumed);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_log_compacts_to_latest_record() {
+        let path = temp_path("compact.log");
+        let _ = std::fs::remove_file(&path);
+        let mut log = StateLog::open(&path, false).unwrap().max_records(4);
+        for micros in 1..=10 {
+            log.append(micros * 1_000).unwrap();
+        }
+        drop(log);
+        assert!(std::fs::metadata(&path).unwrap().len() <= 4 * 8);
+
+        // 잘린 레코드는 버림
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0xFF; 3]).unwrap();
+        drop(file);
+        let log = StateLog::open(&path, false).unwrap();
+        assert_eq!(log.latest_micros(), Some(10_000));
+        assert_eq!(std::fs::metadata(&path).unwrap().len() % 8, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+//! Generation metrics reported through the `metrics` facade, and the rare
+//! events worth a log line reported through the `log` facade. Every function
+//! here compiles to nothing unless the `metrics` or `log` feature is enabled.
+
+#[cfg(feature = "metrics")]
+pub(crate) const IDS_GENERATED: &str = "switflake_ids_generated_total";
+#[cfg(feature = "metrics")]
+pub(crate) const SEQUENCE_EXHAUSTED: &str = "switflake_sequence_exhausted_total";
+#[cfg(feature = "metrics")]
+pub(crate) const COUNTER_WIDENED: &str = "switflake_counter_widened_total";
+#[cfg(feature = "metrics")]
+pub(crate) const CLOCK_FALLBACK: &str = "switflake_clock_fallback_total";
+#[cfg(feature = "metrics")]
+pub(crate) const RESERVED_IDS_LOST: &str = "switflake_reserved_ids_lost_total";
+#[cfg(feature = "metrics")]
+pub(crate) const POOL_SLOTS_USED: &str = "switflake_pool_slots_used";
+
+#[inline(always)]
+pub(crate) fn id_generated() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(IDS_GENERATED).increment(1);
+}
+
+#[inline(always)]
+pub(crate) fn sequence_exhausted() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(SEQUENCE_EXHAUSTED).increment(1);
+}
+
+#[inline(always)]
+pub(crate) fn counter_widened() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(COUNTER_WIDENED).increment(1);
+}
+
+#[inline(always)]
+pub(crate) fn clock_fallback() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(CLOCK_FALLBACK).increment(1);
+}
+
+/// Logs that a [`ClockFallback`](crate::ClockFallback) switched to its
+/// monotonic source because the primary clock failed with `_error`.
+#[inline(always)]
+pub(crate) fn clock_fallback_started(_error: &crate::SwitflakeError) {
+    #[cfg(feature = "log")]
+    ::log::warn!(
+        "primary clock failed ({}), falling back to monotonic time",
+        _error
+    );
+}
+
+#[inline(always)]
+pub(crate) fn reserved_ids_lost(_count: u64) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(RESERVED_IDS_LOST).increment(_count);
+}
+
+#[inline(always)]
+pub(crate) fn pool_slots_used(_bitmask: u64) {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!(POOL_SLOTS_USED).set(_bitmask.count_ones() as f64);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    fn value_of<'a>(snapshot: &'a [(String, DebugValue)], name: &str) -> Option<&'a DebugValue> {
+        snapshot
+            .iter()
+            .find(|(key, _)| key.as_str() == name)
+            .map(|(_, value)| value)
+    }
+
+    #[test]
+    fn test_counters_move_after_generating() {
+        let _guard = serial_guard();
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let mut swit = Switflake::builder()
+                .clock(MockClock::at(1_700_000_000_000))
+                .build()
+                .expect("Failed to create Switflake");
+            for _ in 0..255 {
+                let _ = swit.generate_id().expect("Failed to generate ID");
+            }
+            assert!(swit.generate_id().is_err());
+        });
+
+        let snapshot: Vec<(String, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key.key().name().to_string(), value))
+            .collect();
+        assert_eq!(
+            value_of(&snapshot, IDS_GENERATED),
+            Some(&DebugValue::Counter(255))
+        );
+        assert_eq!(
+            value_of(&snapshot, SEQUENCE_EXHAUSTED),
+            Some(&DebugValue::Counter(1))
+        );
+        // 생성기가 drop된 뒤 게이지는 0으로 돌아옴
+        assert!(matches!(
+            value_of(&snapshot, POOL_SLOTS_USED),
+            Some(DebugValue::Gauge(v)) if v.into_inner() == 0.0
+        ));
+    }
+}
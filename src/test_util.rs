@@ -0,0 +1,82 @@
+//! Assertions for crates that generate IDs with switflake and want to check
+//! the same invariants this crate tests internally. Enabled by the
+//! `test-util` feature; meant for tests, not production code.
+
+use std::collections::HashMap;
+
+use crate::{decompose, Layout};
+
+/// Panics if any ID in `ids` appears more than once, naming the first
+/// repeat and both positions.
+#[track_caller]
+pub fn assert_unique(ids: &[u64]) {
+    let mut seen = HashMap::with_capacity(ids.len());
+    for (i, &id) in ids.iter().enumerate() {
+        if let Some(first) = seen.insert(id, i) {
+            panic!(
+                "duplicate ID {} at positions {} and {} (of {} IDs)",
+                id,
+                first,
+                i,
+                ids.len()
+            );
+        }
+    }
+}
+
+/// Panics unless the IDs of every node, taken in slice order, are strictly
+/// increasing under `layout`. IDs of different nodes may interleave freely.
+#[track_caller]
+pub fn assert_monotonic_per_node(ids: &[u64], layout: &Layout) {
+    let mut last_per_node: HashMap<u64, (usize, u64)> = HashMap::new();
+    for (i, &id) in ids.iter().enumerate() {
+        let node = decompose(id, layout).node_id;
+        if let Some((j, previous)) = last_per_node.insert(node, (i, id)) {
+            if id <= previous {
+                panic!(
+                    "IDs of node {} not increasing: {} at position {} follows {} at position {}",
+                    node, id, i, previous, j
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[test]
+    fn test_generated_ids_pass() {
+        let _guard = serial_guard();
+        let mut a = Switflake::new(1).expect("Failed to create Switflake");
+        let mut b = Switflake::new(2).expect("Failed to create Switflake");
+        let mut ids = Vec::new();
+        for _ in 0..100 {
+            ids.push(a.generate_id_blocking().expect("Failed to generate ID"));
+            ids.push(b.generate_id_blocking().expect("Failed to generate ID"));
+        }
+        assert_unique(&ids);
+        assert_monotonic_per_node(&ids, &a.layout());
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate ID 42 at positions 0 and 2")]
+    fn test_duplicate_panics() {
+        assert_unique(&[42, 7, 42]);
+    }
+
+    #[test]
+    #[should_panic(expected = "IDs of node 3 not increasing")]
+    fn test_regression_panics() {
+        let layout = Layout::default();
+        let ids = [
+            layout.pack(100, 3, 0, 0),
+            layout.pack(100, 4, 0, 0),
+            layout.pack(99, 3, 0, 0),
+        ];
+        assert_monotonic_per_node(&ids, &layout);
+    }
+}
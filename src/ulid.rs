@@ -0,0 +1,68 @@
+//! One-way bridge from switflake IDs to the ULID format.
+//!
+//! A ULID is 128 bits: a 48-bit millisecond Unix timestamp followed by 80
+//! random bits, written as 26 characters of Crockford base32. Converting a
+//! switflake ID keeps its timestamp, so ULID-sorted data stays in
+//! chronological order, but the "random" part is just the ID's node and
+//! sequence bits. There is no way back: arbitrary ULIDs don't fit in 64 bits.
+
+use crate::encoding::BASE32_ALPHABET;
+use crate::Layout;
+
+/// Repacks `id`, read with the default layout and Unix epoch, into ULID
+/// bytes: the timestamp goes into the 48-bit time field and the node and
+/// sequence bits into the low end of the random field.
+pub fn to_ulid_like(id: u64) -> [u8; 16] {
+    let layout = Layout::default();
+    let time = layout.timestamp_of(id) & ((1 << 48) - 1);
+    let rest = id & ((1 << layout.timestamp_shift) - 1);
+    (((time as u128) << 80) | rest as u128).to_be_bytes()
+}
+
+/// Formats ULID bytes as the canonical 26-character Crockford base32 string.
+pub fn format_ulid(ulid: &[u8; 16]) -> String {
+    let mut value = u128::from_be_bytes(*ulid);
+    let mut out = [0u8; 26];
+    for digit in out.iter_mut().rev() {
+        *digit = BASE32_ALPHABET[(value & 0x1F) as usize];
+        value >>= 5;
+    }
+    // 알파벳은 모두 ASCII
+    String::from_utf8(out.to_vec()).expect("alphabet is ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_base32;
+    use crate::pool::serial_guard;
+    use crate::Switflake;
+
+    #[test]
+    fn test_ulid_string_keeps_timestamp() {
+        let _guard = serial_guard();
+        let mut swit = Switflake::new(12).expect("Failed to create Switflake");
+        let (id, parts) = swit.generate_decomposed().expect("Failed to generate ID");
+
+        let ulid = to_ulid_like(id);
+        let text = format_ulid(&ulid);
+        assert_eq!(text.len(), 26);
+        // 앞 10자리가 48비트 시간 필드
+        assert_eq!(decode_base32(&text[..10]), Ok(parts.timestamp));
+        assert_eq!(
+            u128::from_be_bytes(ulid) & ((1 << 23) - 1),
+            (id & ((1 << 23) - 1)) as u128
+        );
+    }
+
+    #[test]
+    fn test_ulid_strings_sort_like_ids() {
+        let earlier = (1_700_000_000_000u64 << 23) | (4095 << 11) | 0x7FF;
+        let later = 1_700_000_000_001u64 << 23;
+        let a = format_ulid(&to_ulid_like(earlier));
+        let b = format_ulid(&to_ulid_like(later));
+        assert!(a < b);
+        assert_eq!(format_ulid(&[0; 16]), "0".repeat(26));
+        assert_eq!(format_ulid(&[0xFF; 16]), format!("7{}", "Z".repeat(25)));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::obfuscate::mix;
+
+/// Outcome of [`StreamingUniquenessChecker::observe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateVerdict {
+    /// The ID was certainly not seen within the window.
+    Unique,
+    /// The ID was probably seen before; with the configured probability it
+    /// is a false positive.
+    LikelyDuplicate,
+}
+
+/// Flags repeated IDs in an endless stream using constant memory, for
+/// long-running validation where a `HashSet` of every ID would not fit.
+///
+/// Two Bloom filters take turns: IDs go into the current one, lookups check
+/// both, and once the current one holds `window` IDs the older is wiped and
+/// becomes the current one. A repeat is therefore always caught while at
+/// most `window` other IDs separate it from the first sighting, and may be
+/// missed after `2 * window`. A unique ID is misreported as a duplicate with
+/// probability at most `false_positive_rate`. Memory is fixed at
+/// construction, about 7.5 MB for a window of a million IDs at a
+/// one-in-a-million rate.
+#[derive(Debug, Clone)]
+pub struct StreamingUniquenessChecker {
+    filters: [Box<[u64]>; 2],
+    current: usize,
+    inserted: usize,
+    window: usize,
+    hashes: u32,
+}
+
+impl StreamingUniquenessChecker {
+    /// # Panics
+    ///
+    /// Panics if `window` is 0 or `false_positive_rate` is not strictly
+    /// between 0 and 1.
+    pub fn new(window: usize, false_positive_rate: f64) -> Self {
+        assert!(window > 0, "window must be non-zero");
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false_positive_rate must be between 0 and 1"
+        );
+        // 두 필터를 모두 조회하므로 각 필터는 절반의 오탐률로 설계
+        let per_filter = false_positive_rate / 2.0;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(window as f64) * per_filter.ln() / (ln2 * ln2)).ceil() as usize;
+        let words = (bits + 63) / 64;
+        let hashes = ((words * 64) as f64 / window as f64 * ln2).round().max(1.0) as u32;
+        StreamingUniquenessChecker {
+            filters: [
+                vec![0; words].into_boxed_slice(),
+                vec![0; words].into_boxed_slice(),
+            ],
+            current: 0,
+            inserted: 0,
+            window,
+            hashes,
+        }
+    }
+
+    /// Records `id` and reports whether it was seen before.
+    pub fn observe(&mut self, id: u64) -> DuplicateVerdict {
+        if self.inserted == self.window {
+            self.current ^= 1;
+            self.filters[self.current].fill(0);
+            self.inserted = 0;
+        }
+        let seen = self.contains(0, id) || self.contains(1, id);
+        if !seen {
+            let bits = self.filters[0].len() as u64 * 64;
+            let filter = &mut self.filters[self.current];
+            for bit in probes(id, self.hashes, bits) {
+                filter[(bit / 64) as usize] |= 1 << (bit % 64);
+            }
+            self.inserted += 1;
+        }
+        if seen {
+            DuplicateVerdict::LikelyDuplicate
+        } else {
+            DuplicateVerdict::Unique
+        }
+    }
+
+    fn contains(&self, filter: usize, id: u64) -> bool {
+        let filter = &self.filters[filter];
+        probes(id, self.hashes, filter.len() as u64 * 64)
+            .all(|bit| filter[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+/// Bit positions for `id` by double hashing.
+#[inline]
+fn probes(id: u64, hashes: u32, bits: u64) -> impl Iterator<Item = u64> {
+    let h1 = mix(id);
+    let h2 = mix(id ^ 0x9E37_79B9_7F4A_7C15) | 1;
+    (0..hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{Switflake, SwitflakeError};
+
+    #[test]
+    fn test_unique_stream_stays_within_false_positive_rate() {
+        let _guard = serial_guard();
+        let clock = MockClock::at(1_700_000_000_000);
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        let mut checker = StreamingUniquenessChecker::new(50_000, 0.001);
+
+        const TOTAL: usize = 300_000;
+        let mut false_positives = 0;
+        let mut seen = 0;
+        while seen < TOTAL {
+            match swit.generate_id() {
+                Ok(id) => {
+                    seen += 1;
+                    if checker.observe(id) == DuplicateVerdict::LikelyDuplicate {
+                        false_positives += 1;
+                    }
+                }
+                Err(SwitflakeError::SequenceExhausted) => clock.advance(1),
+                Err(e) => panic!("Failed to generate ID: {}", e),
+            }
+        }
+        // 기대치 300개 이하, 여유를 두고 검사
+        assert!(
+            false_positives <= 450,
+            "{} false positives",
+            false_positives
+        );
+    }
+
+    #[test]
+    fn test_planted_duplicate_detected() {
+        let mut checker = StreamingUniquenessChecker::new(1_000, 1e-6);
+        let ids: Vec<u64> = (0..5_000u64)
+            .map(|i| (1_700_000_000_000 + i) << 23)
+            .collect();
+        for (i, &id) in ids.iter().enumerate() {
+            assert_eq!(checker.observe(id), DuplicateVerdict::Unique);
+            // 창 안에서 되풀이된 ID는 항상 잡힘
+            if i >= 500 {
+                assert_eq!(
+                    checker.observe(ids[i - 500]),
+                    DuplicateVerdict::LikelyDuplicate
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Process-wide check that no generator emits a timestamp older than one
+//! any other generator has already used. Only compiled with `paranoid`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static HIGH_WATER: AtomicU64 = AtomicU64::new(0);
+
+/// The latest tick start any generator in this process has used, to be
+/// taken before reading the clock and passed to [`observe`] with the
+/// reading.
+#[inline]
+pub(crate) fn snapshot() -> u64 {
+    HIGH_WATER.load(Ordering::Acquire)
+}
+
+/// Records `unix_micros`, the start of the tick being used, and in debug
+/// builds panics if it is older than `snapshot`, a tick another generator
+/// in this process had already used before the clock was read. That points
+/// at clocks that disagree or a clock that was stepped back between
+/// generators; a generator that used a later tick after the read is no
+/// regression.
+#[inline]
+pub(crate) fn observe(snapshot: u64, unix_micros: u64) {
+    debug_assert!(
+        unix_micros >= snapshot,
+        "timestamp regressed across generators: {} after {}",
+        unix_micros,
+        snapshot
+    );
+    HIGH_WATER.fetch_max(unix_micros, Ordering::AcqRel);
+}
+
+#[cfg(test)]
+pub(crate) fn reset() {
+    HIGH_WATER.store(0, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::MockClock;
+    use crate::pool::serial_guard;
+    use crate::{Clock, Switflake, SwitflakeError};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    #[should_panic(expected = "timestamp regressed across generators")]
+    fn test_rewound_generator_trips_check() {
+        let _guard = serial_guard();
+        let build = |clock: &MockClock| {
+            Switflake::builder()
+                .clock(clock.clone())
+                .build()
+                .expect("Failed to create Switflake")
+        };
+        let ahead = MockClock::at(1_700_000_000_000);
+        let behind = MockClock::at(1_700_000_000_000);
+        let mut a = build(&ahead);
+        let mut b = build(&behind);
+        a.generate_id().expect("Failed to generate ID");
+        b.generate_id().expect("Failed to generate ID");
+
+        ahead.advance(10);
+        a.generate_id().expect("Failed to generate ID");
+        // b 자신의 시계로는 앞으로 갔지만 a가 이미 쓴 시각보다는 과거
+        behind.set(1_700_000_000_005);
+        let _ = b.generate_id();
+    }
+
+    #[test]
+    fn test_later_tick_used_during_clock_read_is_no_regression() {
+        // 시계를 읽는 사이 다른 생성기가 더 늦은 틱을 사용함
+        #[derive(Clone, Default)]
+        struct Racing(Arc<AtomicBool>);
+        impl Clock for Racing {
+            fn now_millis(&self) -> Result<u64, SwitflakeError> {
+                if self.0.swap(false, Ordering::SeqCst) {
+                    super::observe(super::snapshot(), 1_700_000_000_001_000);
+                }
+                Ok(1_700_000_000_000)
+            }
+        }
+        let _guard = serial_guard();
+        super::reset();
+        let clock = Racing::default();
+        let mut swit = Switflake::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to create Switflake");
+        clock.0.store(true, Ordering::SeqCst);
+        swit.generate_id().expect("Failed to generate ID");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{telemetry, Clock, SlotLease, SwitflakeError, SystemClock, ThreadIdPool};
+
+const COUNTER_BITS: u32 = 29;
+const COUNTER_MASK: u32 = (1 << COUNTER_BITS) - 1;
+
+/// The individual fields of a 128-bit ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecomposedId128 {
+    pub timestamp: u64,
+    pub node_id: u32,
+    pub thread_id: u8,
+    pub counter: u32,
+}
+
+/// 128-bit variant of [`Switflake`](crate::Switflake).
+///
+/// Bit layout, most significant first:
+/// - 64 bits: full millisecond Unix timestamp.
+/// - 32 bits: node id.
+/// - 32 bits: sequence, a 3-bit thread id from the shared [`ThreadIdPool`]
+///   followed by a 29-bit counter that restarts every millisecond.
+pub struct Switflake128 {
+    node_id: u32,
+    thread_id: u8,
+    generation: u32,
+    last_timestamp: u64,
+    local_counter: u32,
+}
+
+impl Switflake128 {
+    #[must_use = "dropping the generator frees its pool slot right away"]
+    pub fn new(node_id: u32) -> Result<Self, SwitflakeError> {
+        let lease = ThreadIdPool::global().acquire()?;
+        Ok(Switflake128 {
+            node_id,
+            thread_id: lease.thread_id,
+            generation: lease.generation,
+            last_timestamp: 0,
+            local_counter: 0,
+        })
+    }
+
+    #[inline]
+    #[must_use = "a dropped ID still uses up a sequence slot"]
+    pub fn generate_id(&mut self) -> Result<u128, SwitflakeError> {
+        let timestamp = SystemClock.now_millis()?;
+        if timestamp < self.last_timestamp {
+            return Err(SwitflakeError::ClockMovedBackwards);
+        }
+        if timestamp > self.last_timestamp {
+            self.last_timestamp = timestamp;
+            self.local_counter = 0;
+        } else if self.local_counter > COUNTER_MASK {
+            telemetry::sequence_exhausted();
+            return Err(SwitflakeError::SequenceExhausted);
+        }
+
+        let id = compose128(&DecomposedId128 {
+            timestamp,
+            node_id: self.node_id,
+            thread_id: self.thread_id,
+            counter: self.local_counter,
+        });
+        self.local_counter += 1;
+        telemetry::id_generated();
+        Ok(id)
+    }
+}
+
+impl Drop for Switflake128 {
+    fn drop(&mut self) {
+        ThreadIdPool::global().force_release(SlotLease {
+            thread_id: self.thread_id,
+            generation: self.generation,
+        });
+    }
+}
+
+#[inline]
+fn compose128(parts: &DecomposedId128) -> u128 {
+    let sequence = ((parts.thread_id as u32) << COUNTER_BITS) | (parts.counter & COUNTER_MASK);
+    ((parts.timestamp as u128) << 64) | ((parts.node_id as u128) << 32) | sequence as u128
+}
+
+/// Splits a 128-bit ID into its fields.
+#[inline]
+pub fn decompose128(id: u128) -> DecomposedId128 {
+    let sequence = id as u32;
+    DecomposedId128 {
+        timestamp: (id >> 64) as u64,
+        node_id: (id >> 32) as u32,
+        thread_id: (sequence >> COUNTER_BITS) as u8,
+        counter: sequence & COUNTER_MASK,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::serial_guard;
+    use std::collections::HashSet;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_unique_far_beyond_256_per_ms() {
+        let _guard = serial_guard();
+        let mut swit = Switflake128::new(7).expect("Failed to create Switflake128");
+        let mut ids = HashSet::new();
+        let mut per_ms: std::collections::HashMap<u64, u32> = Default::default();
+        for _ in 0..100_000 {
+            let id = swit.generate_id().expect("Failed to generate ID");
+            assert!(ids.insert(id), "Duplicate ID found: {}", id);
+            *per_ms.entry(decompose128(id).timestamp).or_default() += 1;
+        }
+        assert!(
+            per_ms.values().any(|&n| n > 256),
+            "Expected more than 256 IDs in at least one millisecond"
+        );
+    }
+
+    #[test]
+    fn test_extended_timestamp_decodes() {
+        // 41비트 범위를 훨씬 넘는 타임스탬프
+        let parts = DecomposedId128 {
+            timestamp: u64::MAX - 1,
+            node_id: 0xDEAD_BEEF,
+            thread_id: 7,
+            counter: COUNTER_MASK,
+        };
+        assert_eq!(decompose128(compose128(&parts)), parts);
+
+        let _guard = serial_guard();
+        let before = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let mut swit = Switflake128::new(u32::MAX).expect("Failed to create Switflake128");
+        let decoded = decompose128(swit.generate_id().expect("Failed to generate ID"));
+        assert!(decoded.timestamp >= before);
+        assert_eq!(decoded.node_id, u32::MAX);
+        assert_eq!(decoded.counter, 0);
+    }
+}
@@ -0,0 +1,6 @@
+// Compiler diagnostics the public API is expected to produce.
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,10 @@
+#![deny(unused_must_use)]
+
+use switflake::{encode_base62, Switflake};
+
+fn main() {
+    let mut swit = Switflake::new(1).unwrap();
+    swit.generate_id();
+    unsafe { swit.generate_id_unchecked() };
+    encode_base62(42);
+}